@@ -1,38 +1,83 @@
-use super::cebra::CeBrARunTimeSettings;
-use super::icespice::ICESPICERunTimeSettings;
-use super::sps::SPSRunTimeSettings;
+use super::persistence;
+use super::project::{Project, ViewSettings};
+use super::report::{self, ReportFormat};
+use super::scenario::{self, Scenario};
 use eframe::egui::{self};
 use eframe::App;
 
+/// Saved state that could not be restored, kept so it can be written back under
+/// [`persistence::BACKUP_KEY`] instead of being overwritten.
+#[derive(Clone, Debug)]
+struct UnrestoredState {
+    blob: String,
+    error: String,
+    dismissed: bool,
+}
+
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct BeamTimeApp {
-    sps_settings: SPSRunTimeSettings,
-    cebra_settings: CeBrARunTimeSettings,
-    icespice_settings: ICESPICERunTimeSettings,
+    scenarios: Vec<Scenario>,
+    active_scenario: usize,
     show_sps: bool,
     show_cebra: bool,
     show_icespice: bool,
+    show_comparison: bool,
     window: bool,
+    #[serde(skip)]
+    project_path: Option<std::path::PathBuf>,
+    #[serde(skip)]
+    project_error: Option<String>,
+    #[serde(skip)]
+    migration_notes: Vec<String>, // guesses made upgrading saved settings or a project
+    #[serde(skip)]
+    unrestored_state: Option<UnrestoredState>,
 }
 
 impl Default for BeamTimeApp {
     fn default() -> Self {
         Self {
-            sps_settings: SPSRunTimeSettings::default(),
-            cebra_settings: CeBrARunTimeSettings::default(),
-            icespice_settings: ICESPICERunTimeSettings::default(),
+            scenarios: vec![Scenario::default()],
+            active_scenario: 0,
             show_sps: true,
             show_cebra: true,
             show_icespice: true,
+            show_comparison: false,
             window: false,
+            project_path: None,
+            project_error: None,
+            migration_notes: vec![],
+            unrestored_state: None,
         }
     }
 }
 
 impl BeamTimeApp {
     pub fn new(cc: &eframe::CreationContext<'_>, window: bool) -> Self {
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        if let Some(blob) = cc.storage.and_then(|s| s.get_string(eframe::APP_KEY)) {
+            match persistence::restore(&blob) {
+                Ok((app, notes)) => {
+                    for note in &notes {
+                        log::warn!("Upgraded saved settings: {note}");
+                    }
+                    return Self {
+                        migration_notes: notes,
+                        ..app
+                    };
+                }
+                Err(error) => {
+                    log::warn!("Could not restore saved settings: {error}");
+                    return Self {
+                        window,
+                        unrestored_state: Some(UnrestoredState {
+                            blob,
+                            error,
+                            dismissed: false,
+                        }),
+                        ..Default::default()
+                    };
+                }
+            }
         }
 
         Self {
@@ -41,41 +86,302 @@ impl BeamTimeApp {
         }
     }
 
+    fn unrestored_state_ui(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.unrestored_state else {
+            return;
+        };
+        if state.dismissed {
+            return;
+        }
+
+        egui::Window::new("Saved Settings Not Restored")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Your saved settings could not be loaded, so the defaults are shown.");
+                ui.colored_label(ui.visuals().error_fg_color, &state.error);
+                ui.label(format!(
+                    "The old settings are kept in the app storage under \"{}\" and will not be overwritten.",
+                    persistence::BACKUP_KEY
+                ));
+                ui.horizontal(|ui| {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.button("Save Copy...").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .set_file_name("unrestored_settings.ron")
+                            .save_file()
+                        {
+                            if let Err(e) = std::fs::write(&path, &state.blob) {
+                                state.error = format!("Could not write {}: {e}", path.display());
+                            }
+                        }
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        state.dismissed = true;
+                    }
+                });
+            });
+    }
+
+    pub fn project(&self) -> Project {
+        Project {
+            view: ViewSettings {
+                show_sps: self.show_sps,
+                show_cebra: self.show_cebra,
+                show_icespice: self.show_icespice,
+                show_comparison: self.show_comparison,
+            },
+            active_scenario: self.active_scenario,
+            scenarios: self.scenarios.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn apply_project(&mut self, project: Project) {
+        self.show_sps = project.view.show_sps;
+        self.show_cebra = project.view.show_cebra;
+        self.show_icespice = project.view.show_icespice;
+        self.show_comparison = project.view.show_comparison;
+        self.active_scenario = project.active_scenario;
+        self.scenarios = project.scenarios;
+    }
+
+    fn scenario_ui(&mut self, ui: &mut egui::Ui) {
+        if self.scenarios.is_empty() {
+            self.scenarios.push(Scenario::default());
+        }
+        self.active_scenario = self.active_scenario.min(self.scenarios.len() - 1);
+
+        ui.horizontal(|ui| {
+            ui.label("Scenario:");
+            egui::ComboBox::from_id_source("scenario_selector")
+                .selected_text(self.scenarios[self.active_scenario].name.clone())
+                .show_ui(ui, |ui| {
+                    for (index, scenario) in self.scenarios.iter().enumerate() {
+                        ui.selectable_value(&mut self.active_scenario, index, &scenario.name);
+                    }
+                });
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.scenarios[self.active_scenario].name)
+                    .desired_width(150.0),
+            )
+            .on_hover_text("Rename the scenario.");
+
+            if ui.button("New").clicked() {
+                let name = scenario::unique_name(&self.scenarios, "Scenario");
+                self.scenarios.push(Scenario::new(&name));
+                self.active_scenario = self.scenarios.len() - 1;
+            }
+
+            if ui.button("Duplicate").clicked() {
+                let mut copy = self.scenarios[self.active_scenario].clone();
+                copy.name = scenario::unique_name(&self.scenarios, &copy.name);
+                self.scenarios.insert(self.active_scenario + 1, copy);
+                self.active_scenario += 1;
+            }
+
+            if ui
+                .add_enabled(self.scenarios.len() > 1, egui::Button::new("Delete"))
+                .clicked()
+            {
+                self.scenarios.remove(self.active_scenario);
+                self.active_scenario = self.active_scenario.saturating_sub(1);
+            }
+        });
+    }
+
+    fn comparison_ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Scenario Comparison")
+            .open(&mut self.show_comparison)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let highlight = ui.visuals().warn_fg_color;
+                egui::Grid::new("scenario_comparison_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        for scenario in &self.scenarios {
+                            ui.strong(&scenario.name);
+                        }
+                        ui.end_row();
+
+                        let mut section = "";
+                        for row in scenario::compare(&self.scenarios) {
+                            if row.section != section {
+                                section = row.section;
+                                ui.heading(section);
+                                ui.end_row();
+                            }
+
+                            ui.label(row.label);
+                            for (column, value) in row.values.iter().enumerate() {
+                                if row.differs_from_first(column) {
+                                    ui.colored_label(highlight, value)
+                                        .on_hover_text("Differs from the first scenario.");
+                                } else {
+                                    ui.label(value);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn project_dialog() -> rfd::FileDialog {
+        rfd::FileDialog::new().add_filter("Beam time project", &["toml"])
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_project(&mut self) {
+        let Some(path) = Self::project_dialog().pick_file() else {
+            return;
+        };
+        match Project::load(&path) {
+            Ok(mut project) => {
+                self.migration_notes = std::mem::take(&mut project.migration_notes);
+                self.apply_project(project);
+                self.project_path = Some(path);
+                self.project_error = None;
+            }
+            Err(e) => self.project_error = Some(format!("Could not open {}: {e}", path.display())),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_project(&mut self, save_as: bool) {
+        let path = match &self.project_path {
+            Some(path) if !save_as => path.clone(),
+            _ => match Self::project_dialog()
+                .set_file_name("beam_time.toml")
+                .save_file()
+            {
+                Some(path) => path,
+                None => return,
+            },
+        };
+        match self.project().save(&path) {
+            Ok(()) => {
+                self.project_path = Some(path);
+                self.project_error = None;
+            }
+            Err(e) => self.project_error = Some(format!("Could not save {}: {e}", path.display())),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_report(&mut self, format: ReportFormat) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(format.name(), &[format.extension()])
+            .set_file_name(format!("beam_time_report.{}", format.extension()))
+            .save_file()
+        else {
+            return;
+        };
+        if let Err(e) = std::fs::write(&path, report::generate(&self.project(), format)) {
+            self.project_error = Some(format!("Could not write {}: {e}", path.display()));
+        }
+    }
+
     fn ui(&mut self, ui: &mut egui::Ui) {
         egui::menu::bar(ui, |ui| {
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.menu_button("File", |ui| {
+                if ui.button("Open...").clicked() {
+                    ui.close_menu();
+                    self.open_project();
+                }
+                if ui.button("Save").clicked() {
+                    ui.close_menu();
+                    self.save_project(false);
+                }
+                if ui.button("Save As...").clicked() {
+                    ui.close_menu();
+                    self.save_project(true);
+                }
+                ui.separator();
+                ui.menu_button("Export Report", |ui| {
+                    for format in ReportFormat::ALL {
+                        if ui.button(format!("{}...", format.name())).clicked() {
+                            ui.close_menu();
+                            self.export_report(format);
+                        }
+                    }
+                });
+            });
+
             ui.menu_button("View", |ui| {
                 ui.checkbox(&mut self.show_sps, "Show SPS Estimator");
                 ui.checkbox(&mut self.show_cebra, "Show CeBrA Estimator");
                 ui.checkbox(&mut self.show_icespice, "Show ICESPICE Estimator");
+                ui.separator();
+                ui.checkbox(&mut self.show_comparison, "Compare Scenarios");
             });
+
+            if let Some(path) = &self.project_path {
+                ui.label(path.display().to_string());
+            }
         });
 
+        if let Some(error) = self.project_error.clone() {
+            ui.horizontal(|ui| {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+                if ui.button("Dismiss").clicked() {
+                    self.project_error = None;
+                }
+            });
+        }
+
+        if !self.migration_notes.is_empty() {
+            ui.horizontal(|ui| {
+                ui.vertical(|ui| {
+                    for note in &self.migration_notes {
+                        ui.colored_label(ui.visuals().warn_fg_color, note);
+                    }
+                });
+                if ui.button("Dismiss").clicked() {
+                    self.migration_notes.clear();
+                }
+            });
+        }
+
+        self.scenario_ui(ui);
+        let scenario = &mut self.scenarios[self.active_scenario];
+
         egui::SidePanel::left("sps_panel")
             .resizable(false)
             .show_animated_inside(ui, self.show_sps, |ui| {
-                self.sps_settings.ui(ui);
+                scenario.sps.ui(ui);
             });
 
         egui::SidePanel::left("cebra_panel")
             .resizable(false)
             .show_animated_inside(ui, self.show_cebra, |ui| {
-                self.cebra_settings.ui(ui);
+                scenario.cebra.ui(ui);
             });
 
         egui::SidePanel::left("icespice_panel")
             .resizable(false)
             .show_animated_inside(ui, self.show_icespice, |ui| {
-                self.icespice_settings.ui(ui);
+                scenario.icespice.ui(ui);
             });
     }
 }
 
 impl App for BeamTimeApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+        if let Some(state) = &self.unrestored_state {
+            storage.set_string(persistence::BACKUP_KEY, state.blob.clone());
+        }
+        persistence::save(storage, self);
     }
 
     fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
+        self.unrestored_state_ui(ctx);
+        self.comparison_ui(ctx);
+
         if self.window {
             egui::Window::new("Beam Time Estimator").show(ctx, |ui| {
                 self.ui(ui);
@@ -1,4 +1,7 @@
+use super::error::{require_in_range, require_non_negative, EstimateError};
+
 #[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct Efficiency {
     pub a: f64,
     pub b: f64,
@@ -14,46 +17,17 @@ impl Efficiency {
     pub fn calculate_efficiency(&self, energy: f64) -> f64 {
         self.a * (-energy / self.b).exp() + self.c * (-energy / self.d).exp()
     }
-
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
-        ui.add(
-            egui::DragValue::new(&mut self.a)
-                // .prefix("a = ")
-                .speed(0.1)
-                .range(0.0..=f64::INFINITY),
-        )
-        .on_hover_text("Efficiency = a * exp(-energy / b) + c * exp(-energy / d)");
-        ui.add(
-            egui::DragValue::new(&mut self.b)
-                // .prefix("b = ")
-                .speed(10.0)
-                .range(0.0..=f64::INFINITY),
-        )
-        .on_hover_text("Efficiency = a * exp(-energy / b) + c * exp(-energy / d)");
-        ui.add(
-            egui::DragValue::new(&mut self.c)
-                // .prefix("c = ")
-                .speed(0.1)
-                .range(0.0..=f64::INFINITY),
-        )
-        .on_hover_text("Efficiency = a * exp(-energy / b) + c * exp(-energy / d)");
-        ui.add(
-            egui::DragValue::new(&mut self.d)
-                // .prefix("d = ")
-                .speed(10.0)
-                .range(0.0..=f64::INFINITY),
-        )
-        .on_hover_text("Efficiency = a * exp(-energy / b) + c * exp(-energy / d)");
-    }
 }
 
 #[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct Detector {
     pub name: String,
     pub efficiency: Efficiency,
 }
 
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct Decay {
     pub energy: f64,
     pub absolute_intensity: f64,
@@ -61,6 +35,13 @@ pub struct Decay {
     pub efficiency_corrected_counts: f64,
 }
 
+impl Decay {
+    // Estimated counts = Particle counts * Absolute intensity [%] /100 * Efficiency [%] /100
+    pub fn expected_counts(&self, n_particle_counts: i64, efficiency: f64) -> f64 {
+        n_particle_counts as f64 * self.absolute_intensity / 100.0 * efficiency / 100.0
+    }
+}
+
 impl Default for Decay {
     fn default() -> Self {
         Self {
@@ -73,10 +54,11 @@ impl Default for Decay {
 }
 
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct CeBrARunTimeSettings {
-    n_particle_counts: i64,
-    decay: Decay,
-    detectors: Vec<Detector>,
+    pub n_particle_counts: i64,
+    pub decay: Decay,
+    pub detectors: Vec<Detector>,
 }
 
 impl Default for CeBrARunTimeSettings {
@@ -89,164 +71,211 @@ impl Default for CeBrARunTimeSettings {
     }
 }
 
-impl CeBrARunTimeSettings {
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
-        egui::Grid::new("cebra_runtime_settings_grid")
-            .striped(true)
-            .show(ui, |ui| {
-                ui.heading("CeBrA");
-                ui.end_row();
-                ui.label("Particle Counts:")
-                    .on_hover_text("Number of particles detected in the excited state");
-                ui.add(
-                    egui::DragValue::new(&mut self.n_particle_counts)
-                        .speed(1.0)
-                        .range(0..=i64::MAX),
-                );
-                ui.end_row();
-
-                ui.label("γ Decay");
-                ui.label("Energy");
-                ui.label("Intensity")
-                    .on_hover_text("Absolute intensity of the decay");
-                ui.end_row();
-                ui.label("");
-                ui.add(
-                    egui::DragValue::new(&mut self.decay.energy)
-                        .suffix(" keV")
-                        .speed(10.0)
-                        .range(0.0..=f64::INFINITY),
-                );
-                ui.add(
-                    egui::DragValue::new(&mut self.decay.absolute_intensity)
-                        .suffix(" %")
-                        .speed(0.1)
-                        .range(0.0..=100.0),
-                );
-
-                ui.end_row();
-
-                ui.heading("Detectors");
-
-                if ui.button("+").clicked() {
-                    self.detectors.push(Detector {
-                        name: format!("Detector {}", self.detectors.len() + 1),
-                        efficiency: Efficiency::default(),
-                    });
-                }
-
-                if ui.button("REU-2023").on_hover_text("Efficiency values for the 52Cr(d,pγ)53Cr and 34S(d,pγ)35S experiments that took place in the summer of 2023 during the REU.").clicked() {
-                    self.detectors.clear();
-                    self.detectors.push(Detector {
-                        name: "Detector 0".to_string(),
-                        efficiency: Efficiency::new(1.04342, 313.36388, 0.30550, 2796.19080),
-                    });
-
-                    self.detectors.push(Detector {
-                        name: "Detector 1".to_string(),
-                        efficiency: Efficiency::new(0.91597, 344.80832, 0.26477, 3074.53024),
-                    });
-
-                    self.detectors.push(Detector {
-                        name: "Detector 2".to_string(),
-                        efficiency: Efficiency::new(0.34643, 391.57405, 0.11673, 4392.80188),
-                    });
-
-                    self.detectors.push(Detector {
-                        name: "Detector 3".to_string(),
-                        efficiency: Efficiency::new(0.95401, 292.86782, 0.30357, 2592.23281),
-                    });
-
-                    self.detectors.push(Detector {
-                        name: "Detector 4".to_string(),
-                        efficiency: Efficiency::new(1.69550, 304.59392, 0.93590, 4628.69818),
-                    });
-                }
-
-                if ui.button("Summer-2022").on_hover_text("Efficiency values for the 49Ti(d,pγ)50Ti and 61Ni(d,pγ)62Ni experiments that took place in the summer of 2022 during CeBrA's commissioning run.").clicked() {
-                    self.detectors.clear();
-                    self.detectors.push(Detector {
-                        name: "Detector 0".to_string(),
-                        efficiency: Efficiency::new(3.44699544e-01, 2.49383011e+03, 1.00560372e+00, 3.31802347e+02),
-                    });
-
-                    self.detectors.push(Detector {
-                        name: "Detector 1".to_string(),
-                        efficiency: Efficiency::new(3.31397563e-01, 2.57096172e+03, 1.01446003e+00, 3.45717989e+02),
-                    });
-
-                    self.detectors.push(Detector {
-                        name: "Detector 2".to_string(),
-                        efficiency: Efficiency::new(2.99597105e-01, 2.61859263e+03, 8.69973182e-01, 3.60128895e+02),
-                    });
-
-                    self.detectors.push(Detector {
-                        name: "Detector 3".to_string(),
-                        efficiency: Efficiency::new(3.19707493e-01, 2.37437634e+03, 8.53245327e-01, 3.39245613e+02),
-                    });
-
-                    self.detectors.push(Detector {
-                        name: "Detector 4".to_string(),
-                        efficiency: Efficiency::new(5.22246571e-01, 3.76416626e+03, 7.08680303e-01, 4.16212259e+02),
-                    });
-                }
-
-
-                ui.end_row();
-
-                ui.label("Name");
-                ui.label("a");
-                ui.label("b");
-                ui.label("c");
-                ui.label("d");
-                ui.label("ε(γ)");
-                ui.label("Counts");
-                ui.label("");
-                ui.end_row();
-
-                let mut index_to_remove = None;
-
-                let mut total_efficiency = 0.0;
-                let mut total_counts = 0.0;
-                for (index, detector) in self.detectors.iter_mut().enumerate() {
-                    ui.text_edit_singleline(&mut detector.name);
-                    detector.efficiency.ui(ui);
-
-                    let efficiency = detector.efficiency.calculate_efficiency(self.decay.energy);
-                    ui.label(format!("{:.2} %", efficiency));
+/// Expected γ-ray counts in a single detector.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct DetectorEstimate {
+    pub name: String,
+    pub efficiency: f64, // in percentage
+    pub counts: f64,
+}
 
-                    let expected_counts =
-                        self.n_particle_counts as f64 * self.decay.absolute_intensity / 100.0
-                            * efficiency
-                            / 100.0;
+/// Expected γ-ray counts in every detector and in the whole array.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CeBrAEstimate {
+    pub detectors: Vec<DetectorEstimate>,
+    pub total_efficiency: f64, // in percentage
+    pub total_counts: f64,
+}
 
-                    ui.label(format!("{:.0}", expected_counts)).on_hover_text(
-                        "Estimated counts = Particle counts * Absolute intensity [%] /100 * Efficiency [%] /100",
-                    );
+impl CeBrARunTimeSettings {
+    pub fn validate(&self) -> Result<(), EstimateError> {
+        require_non_negative("Particle counts", self.n_particle_counts as f64)?;
+        require_non_negative("γ-ray energy", self.decay.energy)?;
+        require_in_range(
+            "Absolute intensity",
+            self.decay.absolute_intensity,
+            0.0,
+            100.0,
+        )?;
+        Ok(())
+    }
 
-                    if ui.button("-").clicked() {
-                        index_to_remove = Some(index);
-                    }
-                    ui.end_row();
+    pub fn calculate_counts(&self) -> Result<CeBrAEstimate, EstimateError> {
+        self.validate()?;
+
+        let detectors: Vec<DetectorEstimate> = self
+            .detectors
+            .iter()
+            .map(|detector| {
+                let efficiency = detector.efficiency.calculate_efficiency(self.decay.energy);
+                DetectorEstimate {
+                    name: detector.name.clone(),
+                    efficiency,
+                    counts: self
+                        .decay
+                        .expected_counts(self.n_particle_counts, efficiency),
+                }
+            })
+            .collect();
+
+        Ok(CeBrAEstimate {
+            total_efficiency: detectors.iter().map(|d| d.efficiency).sum(),
+            total_counts: detectors.iter().map(|d| d.counts).sum(),
+            detectors,
+        })
+    }
 
-                    total_efficiency += efficiency;
-                    total_counts += expected_counts;
+    // Efficiency values for the 52Cr(d,pγ)53Cr and 34S(d,pγ)35S experiments (REU, summer 2023)
+    pub fn reu_2023_detectors() -> Vec<Detector> {
+        vec![
+            Detector {
+                name: "Detector 0".to_string(),
+                efficiency: Efficiency::new(1.04342, 313.36388, 0.30550, 2796.19080),
+            },
+            Detector {
+                name: "Detector 1".to_string(),
+                efficiency: Efficiency::new(0.91597, 344.80832, 0.26477, 3074.53024),
+            },
+            Detector {
+                name: "Detector 2".to_string(),
+                efficiency: Efficiency::new(0.34643, 391.57405, 0.11673, 4392.80188),
+            },
+            Detector {
+                name: "Detector 3".to_string(),
+                efficiency: Efficiency::new(0.95401, 292.86782, 0.30357, 2592.23281),
+            },
+            Detector {
+                name: "Detector 4".to_string(),
+                efficiency: Efficiency::new(1.69550, 304.59392, 0.93590, 4628.69818),
+            },
+        ]
+    }
 
-                }
+    // Efficiency values for the 49Ti(d,pγ)50Ti and 61Ni(d,pγ)62Ni experiments (commissioning, summer 2022)
+    pub fn summer_2022_detectors() -> Vec<Detector> {
+        vec![
+            Detector {
+                name: "Detector 0".to_string(),
+                efficiency: Efficiency::new(
+                    3.44699544e-01,
+                    2.49383011e+03,
+                    1.00560372e+00,
+                    3.31802347e+02,
+                ),
+            },
+            Detector {
+                name: "Detector 1".to_string(),
+                efficiency: Efficiency::new(
+                    3.31397563e-01,
+                    2.57096172e+03,
+                    1.01446003e+00,
+                    3.45717989e+02,
+                ),
+            },
+            Detector {
+                name: "Detector 2".to_string(),
+                efficiency: Efficiency::new(
+                    2.99597105e-01,
+                    2.61859263e+03,
+                    8.69973182e-01,
+                    3.60128895e+02,
+                ),
+            },
+            Detector {
+                name: "Detector 3".to_string(),
+                efficiency: Efficiency::new(
+                    3.19707493e-01,
+                    2.37437634e+03,
+                    8.53245327e-01,
+                    3.39245613e+02,
+                ),
+            },
+            Detector {
+                name: "Detector 4".to_string(),
+                efficiency: Efficiency::new(
+                    5.22246571e-01,
+                    3.76416626e+03,
+                    7.08680303e-01,
+                    4.16212259e+02,
+                ),
+            },
+        ]
+    }
+}
 
-                if let Some(index) = index_to_remove {
-                    self.detectors.remove(index);
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                ui.label("");
-                ui.label("");
-                ui.label("");
-                ui.label("");
-                ui.label("Total");
-                ui.label(format!("{:.2} %", total_efficiency));
-                ui.label(format!("{:.0}", total_counts));
-                ui.end_row();
+    #[test]
+    fn counts_follow_the_efficiency_of_every_detector() {
+        let settings = CeBrARunTimeSettings {
+            n_particle_counts: 10000,
+            decay: Decay {
+                energy: 1000.0,
+                absolute_intensity: 50.0,
+                ..Default::default()
+            },
+            detectors: vec![
+                Detector {
+                    name: "Flat".to_string(),
+                    efficiency: Efficiency::new(2.0, f64::INFINITY, 0.0, 1.0),
+                },
+                Detector {
+                    name: "Falling".to_string(),
+                    efficiency: Efficiency::new(1.0, 1000.0, 1.0, 500.0),
+                },
+            ],
+        };
+        let estimate = settings.calculate_counts().unwrap();
+
+        // e^−1 + e^−2 % at 1000 keV
+        let falling = (-1.0f64).exp() + (-2.0f64).exp();
+        assert_eq!(estimate.detectors[0].efficiency, 2.0);
+        assert_eq!(estimate.detectors[0].counts, 100.0); // 10000 · 50 % · 2 %
+        assert_eq!(estimate.detectors[1].name, "Falling");
+        assert!((estimate.detectors[1].efficiency - falling).abs() < 1e-12);
+        assert!((estimate.detectors[1].counts - 50.0 * falling).abs() < 1e-9);
+        assert!((estimate.total_efficiency - (2.0 + falling)).abs() < 1e-12);
+        assert!((estimate.total_counts - 50.0 * (2.0 + falling)).abs() < 1e-9);
+    }
 
-            });
+    #[test]
+    fn invalid_inputs_name_the_parameter() {
+        let settings = CeBrARunTimeSettings {
+            detectors: CeBrARunTimeSettings::reu_2023_detectors(),
+            ..Default::default()
+        };
+        assert_eq!(settings.calculate_counts().unwrap().detectors.len(), 5);
+
+        let mut invalid = settings.clone();
+        invalid.decay.absolute_intensity = 120.0;
+        assert_eq!(
+            invalid.calculate_counts(),
+            Err(EstimateError::OutOfRange {
+                parameter: "Absolute intensity",
+                value: 120.0,
+                min: 0.0,
+                max: 100.0
+            })
+        );
+        let mut invalid = settings.clone();
+        invalid.n_particle_counts = -1;
+        assert_eq!(
+            invalid.calculate_counts(),
+            Err(EstimateError::Negative {
+                parameter: "Particle counts",
+                value: -1.0
+            })
+        );
+        let mut invalid = settings;
+        invalid.decay.energy = -511.0;
+        assert_eq!(
+            invalid.calculate_counts(),
+            Err(EstimateError::Negative {
+                parameter: "γ-ray energy",
+                value: -511.0
+            })
+        );
     }
 }
@@ -0,0 +1,1659 @@
+use super::cebra::{CeBrARunTimeSettings, Detector, Efficiency};
+use super::deck::{Deck, DeckCode};
+use super::dwba::{TransferPoint, TransferState};
+use super::error::EstimateError;
+use super::icespice::ICESPICERunTimeSettings;
+use super::kinematics::{EjectileKinematics, Frame};
+use super::listing::{ImportedCrossSections, ListingError, ListingFormat, ListingPoint};
+use super::nuclide::{self, Nuclide};
+use super::optical::{ElasticPoint, GlobalPotential};
+use super::planner::{CampaignAngle, CampaignGoal, CampaignResult, FieldPlanResult, PlanState};
+use super::project::{Project, ProjectError};
+use super::report::{self, ReportFormat};
+use super::resolution::ExResolution;
+use super::rutherford::RutherfordCrossSection;
+use super::spectrograph::{FieldSetting, FocalPlaneState};
+use super::sps::{
+    spin_name, CrossSectionSource, SPSRunTimeSettings, SPSSolution, SPSUnknown, TransferType,
+};
+use super::stopping::{StoppingError, StoppingTable, TargetEnergyLoss};
+use super::target::{Assay, AssayIsotope, Formula};
+use super::units::{CrossSectionUnit, CurrentUnit, SolidAngleUnit, ThicknessUnit, Unit};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+
+/// Estimate beam time and expected counts without launching the GUI.
+#[derive(Debug, Parser)]
+#[command(name = "sps_beam_time_estimator", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Output format of the results
+    #[arg(long, value_enum, global = true, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run time needed on the SE-SPS for the desired counts
+    Sps(Box<SpsArgs>),
+    /// Expected γ-ray counts in CeBrA
+    Cebra(CeBrAArgs),
+    /// Expected conversion electron counts in ICESPICE
+    Icespice(ICESPICEArgs),
+    /// Proposal report (Markdown, HTML or LaTeX) for a project file
+    Report(ReportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct SpsArgs {
+    /// JSON file with SPS settings, flags override its values
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+    /// Cross section in the cross section unit
+    #[arg(long)]
+    pub cross_section: Option<f64>,
+    /// Cross section unit: b/sr, mb/sr, µb/sr (default, also ub/sr) or nb/sr
+    #[arg(long)]
+    pub cross_section_unit: Option<CrossSectionUnit>,
+    /// Frame of the cross sections; cm converts them at the spectrograph angle
+    #[arg(long, value_enum)]
+    pub cross_section_frame: Option<CrossSectionFrame>,
+    /// Source of the cross section; rutherford and optical-model scatter the beam on the
+    /// target nuclide at --beam-energy and --angle, dwba calculates the transfer to the
+    /// --transfer-* state, imported interpolates the angular distribution of --listing and
+    /// scaled multiplies --single-particle-cross-section by the --scaled-* factors
+    #[arg(long, value_enum)]
+    pub cross_section_source: Option<CrossSectionModel>,
+    /// Target density in the target density unit
+    #[arg(long)]
+    pub target_density: Option<f64>,
+    /// Target density unit: µg/cm^2 (default, also ug/cm^2), mg/cm^2, atoms/cm^2 (all atoms of the formula) or nm
+    #[arg(long)]
+    pub target_density_unit: Option<ThicknessUnit>,
+    /// Target chemical formula, e.g. 208Pb, natPb, 6LiF or CD2; sets the molar mass
+    #[arg(long, value_parser = parse_target)]
+    pub target: Option<String>,
+    /// Nuclide of the target the reaction is on, e.g. 6Li; defaults to the whole target
+    #[arg(long)]
+    pub reaction_nuclide: Option<String>,
+    /// Isotopic assay of an enriched element as EL:A=%,A=%,... (repeatable)
+    #[arg(long = "assay", value_parser = parse_assay)]
+    pub assays: Vec<Assay>,
+    /// Target molar mass in g/mol, used when no target formula is given
+    #[arg(long)]
+    pub target_molar_mass: Option<f64>,
+    /// Mass density of the target in g/cm^3, needed for thicknesses in nm
+    #[arg(long)]
+    pub target_mass_density: Option<f64>,
+    /// Beam current on target in the beam current unit
+    #[arg(long)]
+    pub beam_current: Option<f64>,
+    /// Beam current unit: nA (default, electrical), pnA or particles/s
+    #[arg(long)]
+    pub beam_current_unit: Option<CurrentUnit>,
+    /// Beam nuclide, e.g. 7Li, d or a; sets Z and A
+    #[arg(long, value_parser = parse_nuclide)]
+    pub beam: Option<&'static Nuclide>,
+    /// Proton number of the beam
+    #[arg(long)]
+    pub z_beam: Option<i32>,
+    /// Mass number of the beam
+    #[arg(long)]
+    pub a_beam: Option<i32>,
+    /// Charge state of the beam on the Faraday cup
+    #[arg(long)]
+    pub q_beam: Option<i32>,
+    /// Solid angle of the SE-SPS in the slit settings unit
+    #[arg(long)]
+    pub slit_settings: Option<f64>,
+    /// Slit settings unit: msr (default) or sr
+    #[arg(long)]
+    pub slit_settings_unit: Option<SolidAngleUnit>,
+    /// Desired number of counts in the peak of interest
+    #[arg(long)]
+    pub desired_counts: Option<i64>,
+    /// Available beam time in hours, used unless solving for the time
+    #[arg(long)]
+    pub beam_time: Option<f64>,
+    /// Quantity calculated from the others
+    #[arg(long, value_enum)]
+    pub solve_for: Option<SolveFor>,
+    /// Ejectile nuclide detected in the SE-SPS, e.g. p; enables the kinematics
+    #[arg(long)]
+    pub ejectile: Option<String>,
+    /// Atomic mass of the recoil in u, for recoils that are not in the nuclide table
+    #[arg(long)]
+    pub recoil_mass: Option<f64>,
+    /// Beam energy in MeV
+    #[arg(long)]
+    pub beam_energy: Option<f64>,
+    /// Spectrograph angle in degrees
+    #[arg(long)]
+    pub angle: Option<f64>,
+    /// Excitation energy of the recoil in MeV (repeatable)
+    #[arg(long = "excitation")]
+    pub excitation_energies: Vec<f64>,
+    /// Excitation energy in MeV put at the centre of the focal plane by the field setting
+    #[arg(long)]
+    pub center_excitation: Option<f64>,
+    /// FWHM of the beam energy in keV, for the Ex resolution
+    #[arg(long)]
+    pub beam_spread: Option<f64>,
+    /// SRIM stopping table of the beam in the target, replacing the built-in stopping powers
+    #[arg(long)]
+    pub beam_srim: Option<PathBuf>,
+    /// SRIM stopping table of the ejectile in the target
+    #[arg(long)]
+    pub ejectile_srim: Option<PathBuf>,
+    /// Angle in degrees of the target normal from the beam toward the spectrograph
+    #[arg(long, allow_negative_numbers = true)]
+    pub target_angle: Option<f64>,
+    /// Fraction of the kinematic broadening across the slits that is corrected, 0 to 1
+    #[arg(long)]
+    pub kinematic_correction: Option<f64>,
+    /// FWHM position resolution of the focal-plane detector in mm
+    #[arg(long)]
+    pub position_resolution: Option<f64>,
+    /// Lowest excitation energy in MeV the field-setting plan covers
+    #[arg(long)]
+    pub plan_min_excitation: Option<f64>,
+    /// Highest excitation energy in MeV the field-setting plan covers; enables the plan
+    #[arg(long)]
+    pub plan_max_excitation: Option<f64>,
+    /// Fraction of the focal plane shared by neighbouring field settings
+    #[arg(long)]
+    pub plan_overlap: Option<f64>,
+    /// State of the plan as EX:CROSS_SECTION in MeV and the cross section unit (repeatable)
+    #[arg(long = "plan-state", value_parser = parse_plan_state)]
+    pub plan_states: Vec<PlanState>,
+    /// Angle of an angular distribution as ANGLE:CROSS_SECTION in degrees and the cross
+    /// section unit (repeatable)
+    #[arg(long = "campaign-angle", value_parser = parse_campaign_angle)]
+    pub campaign_angles: Vec<CampaignAngle>,
+    /// Statistical uncertainty in % every campaign angle has to reach, instead of the counts
+    #[arg(long)]
+    pub campaign_uncertainty: Option<f64>,
+    /// Hours lost per change of the spectrograph angle
+    #[arg(long)]
+    pub campaign_overhead: Option<f64>,
+    /// List the optical-model elastic cross sections of the beam on the target nuclide every
+    /// 5 degrees in the lab
+    #[arg(long)]
+    pub elastic_curve: bool,
+    /// Excitation energy in MeV of the state of the DWBA cross section
+    #[arg(long)]
+    pub transfer_excitation: Option<f64>,
+    /// Orbital angular momentum transferred to the DWBA state
+    #[arg(long)]
+    pub transfer_l: Option<u32>,
+    /// Total angular momentum of the transferred nucleon, l ± 1/2, e.g. 1.5
+    #[arg(long)]
+    pub transfer_j: Option<f64>,
+    /// Radial nodes of the bound state of the transferred nucleon, without the origin
+    #[arg(long)]
+    pub transfer_nodes: Option<u32>,
+    /// Spectroscopic factor of the DWBA state
+    #[arg(long)]
+    pub spectroscopic_factor: Option<f64>,
+    /// Spin J_i of the target of the DWBA state, e.g. 0 or 1.5
+    #[arg(long)]
+    pub transfer_initial_spin: Option<f64>,
+    /// Spin J_f of the DWBA state; j of the transferred nucleon if not given
+    #[arg(long)]
+    pub transfer_final_spin: Option<f64>,
+    /// Reference single-particle cross section σ_sp of the scaled source, in the cross-section
+    /// unit and frame
+    #[arg(long)]
+    pub single_particle_cross_section: Option<f64>,
+    /// Spectroscopic factor of the scaled source
+    #[arg(long)]
+    pub scaled_spectroscopic_factor: Option<f64>,
+    /// Spin J_i of the target of the scaled source, e.g. 0 or 1.5
+    #[arg(long)]
+    pub initial_spin: Option<f64>,
+    /// Spin J_f of the final state of the scaled source, e.g. 1.5
+    #[arg(long)]
+    pub final_spin: Option<f64>,
+    /// Transfer type of the scaled source; stripping carries (2J_f + 1)/(2J_i + 1)
+    #[arg(long, value_enum)]
+    pub transfer_type: Option<TransferMode>,
+    /// List the DWBA cross sections of the transfer state every 5 degrees in the lab
+    #[arg(long)]
+    pub transfer_curve: bool,
+    /// Ptolemy, FRESCO (fort.201) or DWUCK4 output to import the angular distributions of the
+    /// imported cross-section source from
+    #[arg(long)]
+    pub listing: Option<PathBuf>,
+    /// Code that wrote --listing, detected from its contents if not given
+    #[arg(long, value_enum)]
+    pub listing_format: Option<ListingCode>,
+    /// Number of the imported angular distribution the estimate uses, from 1
+    #[arg(long)]
+    pub listing_state: Option<usize>,
+    /// Excitation energy in MeV of the imported state, for its kinematics
+    #[arg(long)]
+    pub listing_excitation: Option<f64>,
+    /// Write Ptolemy or FRESCO input decks for the --deck-state states into this directory
+    #[arg(long)]
+    pub deck_output: Option<PathBuf>,
+    /// DWBA code of the input decks
+    #[arg(long, value_enum)]
+    pub deck_code: Option<DeckProgram>,
+    /// State of the input decks as EX:ORBIT or EX:ORBIT:S in MeV, e.g. 2.023:2p1/2
+    /// (repeatable)
+    #[arg(long = "deck-state", value_parser = parse_deck_state)]
+    pub deck_states: Vec<TransferState>,
+    /// CM angles of the input decks as MIN:MAX:STEP in degrees
+    #[arg(long, value_parser = parse_deck_angles)]
+    pub deck_angles: Option<(f64, f64, f64)>,
+    /// Optical potential of the entrance channel, that of the beam if not given
+    #[arg(long, value_enum)]
+    pub deck_entrance_potential: Option<OpticalPotential>,
+    /// Optical potential of the exit channel, that of the ejectile if not given
+    #[arg(long, value_enum)]
+    pub deck_exit_potential: Option<OpticalPotential>,
+    /// Leave the spin-orbit terms out of the optical potentials of the input decks
+    #[arg(long)]
+    pub deck_no_spin_orbit: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SolveFor {
+    Time,
+    Counts,
+    BeamCurrent,
+    CrossSection,
+    TargetDensity,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CrossSectionFrame {
+    Lab,
+    Cm,
+}
+
+impl From<CrossSectionFrame> for Frame {
+    fn from(frame: CrossSectionFrame) -> Self {
+        match frame {
+            CrossSectionFrame::Lab => Frame::Lab,
+            CrossSectionFrame::Cm => Frame::CenterOfMass,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CrossSectionModel {
+    Manual,
+    Rutherford,
+    OpticalModel,
+    Dwba,
+    Imported,
+    Scaled,
+}
+
+impl From<CrossSectionModel> for CrossSectionSource {
+    fn from(model: CrossSectionModel) -> Self {
+        match model {
+            CrossSectionModel::Manual => CrossSectionSource::Manual,
+            CrossSectionModel::Rutherford => CrossSectionSource::Rutherford,
+            CrossSectionModel::OpticalModel => CrossSectionSource::OpticalModel,
+            CrossSectionModel::Dwba => CrossSectionSource::Dwba,
+            CrossSectionModel::Imported => CrossSectionSource::Imported,
+            CrossSectionModel::Scaled => CrossSectionSource::Scaled,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TransferMode {
+    Stripping,
+    Pickup,
+}
+
+impl From<TransferMode> for TransferType {
+    fn from(transfer: TransferMode) -> Self {
+        match transfer {
+            TransferMode::Stripping => TransferType::Stripping,
+            TransferMode::Pickup => TransferType::Pickup,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ListingCode {
+    Ptolemy,
+    Fresco,
+    Dwuck4,
+}
+
+impl From<ListingCode> for ListingFormat {
+    fn from(code: ListingCode) -> Self {
+        match code {
+            ListingCode::Ptolemy => ListingFormat::Ptolemy,
+            ListingCode::Fresco => ListingFormat::Fresco,
+            ListingCode::Dwuck4 => ListingFormat::Dwuck4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DeckProgram {
+    Ptolemy,
+    Fresco,
+}
+
+impl From<DeckProgram> for DeckCode {
+    fn from(program: DeckProgram) -> Self {
+        match program {
+            DeckProgram::Ptolemy => DeckCode::Ptolemy,
+            DeckProgram::Fresco => DeckCode::Fresco,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OpticalPotential {
+    KoningDelaroche,
+    AnCai,
+    BecchettiGreenlees,
+    McfaddenSatchler,
+}
+
+impl From<OpticalPotential> for GlobalPotential {
+    fn from(potential: OpticalPotential) -> Self {
+        match potential {
+            OpticalPotential::KoningDelaroche => GlobalPotential::KoningDelaroche,
+            OpticalPotential::AnCai => GlobalPotential::AnCai,
+            OpticalPotential::BecchettiGreenlees => GlobalPotential::BecchettiGreenlees,
+            OpticalPotential::McfaddenSatchler => GlobalPotential::McFaddenSatchler,
+        }
+    }
+}
+
+impl From<SolveFor> for SPSUnknown {
+    fn from(solve_for: SolveFor) -> Self {
+        match solve_for {
+            SolveFor::Time => SPSUnknown::RunTime,
+            SolveFor::Counts => SPSUnknown::Counts,
+            SolveFor::BeamCurrent => SPSUnknown::BeamCurrent,
+            SolveFor::CrossSection => SPSUnknown::CrossSection,
+            SolveFor::TargetDensity => SPSUnknown::TargetDensity,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DetectorPreset {
+    Reu2023,
+    Summer2022,
+}
+
+#[derive(Debug, Args)]
+pub struct CeBrAArgs {
+    /// JSON file with CeBrA settings, flags override its values
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+    /// Number of particles detected in the excited state
+    #[arg(long)]
+    pub particle_counts: Option<i64>,
+    /// γ-ray energy in keV
+    #[arg(long)]
+    pub energy: Option<f64>,
+    /// Absolute intensity of the decay in %
+    #[arg(long)]
+    pub intensity: Option<f64>,
+    /// Replace the detectors with a stored efficiency set
+    #[arg(long, value_enum)]
+    pub preset: Option<DetectorPreset>,
+    /// Add a detector as NAME:a,b,c,d (repeatable)
+    #[arg(long = "detector", value_parser = parse_detector)]
+    pub detectors: Vec<Detector>,
+}
+
+#[derive(Debug, Args)]
+pub struct ICESPICEArgs {
+    /// JSON file with ICESPICE settings, flags override its values
+    #[arg(long)]
+    pub file: Option<PathBuf>,
+    /// Number of particles detected in the excited state
+    #[arg(long)]
+    pub particle_counts: Option<i64>,
+    /// Transmission probability through ICESPICE in %
+    #[arg(long)]
+    pub transmission_prob: Option<f64>,
+    /// Full-energy detector efficiency in %
+    #[arg(long)]
+    pub detector_efficiency: Option<f64>,
+    /// Branching ratio of the transition in %
+    #[arg(long)]
+    pub branching_ratio: Option<f64>,
+    /// Internal conversion coefficient α
+    #[arg(long)]
+    pub conversion_coefficient: Option<f64>,
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Project file saved from the GUI
+    pub project: PathBuf,
+    /// Report format: markdown, html or latex
+    #[arg(long, value_parser = parse_report_format, default_value = "markdown")]
+    pub report_format: ReportFormat,
+    /// Write the report to this file instead of printing it
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    Io(PathBuf, std::io::Error),
+    Json(PathBuf, serde_json::Error),
+    Estimate(EstimateError),
+    Listing(PathBuf, ListingError),
+    Stopping(PathBuf, StoppingError),
+    Project(PathBuf, ProjectError),
+    Write(PathBuf, std::io::Error),
+    Output(serde_json::Error),
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Io(path, e) => write!(f, "could not read {}: {e}", path.display()),
+            CliError::Json(path, e) => write!(f, "could not parse {}: {e}", path.display()),
+            CliError::Estimate(e) => write!(f, "{e}"),
+            CliError::Listing(path, e) => write!(f, "could not import {}: {e}", path.display()),
+            CliError::Stopping(path, e) => write!(f, "could not import {}: {e}", path.display()),
+            CliError::Project(path, e) => write!(f, "could not load {}: {e}", path.display()),
+            CliError::Write(path, e) => write!(f, "could not write {}: {e}", path.display()),
+            CliError::Output(e) => write!(f, "could not write results: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<EstimateError> for CliError {
+    fn from(e: EstimateError) -> Self {
+        CliError::Estimate(e)
+    }
+}
+
+/// Settings together with the estimate calculated from them, for JSON output.
+#[derive(serde::Serialize)]
+struct Output<'a, S, E> {
+    #[serde(flatten)]
+    settings: &'a S,
+    estimate: &'a E,
+}
+
+/// SPS settings with the estimate, the kinematics and focal-plane position of every state, the
+/// field setting and the field-setting and angular-distribution plans, for JSON output.
+#[derive(serde::Serialize)]
+struct SpsOutput<'a> {
+    #[serde(flatten)]
+    settings: &'a SPSRunTimeSettings,
+    estimate: &'a SPSSolution,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    kinematics: &'a [Option<EjectileKinematics>],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field_setting: &'a Option<FieldSetting>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    focal_plane: &'a [Option<FocalPlaneState>],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ex_resolution: &'a Option<ExResolution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    energy_loss: &'a Option<TargetEnergyLoss>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field_plan: &'a Option<FieldPlanResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    campaign: &'a Option<CampaignResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rutherford: &'a Option<RutherfordCrossSection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    optical_model: &'a Option<ElasticPoint>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    elastic_curve: &'a [ElasticPoint],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dwba: &'a Option<TransferPoint>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    transfer_curve: &'a [TransferPoint],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    listing: &'a Option<ListingPoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scaled: &'a Option<String>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    decks: &'a [Deck],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    warnings: &'a [SectionWarning],
+}
+
+/// A section of the SPS output that could not be calculated.
+#[derive(serde::Serialize)]
+struct SectionWarning {
+    section: &'static str,
+    message: String,
+}
+
+/// The result of an add-on section, or `None` with a warning if it failed. An error already
+/// reported by an earlier section, e.g. a recoil missing from the nuclide table, is not repeated.
+fn add_on<T>(
+    warnings: &mut Vec<SectionWarning>,
+    section: &'static str,
+    result: Result<T, EstimateError>,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            let message = e.to_string();
+            if !warnings.iter().any(|warning| warning.message == message) {
+                warnings.push(SectionWarning { section, message });
+            }
+            None
+        }
+    }
+}
+
+fn parse_detector(s: &str) -> Result<Detector, String> {
+    let (name, coefficients) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected NAME:a,b,c,d, got '{s}'"))?;
+    let values = coefficients
+        .split(',')
+        .map(|v| v.trim().parse::<f64>().map_err(|e| format!("'{v}': {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    match values[..] {
+        [a, b, c, d] => Ok(Detector {
+            name: name.to_string(),
+            efficiency: Efficiency::new(a, b, c, d),
+        }),
+        _ => Err(format!(
+            "expected 4 efficiency coefficients, got {}",
+            values.len()
+        )),
+    }
+}
+
+fn parse_target(s: &str) -> Result<String, String> {
+    Formula::parse(s)
+        .map(|_| s.trim().to_string())
+        .map_err(|e| e.to_string())
+}
+
+fn parse_assay(s: &str) -> Result<Assay, String> {
+    let (element, isotopes) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected EL:A=%,A=%,..., got '{s}'"))?;
+    let isotopes = isotopes
+        .split(',')
+        .map(|isotope| {
+            let (a, percent) = isotope
+                .split_once('=')
+                .ok_or_else(|| format!("expected A=%, got '{isotope}'"))?;
+            Ok(AssayIsotope {
+                a: a.trim().parse().map_err(|e| format!("'{a}': {e}"))?,
+                percent: percent
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("'{percent}': {e}"))?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let assay = Assay {
+        element: element.trim().to_string(),
+        isotopes,
+    };
+    assay.fractions().map_err(|e| e.to_string())?;
+    Ok(assay)
+}
+
+fn parse_plan_state(s: &str) -> Result<PlanState, String> {
+    let (excitation, cross_section) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected EX:CROSS_SECTION, got '{s}'"))?;
+    Ok(PlanState {
+        excitation: excitation
+            .trim()
+            .parse()
+            .map_err(|e| format!("'{excitation}': {e}"))?,
+        cross_section: cross_section
+            .trim()
+            .parse()
+            .map_err(|e| format!("'{cross_section}': {e}"))?,
+    })
+}
+
+fn parse_campaign_angle(s: &str) -> Result<CampaignAngle, String> {
+    let (angle, cross_section) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected ANGLE:CROSS_SECTION, got '{s}'"))?;
+    Ok(CampaignAngle {
+        angle: angle
+            .trim()
+            .parse()
+            .map_err(|e| format!("'{angle}': {e}"))?,
+        cross_section: cross_section
+            .trim()
+            .parse()
+            .map_err(|e| format!("'{cross_section}': {e}"))?,
+    })
+}
+
+fn parse_deck_state(s: &str) -> Result<TransferState, String> {
+    let mut parts = s.split(':');
+    let (Some(excitation), Some(orbit)) = (parts.next(), parts.next()) else {
+        return Err(format!("expected EX:ORBIT or EX:ORBIT:S, got '{s}'"));
+    };
+    let excitation = excitation
+        .trim()
+        .parse()
+        .map_err(|e| format!("'{excitation}': {e}"))?;
+    let mut state = TransferState::from_orbit(excitation, orbit)
+        .ok_or_else(|| format!("'{orbit}' is not an orbit like 2p3/2"))?;
+    if let Some(factor) = parts.next() {
+        state.spectroscopic_factor = factor
+            .trim()
+            .parse()
+            .map_err(|e| format!("'{factor}': {e}"))?;
+    }
+    Ok(state)
+}
+
+fn parse_deck_angles(s: &str) -> Result<(f64, f64, f64), String> {
+    let values = s
+        .split(':')
+        .map(|v| v.trim().parse::<f64>().map_err(|e| format!("'{v}': {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    match values[..] {
+        [min, max, step] => Ok((min, max, step)),
+        _ => Err(format!("expected MIN:MAX:STEP, got '{s}'")),
+    }
+}
+
+fn parse_nuclide(s: &str) -> Result<&'static Nuclide, String> {
+    nuclide::parse(s).ok_or_else(|| format!("unknown nuclide '{s}', expected e.g. 7Li or d"))
+}
+
+fn parse_report_format(s: &str) -> Result<ReportFormat, String> {
+    ReportFormat::ALL
+        .into_iter()
+        .find(|format| format.name().eq_ignore_ascii_case(s) || format.extension() == s)
+        .ok_or_else(|| format!("unknown report format '{s}', expected markdown, html or latex"))
+}
+
+fn read_settings<T: serde::de::DeserializeOwned + Default>(
+    path: Option<&Path>,
+) -> Result<T, CliError> {
+    let Some(path) = path else {
+        return Ok(T::default());
+    };
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| CliError::Io(path.to_path_buf(), e))?;
+    serde_json::from_str(&contents).map_err(|e| CliError::Json(path.to_path_buf(), e))
+}
+
+/// Angular distributions of a DWBA code listing, in the given format or the detected one.
+fn import_listing(
+    path: &Path,
+    format: Option<ListingCode>,
+) -> Result<ImportedCrossSections, CliError> {
+    let text = std::fs::read_to_string(path).map_err(|e| CliError::Io(path.to_path_buf(), e))?;
+    let format = match format {
+        Some(code) => code.into(),
+        None => ListingFormat::detect(&text)
+            .ok_or_else(|| CliError::Listing(path.to_path_buf(), ListingError::UnknownFormat))?,
+    };
+    let file = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    ImportedCrossSections::import(&file, format, &text)
+        .map_err(|e| CliError::Listing(path.to_path_buf(), e))
+}
+
+fn import_stopping_table(path: &Path) -> Result<StoppingTable, CliError> {
+    let text = std::fs::read_to_string(path).map_err(|e| CliError::Io(path.to_path_buf(), e))?;
+    let file = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    StoppingTable::import(&file, &text).map_err(|e| CliError::Stopping(path.to_path_buf(), e))
+}
+
+fn override_with<T>(value: &mut T, flag: Option<T>) {
+    if let Some(flag) = flag {
+        *value = flag;
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, CliError> {
+    serde_json::to_string_pretty(value).map_err(CliError::Output)
+}
+
+fn run_sps(args: SpsArgs, format: OutputFormat) -> Result<String, CliError> {
+    let mut settings: SPSRunTimeSettings = read_settings(args.file.as_deref())?;
+    override_with(&mut settings.cross_section, args.cross_section);
+    override_with(&mut settings.target_density, args.target_density);
+    override_with(&mut settings.target, args.target);
+    override_with(&mut settings.reaction_nuclide, args.reaction_nuclide);
+    for assay in args.assays {
+        settings
+            .target_assays
+            .retain(|existing| existing.z() != assay.z());
+        settings.target_assays.push(assay);
+    }
+    override_with(&mut settings.target_molar_mass, args.target_molar_mass);
+    override_with(&mut settings.target_mass_density, args.target_mass_density);
+    override_with(&mut settings.beam_current, args.beam_current);
+    if let Some(beam) = args.beam {
+        settings.z_beam = beam.z;
+        settings.a_beam = beam.a;
+        settings.q_beam = settings.q_beam.min(beam.z);
+    }
+    override_with(&mut settings.z_beam, args.z_beam);
+    override_with(&mut settings.a_beam, args.a_beam);
+    override_with(&mut settings.q_beam, args.q_beam);
+    override_with(&mut settings.slit_settings, args.slit_settings);
+    override_with(&mut settings.desired_counts, args.desired_counts);
+    override_with(&mut settings.beam_time, args.beam_time);
+    override_with(&mut settings.unknown, args.solve_for.map(SPSUnknown::from));
+    override_with(&mut settings.units.cross_section, args.cross_section_unit);
+    override_with(
+        &mut settings.cross_section_frame,
+        args.cross_section_frame.map(Frame::from),
+    );
+    override_with(
+        &mut settings.cross_section_source,
+        args.cross_section_source.map(CrossSectionSource::from),
+    );
+    override_with(&mut settings.units.target_density, args.target_density_unit);
+    override_with(&mut settings.units.beam_current, args.beam_current_unit);
+    override_with(&mut settings.units.slit_settings, args.slit_settings_unit);
+    override_with(&mut settings.reaction.ejectile, args.ejectile);
+    if args.recoil_mass.is_some() {
+        settings.reaction.recoil_mass = args.recoil_mass;
+    }
+    override_with(&mut settings.reaction.beam_energy, args.beam_energy);
+    override_with(&mut settings.reaction.angle, args.angle);
+    if !args.excitation_energies.is_empty() {
+        settings.reaction.excitation_energies = args.excitation_energies;
+    }
+    override_with(
+        &mut settings.reaction.center_excitation,
+        args.center_excitation,
+    );
+    override_with(
+        &mut settings.field_plan.min_excitation,
+        args.plan_min_excitation,
+    );
+    override_with(&mut settings.resolution.beam_spread, args.beam_spread);
+    if let Some(path) = &args.beam_srim {
+        settings.stopping.beam = Some(import_stopping_table(path)?);
+    }
+    if let Some(path) = &args.ejectile_srim {
+        settings.stopping.ejectile = Some(import_stopping_table(path)?);
+    }
+    override_with(&mut settings.resolution.target_angle, args.target_angle);
+    override_with(
+        &mut settings.resolution.kinematic_correction,
+        args.kinematic_correction,
+    );
+    override_with(
+        &mut settings.resolution.position_resolution,
+        args.position_resolution,
+    );
+    override_with(
+        &mut settings.field_plan.max_excitation,
+        args.plan_max_excitation,
+    );
+    override_with(&mut settings.field_plan.overlap, args.plan_overlap);
+    if !args.plan_states.is_empty() {
+        settings.field_plan.states = args.plan_states;
+    }
+    if !args.campaign_angles.is_empty() {
+        settings.campaign.angles = args.campaign_angles;
+    }
+    if let Some(uncertainty) = args.campaign_uncertainty {
+        settings.campaign.goal = CampaignGoal::Uncertainty;
+        settings.campaign.uncertainty = uncertainty;
+    }
+    override_with(&mut settings.campaign.overhead, args.campaign_overhead);
+    override_with(&mut settings.transfer.excitation, args.transfer_excitation);
+    override_with(&mut settings.transfer.l, args.transfer_l);
+    override_with(&mut settings.transfer.j, args.transfer_j);
+    override_with(&mut settings.transfer.nodes, args.transfer_nodes);
+    override_with(
+        &mut settings.transfer.initial_spin,
+        args.transfer_initial_spin,
+    );
+    if args.transfer_final_spin.is_some() {
+        settings.transfer.final_spin = args.transfer_final_spin;
+    }
+    override_with(
+        &mut settings.transfer.spectroscopic_factor,
+        args.spectroscopic_factor,
+    );
+    override_with(
+        &mut settings.scaling.single_particle,
+        args.single_particle_cross_section,
+    );
+    override_with(
+        &mut settings.scaling.spectroscopic_factor,
+        args.scaled_spectroscopic_factor,
+    );
+    override_with(&mut settings.scaling.initial_spin, args.initial_spin);
+    override_with(&mut settings.scaling.final_spin, args.final_spin);
+    override_with(
+        &mut settings.scaling.transfer,
+        args.transfer_type.map(Into::into),
+    );
+    override_with(&mut settings.deck.code, args.deck_code.map(Into::into));
+    if !args.deck_states.is_empty() {
+        settings.deck.states = args.deck_states;
+    }
+    if let Some((min, max, step)) = args.deck_angles {
+        settings.deck.min_angle = min;
+        settings.deck.max_angle = max;
+        settings.deck.angle_step = step;
+    }
+    if let Some(potential) = args.deck_entrance_potential {
+        settings.deck.entrance = Some(potential.into());
+    }
+    if let Some(potential) = args.deck_exit_potential {
+        settings.deck.exit = Some(potential.into());
+    }
+    if args.deck_no_spin_orbit {
+        settings.deck.spin_orbit = false;
+    }
+    if let Some(path) = &args.listing {
+        settings.imported = import_listing(path, args.listing_format)?;
+    }
+    if let Some(state) = args.listing_state {
+        settings.imported.selected = state.saturating_sub(1);
+    }
+    if let (Some(excitation), Some(state)) = (
+        args.listing_excitation,
+        settings.imported.states.get_mut(settings.imported.selected),
+    ) {
+        state.excitation = Some(excitation);
+    }
+
+    let solution = settings.solve()?;
+
+    // Everything else is an add-on to the estimate: one that cannot be calculated becomes a
+    // warning instead of hiding the estimate.
+    let mut warnings = vec![];
+    let reaction = add_on(&mut warnings, "Reaction", settings.reaction()).flatten();
+    let frame_kinematics = add_on(&mut warnings, "Lab/CM", settings.frame_kinematics()).flatten();
+    let kinematics = add_on(&mut warnings, "Kinematics", settings.kinematics()).unwrap_or_default();
+    let field_setting = add_on(&mut warnings, "SPS Field", settings.field_setting()).flatten();
+    let focal_plane =
+        add_on(&mut warnings, "Focal Plane", settings.focal_plane()).unwrap_or_default();
+    let ex_resolution = add_on(&mut warnings, "Ex Resolution", settings.ex_resolution()).flatten();
+    let energy_loss = (settings.formula().is_ok_and(|formula| formula.is_some())
+        || settings.stopping.beam.is_some()
+        || settings.stopping.ejectile.is_some())
+    .then(|| add_on(&mut warnings, "Energy Loss", settings.target_energy_loss()))
+    .flatten();
+    let field_plan = add_on(
+        &mut warnings,
+        "Field Settings",
+        settings.plan_field_settings(),
+    )
+    .flatten();
+    let campaign = add_on(&mut warnings, "Angular Dist.", settings.plan_campaign()).flatten();
+    let rutherford = match settings.cross_section_source {
+        CrossSectionSource::Rutherford => {
+            add_on(&mut warnings, "Rutherford", settings.rutherford())
+        }
+        _ => None,
+    };
+    let elastic = (settings.cross_section_source == CrossSectionSource::OpticalModel
+        || args.elastic_curve)
+        .then(|| {
+            add_on(
+                &mut warnings,
+                "Elastic Scattering",
+                settings.elastic_scattering(),
+            )
+        })
+        .flatten();
+    let optical_model = match settings.cross_section_source {
+        CrossSectionSource::OpticalModel => {
+            add_on(&mut warnings, "Optical Model", settings.optical_model())
+        }
+        _ => None,
+    };
+    let elastic_curve = match (&elastic, args.elastic_curve) {
+        (Some(elastic), true) => elastic.curve(),
+        _ => vec![],
+    };
+    let transfer = (settings.cross_section_source == CrossSectionSource::Dwba
+        || args.transfer_curve)
+        .then(|| add_on(&mut warnings, "Transfer (DWBA)", settings.transfer()))
+        .flatten();
+    let dwba = match settings.cross_section_source {
+        CrossSectionSource::Dwba => add_on(&mut warnings, "DWBA", settings.dwba()),
+        _ => None,
+    };
+    let transfer_curve = match (&transfer, args.transfer_curve) {
+        (Some(transfer), true) => transfer.curve(),
+        _ => vec![],
+    };
+    let imported = match settings.cross_section_source {
+        CrossSectionSource::Imported => add_on(&mut warnings, "Imported", settings.imported()),
+        _ => None,
+    };
+    let scaled = match settings.cross_section_source {
+        CrossSectionSource::Scaled => {
+            add_on(&mut warnings, "Scaled", settings.scaled_cross_section()).map(|_| {
+                settings
+                    .scaling
+                    .explanation(settings.units.cross_section.symbol())
+            })
+        }
+        _ => None,
+    };
+    let decks = args
+        .deck_output
+        .as_ref()
+        .and_then(|_| add_on(&mut warnings, "Input Decks", settings.input_decks()))
+        .unwrap_or_default();
+    if let Some(directory) = &args.deck_output {
+        for deck in &decks {
+            let path = directory.join(&deck.file_name);
+            std::fs::write(&path, &deck.contents).map_err(|e| CliError::Write(path, e))?;
+        }
+    }
+
+    match format {
+        OutputFormat::Json => to_json(&SpsOutput {
+            settings: &settings,
+            estimate: &solution,
+            kinematics: &kinematics,
+            field_setting: &field_setting,
+            focal_plane: &focal_plane,
+            ex_resolution: &ex_resolution,
+            energy_loss: &energy_loss,
+            field_plan: &field_plan,
+            campaign: &campaign,
+            rutherford: &rutherford,
+            optical_model: &optical_model,
+            elastic_curve: &elastic_curve,
+            dwba: &dwba,
+            transfer_curve: &transfer_curve,
+            listing: &imported,
+            scaled: &scaled,
+            decks: &decks,
+            warnings: &warnings,
+        }),
+        OutputFormat::Text => {
+            let units = &settings.units;
+            let mut lines = vec![];
+            if settings.unknown != SPSUnknown::CrossSection {
+                match settings.cross_section_source {
+                    CrossSectionSource::Manual => lines.push(format!(
+                        "Cross Section:     {} {} ({})",
+                        settings.cross_section,
+                        units.cross_section.symbol(),
+                        settings.cross_section_frame.name()
+                    )),
+                    source => lines.push(format!(
+                        "Cross Section:     {:.4e} {} ({}, {})",
+                        settings.cross_section_value()?,
+                        units.cross_section.symbol(),
+                        settings.cross_section_frame.name(),
+                        source.name()
+                    )),
+                }
+            }
+            if let Some(rutherford) = &rutherford {
+                lines.push(format!(
+                    "Rutherford:        {:.4} mb/sr (Lab) | {:.4} mb/sr (CM) at θcm = {:.2} deg, Ecm = {:.3} MeV",
+                    rutherford.lab, rutherford.cm, rutherford.cm_angle, rutherford.cm_energy
+                ));
+                if rutherford.above_barrier {
+                    lines.push(format!(
+                        "Warning:           {} MeV is above the Coulomb barrier ({:.2} MeV in the lab), pure Rutherford scattering is unreliable",
+                        settings.reaction.beam_energy, rutherford.barrier
+                    ));
+                }
+            }
+            if let (Some(elastic), Some(point)) = (&elastic, &optical_model) {
+                lines.push(format!(
+                    "Optical Model:     {:.4} mb/sr (Lab) | {:.4} mb/sr (CM) = {:.4} × Rutherford at θcm = {:.2} deg, {}",
+                    point.lab,
+                    point.cm,
+                    point.ratio,
+                    point.cm_angle,
+                    elastic.potential.name()
+                ));
+            }
+            if let (Some(transfer), Some(point)) = (&transfer, &dwba) {
+                lines.push(format!(
+                    "DWBA:              {:.4} mb/sr (Lab) | {:.4} mb/sr (CM) at θcm = {:.2} deg, {} at {} MeV, S = {}, J_i = {} → J_f = {}",
+                    point.lab,
+                    point.cm,
+                    point.cm_angle,
+                    transfer.state.orbit(),
+                    transfer.state.excitation,
+                    transfer.state.spectroscopic_factor,
+                    spin_name(transfer.state.initial_spin),
+                    spin_name(transfer.state.spin())
+                ));
+            }
+            if let Some(point) = &imported {
+                lines.push(format!(
+                    "Imported:          {:.4} mb/sr (Lab) | {:.4} mb/sr (CM) at θcm = {:.2} deg, {} at {} MeV from {}",
+                    point.lab,
+                    point.cm,
+                    point.cm_angle,
+                    point.state,
+                    point.excitation,
+                    settings.imported.file
+                ));
+            }
+            if let Some(scaled) = &scaled {
+                lines.push(format!(
+                    "Scaled:            {scaled} ({})",
+                    settings.scaling.transfer.name()
+                ));
+            }
+            if settings.unknown != SPSUnknown::TargetDensity {
+                lines.push(format!(
+                    "Target Density:    {} {}",
+                    settings.target_density,
+                    units.target_density.symbol()
+                ));
+            }
+            if units.target_density == ThicknessUnit::Nanometer {
+                lines.push(format!(
+                    "Target Mass Dens.: {} g/cm^3",
+                    settings.target_mass_density
+                ));
+            }
+            if !settings.target.is_empty() {
+                lines.push(format!("Target:            {}", settings.target));
+            }
+            if !settings.reaction_nuclide.is_empty() {
+                lines.push(format!("Reaction Nuclide:  {}", settings.reaction_nuclide));
+            }
+            if settings.target.is_empty() {
+                lines.push(format!(
+                    "Target Molar Mass: {} g/mol",
+                    settings.target_molar_mass
+                ));
+            } else {
+                lines.push(format!(
+                    "Target Molar Mass: {:.4} g/mol",
+                    settings.molar_mass()?
+                ));
+                for isotope in settings.target_composition()? {
+                    lines.push(format!(
+                        "  {:<16}{:.3e} atoms/cm^2 | {:.3} µg/cm^2",
+                        isotope.nuclide.name(),
+                        isotope.atoms_per_cm2,
+                        isotope.micrograms_per_cm2
+                    ));
+                }
+            }
+            for assay in &settings.target_assays {
+                let isotopes: Vec<String> = assay
+                    .isotopes
+                    .iter()
+                    .map(|isotope| format!("{}{} {} %", isotope.a, assay.element, isotope.percent))
+                    .collect();
+                lines.push(format!("Assay:             {}", isotopes.join(", ")));
+            }
+            lines.push(format!("Beam:              {}", settings.beam_species()));
+            if settings.unknown != SPSUnknown::BeamCurrent {
+                let mut current = format!(
+                    "Beam Current:      {} {}",
+                    settings.beam_current,
+                    units.beam_current.symbol()
+                );
+                for other in settings.beam_intensity()? {
+                    if other.unit != units.beam_current {
+                        current += &format!(" = {other}");
+                    }
+                }
+                lines.push(current);
+            }
+            lines.push(format!(
+                "Slit Settings:     {} {}",
+                settings.slit_settings,
+                units.slit_settings.symbol()
+            ));
+            if settings.unknown != SPSUnknown::Counts {
+                lines.push(format!("Counts:            {}", settings.desired_counts));
+            }
+            if settings.unknown == SPSUnknown::RunTime {
+                lines.push(format!("Estimated Time:    {solution}"));
+            } else {
+                lines.push(format!("Beam Time:         {} h", settings.beam_time));
+                let frame = match settings.unknown {
+                    SPSUnknown::CrossSection => {
+                        format!(" ({})", settings.cross_section_frame.name())
+                    }
+                    _ => String::new(),
+                };
+                lines.push(format!(
+                    "{:<19}{solution}{frame}",
+                    format!("{}:", settings.unknown.name())
+                ));
+            }
+            let yields = settings.isotope_yields()?;
+            if !yields.is_empty() {
+                lines.push("Other Isotopes:".to_string());
+                for isotope in yields {
+                    lines.push(format!(
+                        "  {:<16}{:.0} counts",
+                        isotope.nuclide.name(),
+                        isotope.counts
+                    ));
+                }
+            }
+            if let Some(reaction) = &reaction {
+                lines.push(format!(
+                    "Reaction:          {} at {} MeV, {} deg, Q = {:.3} MeV",
+                    reaction.name(),
+                    settings.reaction.beam_energy,
+                    settings.reaction.angle,
+                    reaction.q_value()
+                ));
+                if let Some(state) = &frame_kinematics {
+                    lines.push(format!(
+                        "Lab/CM:            θcm = {:.2} deg, dΩcm/dΩlab = {:.4} at Ex = {} MeV",
+                        state.cm_angle, state.jacobian, state.excitation
+                    ));
+                }
+                if let Some(setting) = &field_setting {
+                    lines.push(format!(
+                        "SPS Field:         {:.4} T (NMR {:.4} T), Ex = {} MeV at Bρ = {:.4} T·m centred",
+                        setting.field, setting.nmr_field, setting.excitation, setting.rigidity
+                    ));
+                }
+                lines.push(format!(
+                    "  {:>10}{:>14}{:>14}{:>14}{:>14}{:>14}{:>12}{:>10}",
+                    "Ex (MeV)",
+                    "T ejec (MeV)",
+                    "p (MeV/c)",
+                    "dE/dθ (keV/°)",
+                    "T rec (MeV)",
+                    "θ rec (°)",
+                    "Bρ (T·m)",
+                    "x (cm)"
+                ));
+                for (index, (excitation, state)) in settings
+                    .reaction
+                    .excitation_energies
+                    .iter()
+                    .zip(&kinematics)
+                    .enumerate()
+                {
+                    let Some(state) = state else {
+                        lines.push(format!("  {excitation:>10.3}  not reachable at this angle"));
+                        continue;
+                    };
+                    // Without a field setting the focal-plane columns stay empty
+                    let position = match focal_plane.get(index).copied().flatten() {
+                        Some(position) => format!(
+                            "{:>12.4}{:>10.1}{}",
+                            position.rigidity,
+                            position.position,
+                            if position.on_detector {
+                                ""
+                            } else {
+                                "  off detector"
+                            }
+                        ),
+                        None => String::new(),
+                    };
+                    lines.push(format!(
+                        "  {:>10.3}{:>14.3}{:>14.3}{:>14.2}{:>14.3}{:>14.2}{position}",
+                        state.excitation,
+                        state.ejectile_energy,
+                        state.ejectile_momentum,
+                        state.kinematic_factor,
+                        state.recoil_energy,
+                        state.recoil_angle,
+                    ));
+                }
+                if let Some(resolution) = &ex_resolution {
+                    lines.push(format!(
+                        "Ex Resolution:     {:.1} keV FWHM at Ex = {} MeV ({:.3} mg/cm^2, {:.2} deg acceptance, {:.2} keV/mm)",
+                        resolution.fwhm,
+                        resolution.excitation,
+                        resolution.areal_mass,
+                        resolution.acceptance,
+                        resolution.energy_dispersion
+                    ));
+                    for contribution in &resolution.contributions {
+                        lines.push(format!(
+                            "  {:<24}{:>8.1} keV{:>8.1} %",
+                            contribution.term.name(),
+                            contribution.fwhm,
+                            contribution.share * 100.0
+                        ));
+                    }
+                }
+            }
+            if let Some(loss) = &energy_loss {
+                lines.push(format!(
+                    "Energy Loss:       {:.3} mg/cm^2 target, mean beam energy {:.4} MeV, {:.3} mW deposited",
+                    loss.areal_mass, loss.mean_beam_energy, loss.power
+                ));
+                for passage in std::iter::once(&loss.beam).chain(&loss.ejectile) {
+                    lines.push(format!(
+                        "  {:<8}{:.4} -> {:.4} MeV, ΔE = {:.1} keV, straggling {:.1} keV FWHM, S = {:.2} keV/(mg/cm^2) ({})",
+                        passage.ion,
+                        passage.energy_in,
+                        passage.energy_out,
+                        passage.loss,
+                        passage.straggling,
+                        passage.stopping,
+                        passage.source
+                    ));
+                }
+            }
+            if let Some(plan) = &field_plan {
+                lines.push(format!(
+                    "Field Settings:    {} for Ex = {}-{} MeV, {} % overlap",
+                    plan.settings.len(),
+                    settings.field_plan.min_excitation,
+                    settings.field_plan.max_excitation,
+                    settings.field_plan.overlap * 100.0
+                ));
+                for (index, setting) in plan.settings.iter().enumerate() {
+                    let weakest = match setting.weakest_state {
+                        Some(excitation) => format!("Ex = {excitation} MeV"),
+                        None => "SPS cross section".to_string(),
+                    };
+                    lines.push(format!(
+                        "  {:>2}. {:.4} T (NMR {:.4} T)  Ex {:.2}-{:.2} MeV  {} {} ({weakest})  {:.2} h",
+                        index + 1,
+                        setting.field,
+                        setting.nmr_field,
+                        setting.min_excitation,
+                        setting.max_excitation,
+                        setting.cross_section,
+                        units.cross_section.symbol(),
+                        setting.run_time.hours
+                    ));
+                }
+                lines.push(format!(
+                    "Total Time:        {}",
+                    SPSSolution::RunTime(plan.total)
+                ));
+            }
+            if let Some(campaign) = &campaign {
+                let goal = match settings.campaign.goal {
+                    CampaignGoal::Counts => format!("{} counts", settings.desired_counts),
+                    CampaignGoal::Uncertainty => format!(
+                        "{} % uncertainty = {} counts",
+                        settings.campaign.uncertainty,
+                        settings.campaign_counts()?
+                    ),
+                };
+                lines.push(format!(
+                    "Angular Dist.:     {} angles, {goal} each",
+                    campaign.angles.len()
+                ));
+                for angle in &campaign.angles {
+                    let cross_section = match settings.cross_section_source {
+                        CrossSectionSource::Manual => angle.cross_section.to_string(),
+                        _ => format!("{:.4e}", angle.cross_section),
+                    };
+                    lines.push(format!(
+                        "  {:>6.1} deg  {} {}  {:.2} h",
+                        angle.angle,
+                        cross_section,
+                        units.cross_section.symbol(),
+                        angle.run_time.hours
+                    ));
+                }
+                lines.push(format!(
+                    "  Overhead   {:.2} h for {} angle changes",
+                    campaign.overhead.hours,
+                    campaign.angles.len() - 1
+                ));
+                lines.push(format!(
+                    "Campaign Time:     {}",
+                    SPSSolution::RunTime(campaign.total)
+                ));
+            }
+            if let (Some(elastic), false) = (&elastic, elastic_curve.is_empty()) {
+                lines.push(format!(
+                    "Elastic Scattering: {} at {} MeV, {} partial waves",
+                    elastic.potential.name(),
+                    elastic.beam_energy,
+                    elastic.partial_waves()
+                ));
+                lines.push(format!(
+                    "{:>10}{:>10}{:>16}{:>16}{:>10}",
+                    "θlab (°)", "θcm (°)", "Lab (mb/sr)", "CM (mb/sr)", "σ/σR"
+                ));
+                for point in &elastic_curve {
+                    lines.push(format!(
+                        "{:>10.1}{:>10.2}{:>16.4e}{:>16.4e}{:>10.4}",
+                        point.angle, point.cm_angle, point.lab, point.cm, point.ratio
+                    ));
+                }
+            }
+            if let (Some(transfer), false) = (&transfer, transfer_curve.is_empty()) {
+                lines.push(format!(
+                    "Transfer (DWBA):   {} to {} at {} MeV, S = {}, {} partial waves",
+                    transfer.reaction.name(),
+                    transfer.state.orbit(),
+                    transfer.state.excitation,
+                    transfer.state.spectroscopic_factor,
+                    transfer.partial_waves
+                ));
+                lines.push(format!(
+                    "  {} + {}, binding {:.3} MeV in a {:.2} MeV Woods–Saxon well",
+                    transfer.entrance.name(),
+                    transfer.exit.name(),
+                    transfer.bound_state.binding,
+                    transfer.bound_state.depth
+                ));
+                lines.push(format!(
+                    "{:>10}{:>10}{:>16}{:>16}",
+                    "θlab (°)", "θcm (°)", "Lab (mb/sr)", "CM (mb/sr)"
+                ));
+                for point in &transfer_curve {
+                    lines.push(format!(
+                        "{:>10.1}{:>10.2}{:>16.4e}{:>16.4e}",
+                        point.angle, point.cm_angle, point.lab, point.cm
+                    ));
+                }
+            }
+            if let Some(directory) = &args.deck_output {
+                for deck in &decks {
+                    lines.push(format!(
+                        "Input Deck:        {} ({})",
+                        directory.join(&deck.file_name).display(),
+                        settings.deck.code.name()
+                    ));
+                }
+            }
+            for warning in &warnings {
+                lines.push(format!(
+                    "Warning:           {}: {}",
+                    warning.section, warning.message
+                ));
+            }
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+fn run_cebra(args: CeBrAArgs, format: OutputFormat) -> Result<String, CliError> {
+    let mut settings: CeBrARunTimeSettings = read_settings(args.file.as_deref())?;
+    override_with(&mut settings.n_particle_counts, args.particle_counts);
+    override_with(&mut settings.decay.energy, args.energy);
+    override_with(&mut settings.decay.absolute_intensity, args.intensity);
+    match args.preset {
+        Some(DetectorPreset::Reu2023) => {
+            settings.detectors = CeBrARunTimeSettings::reu_2023_detectors();
+        }
+        Some(DetectorPreset::Summer2022) => {
+            settings.detectors = CeBrARunTimeSettings::summer_2022_detectors();
+        }
+        None => {}
+    }
+    settings.detectors.extend(args.detectors);
+
+    let estimate = settings.calculate_counts()?;
+
+    match format {
+        OutputFormat::Json => to_json(&Output {
+            settings: &settings,
+            estimate: &estimate,
+        }),
+        OutputFormat::Text => {
+            let mut out = format!(
+                "Particle Counts: {}\n\
+                 γ Decay:         {} keV, {} %\n\
+                 {:<16} {:>10} {:>10}\n",
+                settings.n_particle_counts,
+                settings.decay.energy,
+                settings.decay.absolute_intensity,
+                "Name",
+                "ε(γ)",
+                "Counts"
+            );
+            for detector in &estimate.detectors {
+                out += &format!(
+                    "{:<16} {:>8.2} % {:>10.0}\n",
+                    detector.name, detector.efficiency, detector.counts
+                );
+            }
+            out += &format!(
+                "{:<16} {:>8.2} % {:>10.0}",
+                "Total", estimate.total_efficiency, estimate.total_counts
+            );
+            Ok(out)
+        }
+    }
+}
+
+fn run_icespice(args: ICESPICEArgs, format: OutputFormat) -> Result<String, CliError> {
+    let mut settings: ICESPICERunTimeSettings = read_settings(args.file.as_deref())?;
+    override_with(&mut settings.n_particle_counts, args.particle_counts);
+    override_with(&mut settings.transmission_prob, args.transmission_prob);
+    override_with(&mut settings.detector_efficiency, args.detector_efficiency);
+    override_with(&mut settings.branching_ratio, args.branching_ratio);
+    override_with(
+        &mut settings.conversion_coefficient,
+        args.conversion_coefficient,
+    );
+
+    let estimate = settings.calculate_conversion_electrons()?;
+
+    match format {
+        OutputFormat::Json => to_json(&Output {
+            settings: &settings,
+            estimate: &estimate,
+        }),
+        OutputFormat::Text => Ok(format!(
+            "Particle Counts:          {}\n\
+             Transmission Probability: {} %\n\
+             Detector Efficiency:      {} %\n\
+             Branching Ratio:          {} %\n\
+             Conversion Coefficient:   {}\n\
+             Detected Conversion Electrons: {:.0}",
+            settings.n_particle_counts,
+            settings.transmission_prob,
+            settings.detector_efficiency,
+            settings.branching_ratio,
+            settings.conversion_coefficient,
+            estimate.detected_conversion_electrons
+        )),
+    }
+}
+
+fn run_report(args: ReportArgs) -> Result<String, CliError> {
+    let project =
+        Project::load(&args.project).map_err(|e| CliError::Project(args.project.clone(), e))?;
+    for note in &project.migration_notes {
+        eprintln!("warning: {note}");
+    }
+    let report = report::generate(&project, args.report_format);
+
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, report).map_err(|e| CliError::Write(path.clone(), e))?;
+            Ok(format!("Wrote {}", path.display()))
+        }
+        None => Ok(report),
+    }
+}
+
+impl Cli {
+    /// Runs the selected estimator and returns the formatted results.
+    pub fn run(self) -> Result<String, CliError> {
+        match self.command {
+            Command::Sps(args) => run_sps(*args, self.format),
+            Command::Cebra(args) => run_cebra(args, self.format),
+            Command::Icespice(args) => run_icespice(args, self.format),
+            Command::Report(args) => run_report(args),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &str) -> Result<String, CliError> {
+        Cli::try_parse_from(
+            std::iter::once("sps_beam_time_estimator").chain(args.split_whitespace()),
+        )
+        .unwrap()
+        .run()
+    }
+
+    #[test]
+    fn sps_flags_override_the_defaults() {
+        let output = run("sps").unwrap();
+        assert!(output.contains("Estimated Time:    69000 s | 19.17 h | 0.80 d"));
+        let output = run("sps --cross-section 50 --desired-counts 2000").unwrap();
+        assert!(output.contains("Cross Section:     50 µb/sr"));
+        assert!(output.contains("Estimated Time:    275998 s | 76.67 h | 3.19 d"));
+    }
+
+    #[test]
+    fn cebra_counts_every_detector() {
+        let output = run(
+            "cebra --format json --particle-counts 10000 --energy 1000 --intensity 50 \
+             --detector A:10,1000,0,1 --detector B:0,1,4,500",
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        // 10 % · e^−1 and 4 % · e^−2 of 5000 γ rays
+        let expected = [10.0 * (-1.0f64).exp(), 4.0 * (-2.0f64).exp()];
+        for (detector, efficiency) in json["estimate"]["detectors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .zip(expected)
+        {
+            assert!((detector["efficiency"].as_f64().unwrap() - efficiency).abs() < 1e-12);
+            assert!((detector["counts"].as_f64().unwrap() - 50.0 * efficiency).abs() < 1e-9);
+        }
+        let total = json["estimate"]["total_counts"].as_f64().unwrap();
+        assert!((total - 50.0 * expected.iter().sum::<f64>()).abs() < 1e-9);
+
+        let output = run("cebra --preset reu2023").unwrap();
+        assert_eq!(output.lines().count(), 3 + 5 + 1);
+        assert!(parse_detector("A:1,2,3").is_err());
+        assert!(parse_detector("A 1,2,3,4").is_err());
+    }
+
+    #[test]
+    fn icespice_detects_the_transmitted_electrons() {
+        let output = run("icespice").unwrap();
+        // 10000 · 1.29 % · 30.7 %
+        assert!(output.ends_with("Detected Conversion Electrons: 40"));
+        let output = run("icespice --format json --branching-ratio 50").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let electrons = json["estimate"]["detected_conversion_electrons"]
+            .as_f64()
+            .unwrap();
+        assert!((electrons - 0.5 * 10000.0 * 0.0129 * 0.307).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invalid_inputs_are_reported_instead_of_a_time() {
+        match run("sps --cross-section 0") {
+            Err(CliError::Estimate(EstimateError::NotPositive { parameter, .. })) => {
+                assert_eq!(parameter, "Cross section");
+            }
+            other => panic!("{other:?}"),
+        }
+        assert!(matches!(
+            run("icespice --branching-ratio 120"),
+            Err(CliError::Estimate(EstimateError::OutOfRange { .. }))
+        ));
+    }
+
+    #[test]
+    fn micro_units_take_ascii_spellings() {
+        let seconds = |args: &str| {
+            let output = run(&format!("sps --format json {args}")).unwrap();
+            let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+            json["estimate"]["seconds"].as_f64().unwrap()
+        };
+        let micro = seconds(
+            "--cross-section 100 --cross-section-unit µb/sr \
+             --target-density 50 --target-density-unit µg/cm^2",
+        );
+        let ascii = seconds(
+            "--cross-section 100 --cross-section-unit ub/sr \
+             --target-density 50 --target-density-unit ug/cm^2",
+        );
+        assert_eq!(ascii, micro);
+        let milli = seconds(
+            "--cross-section 0.1 --cross-section-unit mb/sr \
+             --target-density 0.05 --target-density-unit mg/cm^2",
+        );
+        assert!((milli - micro).abs() < 1e-9 * micro);
+    }
+
+    #[test]
+    fn assays_are_parsed_from_the_command_line() {
+        let assay = parse_assay("Ca: 48=95.9, 40=4.1").unwrap();
+        assert_eq!(assay.element, "Ca");
+        assert_eq!(
+            assay.isotopes,
+            [
+                AssayIsotope {
+                    a: 48,
+                    percent: 95.9
+                },
+                AssayIsotope {
+                    a: 40,
+                    percent: 4.1
+                },
+            ]
+        );
+        assert!(parse_assay("Ca 48=95.9").is_err());
+        assert!(parse_assay("Ca:48").is_err());
+        assert!(parse_assay("Ca:60=100").is_err());
+        assert_eq!(parse_assay("N: 15=99.5, 14=0.5").unwrap().z(), Ok(7));
+        assert!(parse_target("Ca(OH)2").is_ok());
+        assert!(parse_target("Ca(OH").is_err());
+    }
+
+    #[test]
+    fn failing_add_on_keeps_the_estimate() {
+        let output = run(
+            "sps --target 52Cr --beam d --beam-energy 16 --angle 20 --ejectile p \
+             --center-excitation 30",
+        )
+        .unwrap();
+        assert!(output.contains("Estimated Time:"));
+        assert!(output.contains("Warning:           Lab/CM: the state at 30 MeV cannot be reached"));
+    }
+
+    #[test]
+    fn add_on_warnings_are_listed_in_json() {
+        let output = run(
+            "sps --target 52Cr --beam d --beam-energy 16 --angle 20 --ejectile p \
+             --center-excitation 30 --format json",
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert!(json["estimate"]["hours"].as_f64().unwrap() > 0.0);
+        assert_eq!(json["warnings"][0]["section"], "Lab/CM");
+        assert!(json.get("field_setting").is_none());
+    }
+}
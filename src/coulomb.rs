@@ -0,0 +1,223 @@
+//! Coulomb wave functions F_L(η, ρ), G_L(η, ρ) and Coulomb phase shifts for the scattering
+//! calculations.
+//!
+//! The wave functions follow Steed's method (A. R. Barnett, Comput. Phys. Commun. 21 (1981)
+//! 297): the continued fraction CF1 gives F'_L/F_L at the highest L, downward recurrence the
+//! unnormalized F_L, the complex continued fraction CF2 gives (G'_0 + iF'_0)/(G_0 + iF_0), the
+//! Wronskian G F' − F G' = 1 fixes the normalization and upward recurrence gives G_L.
+
+use super::complex::Complex;
+
+const ACCURACY: f64 = 1e-14;
+const MAX_ITERATIONS: usize = 100_000;
+const TINY: f64 = 1e-300;
+const RESCALE: f64 = 1e250; // largest F_L kept during the downward recurrence
+
+/// F_L, G_L and their derivatives with respect to ρ for L = 0, 1, ..., l_max.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoulombWaves {
+    pub f: Vec<f64>,
+    pub fp: Vec<f64>,
+    pub g: Vec<f64>,
+    pub gp: Vec<f64>,
+}
+
+impl CoulombWaves {
+    /// Coulomb wave functions at `rho` > 0 for the Sommerfeld parameter `eta`. `None` if a
+    /// continued fraction does not converge, i.e. far inside the Coulomb turning point.
+    pub fn new(eta: f64, rho: f64, l_max: usize) -> Option<Self> {
+        if rho.is_nan() || rho <= 0.0 {
+            return None;
+        }
+        let s = |l: f64| l / rho + eta / l;
+        let r = |l: f64| (1.0 + (eta / l).powi(2)).sqrt();
+
+        // CF1 by the modified Lentz method for F'_L/F_L at L = l_max
+        let top = l_max as f64;
+        let mut ratio = s(top + 1.0);
+        let mut c = ratio;
+        let mut d = 0.0;
+        let mut converged = false;
+        for k in 1..MAX_ITERATIONS {
+            let l = top + k as f64;
+            let a = -r(l).powi(2);
+            let b = s(l) + s(l + 1.0);
+            d = b + a * d;
+            if d == 0.0 {
+                d = TINY;
+            }
+            c = b + a / c;
+            if c == 0.0 {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = c * d;
+            ratio *= delta;
+            if (delta - 1.0).abs() < ACCURACY {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return None;
+        }
+
+        // Downward recurrence R_L F_{L-1} = S_L F_L + F'_L, F'_{L-1} = S_L F_{L-1} - R_L F_L
+        let mut f = vec![0.0; l_max + 1];
+        let mut fp = vec![0.0; l_max + 1];
+        f[l_max] = 1e-30;
+        fp[l_max] = ratio * f[l_max];
+        for l in (1..=l_max).rev() {
+            let lf = l as f64;
+            f[l - 1] = (s(lf) * f[l] + fp[l]) / r(lf);
+            fp[l - 1] = s(lf) * f[l - 1] - r(lf) * f[l];
+            if f[l - 1].abs() > RESCALE {
+                for value in f[l - 1..].iter_mut().chain(fp[l - 1..].iter_mut()) {
+                    *value /= RESCALE;
+                }
+            }
+        }
+
+        // CF2 at L = 0: p + iq = i (1 - η/ρ) + (i/ρ) a_1 / (b_1 + a_2 / (b_2 + ...)) with
+        // a_k = (iη + k - 1)(iη + k) and b_k = 2 (ρ - η + ik)
+        let a = |k: f64| Complex::new(k - 1.0, eta) * Complex::new(k, eta);
+        let b = |k: f64| Complex::new(2.0 * (rho - eta), 2.0 * k);
+        let mut denominator = b(1.0);
+        let mut c = denominator;
+        let mut d = Complex::ZERO;
+        let mut converged = false;
+        for k in 2..MAX_ITERATIONS {
+            let (a, b) = (a(k as f64), b(k as f64));
+            d = b + a * d;
+            if d.norm_sqr() == 0.0 {
+                d = Complex::from(TINY);
+            }
+            c = b + a / c;
+            if c.norm_sqr() == 0.0 {
+                c = Complex::from(TINY);
+            }
+            d = Complex::ONE / d;
+            let delta = c * d;
+            denominator = denominator * delta;
+            if (delta - Complex::ONE).abs() < ACCURACY {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return None;
+        }
+        let i = Complex::I;
+        let pq = i * (1.0 - eta / rho) + i * a(1.0) / denominator / rho;
+        let (p, q) = (pq.re, pq.im);
+
+        // Normalization from the Wronskian with G_0 = γ F_0, γ = (F'_0/F_0 - p)/q
+        let ratio0 = fp[0] / f[0];
+        let gamma = (ratio0 - p) / q;
+        let f0 = (1.0 / (q * (1.0 + gamma * gamma))).sqrt().copysign(f[0]);
+        let norm = f0 / f[0];
+        for value in f.iter_mut().chain(fp.iter_mut()) {
+            *value *= norm;
+        }
+
+        // Upward recurrence R_L G_L = S_L G_{L-1} - G'_{L-1}, G'_L = R_L G_{L-1} - S_L G_L
+        let mut g = vec![0.0; l_max + 1];
+        let mut gp = vec![0.0; l_max + 1];
+        g[0] = gamma * f0;
+        gp[0] = p * g[0] - q * f0;
+        for l in 1..=l_max {
+            let lf = l as f64;
+            g[l] = (s(lf) * g[l - 1] - gp[l - 1]) / r(lf);
+            gp[l] = r(lf) * g[l - 1] - s(lf) * g[l];
+        }
+
+        Some(Self { f, fp, g, gp })
+    }
+
+    /// Outgoing wave H⁺_L = G_L + i F_L.
+    pub fn h_plus(&self, l: usize) -> Complex {
+        Complex::new(self.g[l], self.f[l])
+    }
+
+    /// Incoming wave H⁻_L = G_L − i F_L.
+    pub fn h_minus(&self, l: usize) -> Complex {
+        Complex::new(self.g[l], -self.f[l])
+    }
+}
+
+/// Coulomb phase shifts σ_L = arg Γ(L + 1 + iη) for L = 0, 1, ..., l_max.
+pub fn coulomb_phases(eta: f64, l_max: usize) -> Vec<f64> {
+    // Stirling series for ln Γ(z) at a large L, then down with σ_{L-1} = σ_L − atan(η/L)
+    let top = l_max.max(50);
+    let z = Complex::new(top as f64 + 1.0, eta);
+    let w = Complex::ONE / z;
+    let w2 = w * w;
+    let series = w * (1.0 / 12.0) - w * w2 * (1.0 / 360.0) + w * w2 * w2 * (1.0 / 1260.0);
+    let mut sigma = (z.re - 0.5) * z.arg() + eta * z.abs().ln() - eta + series.im;
+
+    let mut phases = vec![0.0; l_max + 1];
+    for l in (0..=top).rev() {
+        if l <= l_max {
+            phases[l] = sigma;
+        }
+        if l > 0 {
+            sigma -= (eta / l as f64).atan();
+        }
+    }
+    phases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn without_charge_the_waves_are_riccati_bessel_functions() {
+        let rho = 2.5f64;
+        let waves = CoulombWaves::new(0.0, rho, 1).unwrap();
+        let (sin, cos) = rho.sin_cos();
+        for (actual, expected) in [
+            (waves.f[0], sin),
+            (waves.g[0], cos),
+            (waves.fp[0], cos),
+            (waves.gp[0], -sin),
+            (waves.f[1], sin / rho - cos),
+            (waves.g[1], cos / rho + sin),
+        ] {
+            assert!((actual - expected).abs() < 1e-10, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn waves_satisfy_the_wronskian_and_the_asymptotic_form() {
+        let eta = 1.0;
+        let sigma0 = coulomb_phases(eta, 0)[0];
+        for rho in [0.5, 5.0, 30.0, 500.0] {
+            let waves = CoulombWaves::new(eta, rho, 10).unwrap();
+            for l in 0..=10 {
+                let wronskian = waves.g[l] * waves.fp[l] - waves.f[l] * waves.gp[l];
+                assert!((wronskian - 1.0).abs() < 1e-10, "L = {l}, ρ = {rho}");
+            }
+        }
+        // F₀ → sin(ρ − η ln 2ρ + σ₀) up to terms of order η/ρ
+        let rho = 500.0;
+        let waves = CoulombWaves::new(eta, rho, 0).unwrap();
+        let theta = rho - eta * (2.0 * rho).ln() + sigma0;
+        assert!((waves.f[0] - theta.sin()).abs() < 5e-3);
+        assert!((waves.g[0] - theta.cos()).abs() < 5e-3);
+    }
+
+    #[test]
+    fn phases_are_arguments_of_the_gamma_function() {
+        // arg Γ(1 + i) = −0.3016403205 (Abramowitz & Stegun, Table 6.7)
+        let phases = coulomb_phases(1.0, 3);
+        assert!((phases[0] + 0.3016403205).abs() < 1e-9);
+        for l in 1..=3 {
+            let step = phases[l] - phases[l - 1];
+            assert!((step - (1.0 / l as f64).atan()).abs() < 1e-12);
+        }
+        assert!(coulomb_phases(0.0, 5)
+            .iter()
+            .all(|&sigma| sigma.abs() < 1e-12));
+    }
+}
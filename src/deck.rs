@@ -0,0 +1,545 @@
+//! Input decks for the DWBA codes Ptolemy and FRESCO, written from the reaction of the SPS
+//! settings, so that the reaction parameters of a proposal are only entered once.
+//!
+//! The decks describe the same zero-range DWBA as the built-in [`dwba`](super::dwba) for a list
+//! of final states, each given by its excitation energy and the orbit of the transferred
+//! nucleon. Binding energies follow from the masses of the reaction. The optical potentials of
+//! both channels are the chosen global potentials at the beam energy and at the lab energy of
+//! the ejectile for each state. The bound nucleon sits in the Woods–Saxon well of the built-in
+//! DWBA, whose depth the codes adjust to the binding energy. Final states are written as
+//! J = j with parity (−1)^l, as on a spin-0 target.
+
+use super::dwba::{
+    exit_energy, TransferError, TransferState, Vertex, BOUND_DIFFUSENESS, BOUND_RADIUS,
+    BOUND_SPIN_ORBIT, BOUND_SPIN_ORBIT_RADIUS,
+};
+use super::kinematics::{Particle, Reaction, ATOMIC_MASS_UNIT, ELECTRON_MASS};
+use super::optical::{GlobalPotential, OpticalError, OpticalParameters};
+
+const BOUND_DEPTH: f64 = 50.0; // MeV, starting depth of the FRESCO search
+const FRESCO_STEP: f64 = 0.05; // fm, hcm
+const FRESCO_MATCHING_RADIUS: f64 = 30.0; // fm, rmatch
+const FRESCO_MAX_J: f64 = 40.0; // jtmax
+
+/// DWBA code the decks are written for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum DeckCode {
+    #[default]
+    Ptolemy,
+    Fresco,
+}
+
+impl DeckCode {
+    pub const ALL: [DeckCode; 2] = [DeckCode::Ptolemy, DeckCode::Fresco];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeckCode::Ptolemy => "Ptolemy",
+            DeckCode::Fresco => "FRESCO",
+        }
+    }
+}
+
+/// States, angles and potentials of the decks.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct DeckSettings {
+    pub code: DeckCode,
+    pub states: Vec<TransferState>,
+    pub min_angle: f64,                    // deg, CM
+    pub max_angle: f64,                    // deg, CM
+    pub angle_step: f64,                   // deg
+    pub entrance: Option<GlobalPotential>, // the potential of the beam if not chosen
+    pub exit: Option<GlobalPotential>,     // the potential of the ejectile if not chosen
+    pub spin_orbit: bool,                  // keep the spin-orbit terms of the optical potentials
+}
+
+impl Default for DeckSettings {
+    fn default() -> Self {
+        Self {
+            code: DeckCode::default(),
+            states: vec![],
+            min_angle: 0.0,
+            max_angle: 60.0,
+            angle_step: 1.0,
+            entrance: None,
+            exit: None,
+            spin_orbit: true,
+        }
+    }
+}
+
+/// One input file.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct Deck {
+    pub file_name: String,
+    pub contents: String,
+}
+
+/// Optical potential of one channel of one state.
+struct DeckChannel {
+    potential: GlobalPotential,
+    parameters: OpticalParameters,
+    energy: f64, // MeV, lab
+}
+
+impl DeckChannel {
+    /// `potential`, or that of `projectile`, on `target` at `energy` MeV.
+    fn new(
+        potential: Option<GlobalPotential>,
+        projectile: &Particle,
+        target: &Particle,
+        energy: f64,
+        spin_orbit: bool,
+    ) -> Result<Self, TransferError> {
+        let potential = potential
+            .or_else(|| GlobalPotential::for_beam(projectile.z, projectile.a))
+            .ok_or_else(|| OpticalError::NoPotential(projectile.short_name()))?;
+        let mut parameters = potential.parameters(target.z, target.a, energy);
+        if !spin_orbit {
+            parameters.spin_orbit.depth = 0.0;
+            parameters.spin_orbit_imaginary = 0.0;
+        }
+        Ok(Self {
+            potential,
+            parameters,
+            energy,
+        })
+    }
+
+    fn describe(&self, projectile: &Particle, target: &Particle) -> String {
+        format!(
+            "{}, {} + {} at {:.3} MeV",
+            self.potential.name(),
+            code_name(projectile),
+            target.name(),
+            self.energy
+        )
+    }
+}
+
+/// One final state with everything the decks need.
+struct DeckState<'a> {
+    state: &'a TransferState,
+    binding: f64, // MeV
+    entrance: DeckChannel,
+    exit: DeckChannel,
+}
+
+/// Input decks of `settings.code` for `reaction` at `beam_energy` MeV: one Ptolemy deck for
+/// every state together, or one FRESCO deck per state.
+pub fn write_decks(
+    settings: &DeckSettings,
+    reaction: &Reaction,
+    beam_energy: f64,
+) -> Result<Vec<Deck>, TransferError> {
+    let vertex = Vertex::of(reaction)?;
+    let states = settings
+        .states
+        .iter()
+        .map(|state| {
+            if (state.j - state.l as f64).abs() != 0.5 {
+                return Err(TransferError::InvalidSpin {
+                    l: state.l,
+                    j: state.j,
+                });
+            }
+            let binding = vertex.binding(reaction, state.excitation);
+            if binding <= 0.0 {
+                return Err(TransferError::Unbound(binding));
+            }
+            let exit_energy = exit_energy(reaction, beam_energy, state.excitation)?;
+            Ok(DeckState {
+                state,
+                binding,
+                entrance: DeckChannel::new(
+                    settings.entrance,
+                    &reaction.beam,
+                    &reaction.target,
+                    beam_energy,
+                    settings.spin_orbit,
+                )?,
+                exit: DeckChannel::new(
+                    settings.exit,
+                    &reaction.ejectile,
+                    &reaction.recoil,
+                    exit_energy,
+                    settings.spin_orbit,
+                )?,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if states.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let base = format!(
+        "{}_{}_{}_{}",
+        reaction.target.name(),
+        code_name(&reaction.beam),
+        code_name(&reaction.ejectile),
+        reaction.recoil.name()
+    );
+    Ok(match settings.code {
+        DeckCode::Ptolemy => vec![Deck {
+            file_name: format!("{base}.in"),
+            contents: ptolemy(settings, reaction, beam_energy, &states),
+        }],
+        DeckCode::Fresco => states
+            .iter()
+            .map(|state| Deck {
+                file_name: format!(
+                    "{base}_{:.3}_{}.in",
+                    state.state.excitation,
+                    state.state.orbit().replace('/', "-")
+                ),
+                contents: fresco(settings, reaction, &vertex, beam_energy, state),
+            })
+            .collect(),
+    })
+}
+
+/// Name of a nucleus in the reaction notation of the codes, e.g. `d`, `3He` or `48Ca`.
+fn code_name(particle: &Particle) -> String {
+    match (particle.z, particle.a) {
+        (0, 1) => "n".to_string(),
+        (1, 1) => "p".to_string(),
+        (1, 2) => "d".to_string(),
+        (1, 3) => "t".to_string(),
+        (2, 4) => "a".to_string(),
+        _ => particle.name(),
+    }
+}
+
+/// Atomic mass in u of the neutral atom.
+fn atomic_mass(particle: &Particle) -> f64 {
+    (particle.mass + particle.z as f64 * ELECTRON_MASS) / ATOMIC_MASS_UNIT
+}
+
+/// Spin and parity of the final state, e.g. `3/2-`.
+fn spin_parity(state: &TransferState) -> String {
+    let parity = match state.l % 2 {
+        0 => '+',
+        _ => '-',
+    };
+    format!("{}/2{parity}", (2.0 * state.j).round())
+}
+
+fn ptolemy(
+    settings: &DeckSettings,
+    reaction: &Reaction,
+    beam_energy: f64,
+    states: &[DeckState<'_>],
+) -> String {
+    let mut deck = format!(
+        "$ {} at {beam_energy} MeV, zero-range DWBA written by the SPS beam time estimator\n",
+        reaction.name()
+    );
+    let helion = reaction.beam.z == 2 || reaction.ejectile.z == 2;
+    for state in states {
+        let s = state.state;
+        deck += &format!(
+            "$ {} at Ex = {:.3} MeV, binding {:.3} MeV\n",
+            s.orbit(),
+            s.excitation,
+            state.binding
+        );
+        deck += "reset\nr0target\nprint 0\n";
+        deck += &format!(
+            "REACTION: {}({},{}){}({} {:.3}) ELAB={beam_energy:.3}\n",
+            code_name(&reaction.target),
+            code_name(&reaction.beam),
+            code_name(&reaction.ejectile),
+            code_name(&reaction.recoil),
+            spin_parity(s),
+            s.excitation
+        );
+        match helion {
+            false => {
+                deck += "PARAMETERSET dpsb\n";
+                deck += "PROJECTILE\nwavefunction av18\nr0=1 a=0.5 l=0 rc0=1.2\n;\n";
+            }
+            true => {
+                deck += "PARAMETERSET alpha3\n";
+                deck += "PROJECTILE\nwavefunction phiffer\nnodes=0 l=0 jp=1/2 spfacp=1.31 v=179.94 r=0.54 a=0.68 param1=0.64 param2=1.13 rc=2.0\n;\n";
+            }
+        }
+        deck += &format!(
+            "TARGET\nnodes={} l={} jp={}/2\nr0={BOUND_RADIUS} a={BOUND_DIFFUSENESS}\nvso={BOUND_SPIN_ORBIT} rso0={BOUND_SPIN_ORBIT_RADIUS} aso={BOUND_DIFFUSENESS}\nrc0={BOUND_RADIUS}\n;\n",
+            s.nodes,
+            s.l,
+            (2.0 * s.j).round()
+        );
+        for (block, channel, projectile, target) in [
+            (
+                "INCOMING",
+                &state.entrance,
+                &reaction.beam,
+                &reaction.target,
+            ),
+            (
+                "OUTGOING",
+                &state.exit,
+                &reaction.ejectile,
+                &reaction.recoil,
+            ),
+        ] {
+            let p = &channel.parameters;
+            deck += &format!("{block}\n$ {}\n", channel.describe(projectile, target));
+            deck += &format!(
+                "v={:.4} r0={:.4} a={:.4}\n",
+                p.real.depth, p.real.radius, p.real.diffuseness
+            );
+            deck += &format!(
+                "vi={:.4} ri0={:.4} ai={:.4}\n",
+                p.volume.depth, p.volume.radius, p.volume.diffuseness
+            );
+            deck += &format!(
+                "vsi={:.4} rsi0={:.4} asi={:.4}\n",
+                p.surface.depth, p.surface.radius, p.surface.diffuseness
+            );
+            if p.spin_orbit.depth != 0.0 {
+                deck += &format!(
+                    "vso={:.4} rso0={:.4} aso={:.4}\n",
+                    p.spin_orbit.depth, p.spin_orbit.radius, p.spin_orbit.diffuseness
+                );
+                deck += &format!(
+                    "vsoi={:.4} rsoi0={:.4} asoi={:.4}\n",
+                    p.spin_orbit_imaginary, p.spin_orbit.radius, p.spin_orbit.diffuseness
+                );
+            }
+            deck += &format!("rc0={:.4}\n;\n", p.coulomb_radius);
+        }
+        deck += &format!(
+            "anglemin={} anglemax={} anglestep={}\n;\n",
+            settings.min_angle, settings.max_angle, settings.angle_step
+        );
+    }
+    deck
+}
+
+/// `&POT` lines of potential `kp` of a channel on `target`.
+fn fresco_potential(kp: usize, target: &Particle, parameters: &OpticalParameters) -> String {
+    let p = parameters;
+    let mut lines = format!(
+        " &POT kp={kp} ap=0 at={} rc={:.4} /\n",
+        target.a, p.coulomb_radius
+    );
+    lines += &format!(
+        " &POT kp={kp} type=1 p1={:.4} p2={:.4} p3={:.4} p4={:.4} p5={:.4} p6={:.4} /\n",
+        p.real.depth,
+        p.real.radius,
+        p.real.diffuseness,
+        p.volume.depth,
+        p.volume.radius,
+        p.volume.diffuseness
+    );
+    if p.surface.depth != 0.0 {
+        lines += &format!(
+            " &POT kp={kp} type=2 p1=0.0 p2={:.4} p3={:.4} p4={:.4} p5={:.4} p6={:.4} /\n",
+            p.surface.radius,
+            p.surface.diffuseness,
+            p.surface.depth,
+            p.surface.radius,
+            p.surface.diffuseness
+        );
+    }
+    if p.spin_orbit.depth != 0.0 {
+        lines += &format!(
+            " &POT kp={kp} type=3 p1={:.4} p2={:.4} p3={:.4} p4={:.4} p5={:.4} p6={:.4} /\n",
+            p.spin_orbit.depth,
+            p.spin_orbit.radius,
+            p.spin_orbit.diffuseness,
+            p.spin_orbit_imaginary,
+            p.spin_orbit.radius,
+            p.spin_orbit.diffuseness
+        );
+    }
+    lines
+}
+
+fn fresco(
+    settings: &DeckSettings,
+    reaction: &Reaction,
+    vertex: &Vertex,
+    beam_energy: f64,
+    state: &DeckState<'_>,
+) -> String {
+    let s = state.state;
+    let Reaction {
+        beam,
+        target,
+        ejectile,
+        recoil,
+    } = reaction;
+    let (spin_a, spin_b) = vertex.spins;
+    let parity = match s.l % 2 {
+        0 => 1,
+        _ => -1,
+    };
+    let mut deck = format!(
+        "{} {} Ex = {:.3} MeV at {beam_energy} MeV, zero-range DWBA\n",
+        reaction.name(),
+        s.orbit(),
+        s.excitation
+    );
+    deck += "NAMELIST\n";
+    deck += &format!(
+        " &FRESCO hcm={FRESCO_STEP} rmatch={FRESCO_MATCHING_RADIUS} jtmin=0.0 jtmax={FRESCO_MAX_J} absend=-1.0\n     thmin={} thmax={} thinc={}\n     iter=1 chans=1 smats=2 xstabl=1\n     elab={beam_energy} /\n\n",
+        settings.min_angle, settings.max_angle, settings.angle_step
+    );
+    deck += &format!(
+        " &PARTITION namep='{}' massp={:.6} zp={} namet='{}' masst={:.6} zt={} qval=0.0 nex=1 /\n",
+        code_name(beam),
+        atomic_mass(beam),
+        beam.z,
+        target.name(),
+        atomic_mass(target),
+        target.z
+    );
+    deck += &format!(" &STATES jp={spin_a} bandp=1 ep=0.0 cpot=1 jt=0.0 bandt=1 et=0.0 /\n");
+    deck += &format!(
+        " &PARTITION namep='{}' massp={:.6} zp={} namet='{}' masst={:.6} zt={} qval={:.4} nex=1 /\n",
+        code_name(ejectile),
+        atomic_mass(ejectile),
+        ejectile.z,
+        recoil.name(),
+        atomic_mass(recoil),
+        recoil.z,
+        reaction.q_value()
+    );
+    deck += &format!(
+        " &STATES jp={spin_b} bandp=1 ep=0.0 cpot=2 jt={} bandt={parity} et={:.4} /\n",
+        s.j, s.excitation
+    );
+    deck += " &partition /\n\n";
+    deck += &format!("! {}\n", state.entrance.describe(beam, target));
+    deck += &fresco_potential(1, target, &state.entrance.parameters);
+    deck += &format!("! {}\n", state.exit.describe(ejectile, recoil));
+    deck += &fresco_potential(2, recoil, &state.exit.parameters);
+    let core = match vertex.stripping {
+        true => target,
+        false => recoil,
+    };
+    deck += &format!("! binding potential of the transferred nucleon\n &POT kp=3 ap=0 at={} rc={BOUND_RADIUS} /\n", core.a);
+    deck += &format!(
+        " &POT kp=3 type=1 p1={BOUND_DEPTH} p2={BOUND_RADIUS} p3={BOUND_DIFFUSENESS} /\n &POT kp=3 type=3 p1={BOUND_SPIN_ORBIT} p2={BOUND_SPIN_ORBIT_RADIUS} p3={BOUND_DIFFUSENESS} /\n"
+    );
+    deck += " &pot /\n\n";
+    let (core_partition, composite_partition) = match vertex.stripping {
+        true => (1, 2),
+        false => (2, 1),
+    };
+    deck += &format!(
+        " &OVERLAP kn1=1 ic1={core_partition} ic2={composite_partition} in=2 kind=0 nn={} l={} sn=0.5 j={} kbpot=3 be={:.4} isc=1 ipc=0 /\n",
+        s.nodes + 1,
+        s.l,
+        s.j,
+        state.binding
+    );
+    deck += " &overlap /\n\n";
+    let post_prior = match vertex.stripping {
+        true => 1, // the n-p interaction of the deuteron sits in the entrance channel
+        false => 0,
+    };
+    deck += &format!(
+        " &COUPLING icto=2 icfrom=1 kind=7 ip1={post_prior} ip2=-1 p1={:.2} /\n",
+        -vertex.d0_squared.sqrt()
+    );
+    deck += &format!(
+        " &CFP in=2 ib=1 ia=1 kn=1 a={:.4} /\n &cfp /\n &coupling /\n",
+        s.spectroscopic_factor.sqrt()
+    );
+    deck
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16O(d,p)17O
+    fn o16_dp() -> Reaction {
+        let [beam, target, ejectile, recoil] =
+            [(1, 2), (8, 16), (1, 1), (8, 17)].map(|(z, a)| Particle::find(z, a).unwrap());
+        Reaction::new(beam, target, ejectile, recoil).unwrap()
+    }
+
+    fn deck_settings(code: DeckCode) -> DeckSettings {
+        DeckSettings {
+            code,
+            states: vec![
+                TransferState::from_orbit(0.0, "1d5/2").unwrap(),
+                TransferState::from_orbit(0.871, "2s1/2").unwrap(),
+            ],
+            min_angle: 5.0,
+            max_angle: 90.0,
+            angle_step: 2.5,
+            ..Default::default()
+        }
+    }
+
+    /// Whether `deck` has all of `lines`, in order.
+    fn has_lines(deck: &str, lines: &[&str]) -> bool {
+        let mut rest = deck.lines();
+        lines.iter().all(|line| rest.any(|l| l == *line))
+    }
+
+    #[test]
+    fn ptolemy_deck_has_both_states() {
+        let settings = deck_settings(DeckCode::Ptolemy);
+        let decks = write_decks(&settings, &o16_dp(), 16.0).unwrap();
+        assert_eq!(decks.len(), 1);
+        assert_eq!(decks[0].file_name, "16O_d_p_17O.in");
+        let deck = &decks[0].contents;
+        assert!(has_lines(
+            deck,
+            &[
+                // S_n(17O) = 4.143 MeV
+                "$ 1d5/2 at Ex = 0.000 MeV, binding 4.143 MeV",
+                "REACTION: 16O(d,p)17O(5/2+ 0.000) ELAB=16.000",
+                "PARAMETERSET dpsb",
+                "nodes=0 l=2 jp=5/2",
+                "INCOMING",
+                "$ An–Cai, d + 16O at 16.000 MeV",
+                "OUTGOING",
+                "anglemin=5 anglemax=90 anglestep=2.5",
+                "$ 2s1/2 at Ex = 0.871 MeV, binding 3.272 MeV",
+                "REACTION: 16O(d,p)17O(1/2+ 0.871) ELAB=16.000",
+                "nodes=1 l=0 jp=1/2",
+                "anglemin=5 anglemax=90 anglestep=2.5",
+            ]
+        ));
+        assert_eq!(deck.matches("\nreset\n").count(), 2);
+    }
+
+    #[test]
+    fn fresco_decks_have_masses_energies_and_the_orbit() {
+        let settings = deck_settings(DeckCode::Fresco);
+        let decks = write_decks(&settings, &o16_dp(), 16.0).unwrap();
+        let names: Vec<_> = decks.iter().map(|deck| deck.file_name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["16O_d_p_17O_0.000_1d5-2.in", "16O_d_p_17O_0.871_2s1-2.in"]
+        );
+        assert!(has_lines(
+            &decks[1].contents,
+            &[
+                "     thmin=5 thmax=90 thinc=2.5",
+                "     elab=16 /",
+                " &PARTITION namep='d' massp=2.014102 zp=1 namet='16O' masst=15.994915 zt=8 qval=0.0 nex=1 /",
+                " &STATES jp=1 bandp=1 ep=0.0 cpot=1 jt=0.0 bandt=1 et=0.0 /",
+                // Q = S_n(17O) − B(d) = 4.143 − 2.225 MeV
+                " &PARTITION namep='p' massp=1.007825 zp=1 namet='17O' masst=16.999132 zt=8 qval=1.9186 nex=1 /",
+                " &STATES jp=0.5 bandp=1 ep=0.0 cpot=2 jt=0.5 bandt=1 et=0.8710 /",
+                " &POT kp=3 ap=0 at=16 rc=1.25 /",
+                // one node in FRESCO's counting from 1
+                " &OVERLAP kn1=1 ic1=1 ic2=2 in=2 kind=0 nn=2 l=0 sn=0.5 j=0.5 kbpot=3 be=3.2721 isc=1 ipc=0 /",
+                " &COUPLING icto=2 icfrom=1 kind=7 ip1=1 ip2=-1 p1=-124.50 /",
+                " &CFP in=2 ib=1 ia=1 kn=1 a=1.0000 /",
+            ]
+        ));
+        assert!(decks[0]
+            .contents
+            .contains(" nn=1 l=2 sn=0.5 j=2.5 kbpot=3 be=4.1431 "));
+    }
+}
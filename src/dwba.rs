@@ -0,0 +1,862 @@
+//! Zero-range DWBA for single-nucleon transfer, (d,p), (p,d), (³He,d) and (d,³He), as an
+//! estimate of the cross section of a state before it has been measured.
+//!
+//! Entrance and exit channel are distorted by the global optical potentials of the
+//! [`optical`](super::optical) module, taken without spin-orbit term, and the transferred
+//! nucleon is bound in a Woods–Saxon well whose depth reproduces its binding energy. The
+//! zero-range amplitude
+//!
+//! T_m = D₀ ∫ χ_b⁽⁻⁾*(s_b r) φ_lm(r) χ_a⁽⁺⁾(s_a r) d³r
+//!
+//! is expanded in partial waves, with s_b = A/B for stripping and s_a = B/A for pickup. The
+//! single-particle cross section
+//!
+//! σ_sp = μ_a μ_b / (2πħ²)² · k_b/k_a · f · Σ_m |T_m|² / (2l + 1)
+//!
+//! carries the spin factor f of the light particles, 1/2 for stripping and
+//! (2s_b + 1)/((2s_a + 1)(2s_x + 1)) for pickup. A state with spectroscopic factor S then has
+//! dσ/dΩ = (2J_f + 1)/(2J_i + 1) · S · σ_sp in stripping, with J_f = j unless given, i.e.
+//! (2j + 1) S σ_sp on a spin-0 target, and S σ_sp in pickup. Cross sections are in mb/sr,
+//! energies in MeV and angles in degrees.
+
+use std::f64::consts::PI;
+
+use super::complex::Complex;
+use super::coulomb::coulomb_phases;
+use super::kinematics::{KinematicsError, Particle, Reaction};
+use super::optical::{
+    Channel, GlobalPotential, OpticalError, OpticalParameters, WoodsSaxon, CURVE_STEP, HBARC,
+};
+use super::rutherford::MB_PER_FM2;
+
+/// D₀² of the zero-range d → p + n vertex, in MeV² fm³.
+pub const D0_SQUARED_DEUTERON: f64 = 1.55e4;
+/// D₀² of the zero-range ³He → d + p vertex (Bassel, Phys. Rev. 149 (1966) 791), in MeV² fm³.
+pub const D0_SQUARED_HELION: f64 = 2.95e4;
+
+/// Woods–Saxon well of the bound nucleon, with the spin-orbit term per unit of l·σ.
+pub const BOUND_RADIUS: f64 = 1.25; // fm, reduced
+pub const BOUND_DIFFUSENESS: f64 = 0.65; // fm
+pub const BOUND_SPIN_ORBIT: f64 = 6.0; // MeV
+pub const BOUND_SPIN_ORBIT_RADIUS: f64 = 1.10; // fm, reduced
+
+const ORBIT_LETTERS: &str = "spdfghiklmnoqrtuv"; // spectroscopic notation of l
+const INTEGRATION_RADIUS: f64 = 30.0; // fm, of the radial integrals
+const MAX_DEPTH: f64 = 500.0; // MeV, of the depth search
+const DEPTH_ACCURACY: f64 = 1e-9; // MeV
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransferError {
+    /// The reaction is not a single-nucleon transfer the DWBA handles.
+    NotTransfer(String),
+    /// j is not l ± ½.
+    InvalidSpin { l: u32, j: f64 },
+    /// The nucleon is not bound in the final nucleus of this state.
+    Unbound(f64),
+    /// No well depth gives a bound state with the requested number of nodes.
+    NoBoundState,
+    /// The state lies above the energy available in the exit channel.
+    Closed(f64),
+    /// A distorted wave cannot be calculated.
+    OpticalModel(OpticalError),
+}
+
+impl std::fmt::Display for TransferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferError::NotTransfer(reaction) => write!(
+                f,
+                "{reaction} is not a (d,p), (p,d), (3He,d) or (d,3He) transfer for the DWBA"
+            ),
+            TransferError::InvalidSpin { l, j } => {
+                write!(f, "j = {j} is not l ± 1/2 for l = {l}")
+            }
+            TransferError::Unbound(binding) => write!(
+                f,
+                "the transferred nucleon is unbound in this state (binding energy {binding:.3} MeV)"
+            ),
+            TransferError::NoBoundState => write!(
+                f,
+                "no Woods–Saxon depth binds the nucleon with these quantum numbers"
+            ),
+            TransferError::Closed(excitation) => write!(
+                f,
+                "the state at {excitation} MeV is above the energy available in the exit channel"
+            ),
+            TransferError::OpticalModel(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for TransferError {}
+
+impl From<OpticalError> for TransferError {
+    fn from(e: OpticalError) -> Self {
+        TransferError::OpticalModel(e)
+    }
+}
+
+/// Final state populated by the transfer.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct TransferState {
+    pub excitation: f64, // MeV, of the recoil
+    pub l: u32,          // orbital angular momentum of the transferred nucleon
+    pub j: f64,          // total angular momentum of the transferred nucleon, l ± ½
+    pub nodes: u32,      // radial nodes of the bound state, without the origin
+    pub spectroscopic_factor: f64,
+    pub initial_spin: f64,       // J_i of the target
+    pub final_spin: Option<f64>, // J_f of the state, j as on a spin-0 target when not given
+}
+
+impl Default for TransferState {
+    fn default() -> Self {
+        Self {
+            excitation: 0.0,
+            l: 0,
+            j: 0.5,
+            nodes: 0,
+            spectroscopic_factor: 1.0,
+            initial_spin: 0.0,
+            final_spin: None,
+        }
+    }
+}
+
+impl TransferState {
+    /// Spin J_f of the state: the one given, else j.
+    pub fn spin(&self) -> f64 {
+        self.final_spin.unwrap_or(self.j)
+    }
+
+    /// State at `excitation` MeV with the nucleon in `orbit`, written as by
+    /// [`orbit`](Self::orbit), e.g. `2p3/2`, and a spectroscopic factor of 1.
+    pub fn from_orbit(excitation: f64, orbit: &str) -> Option<Self> {
+        let orbit = orbit.trim();
+        let letter = orbit.find(|c: char| c.is_ascii_alphabetic())?;
+        let n: u32 = orbit[..letter].parse().ok()?;
+        let rest = &orbit[letter..];
+        let l = ORBIT_LETTERS.find(rest.chars().next()?.to_ascii_lowercase())? as u32;
+        let (twice_j, two) = rest[1..].split_once('/')?;
+        let j = twice_j.parse::<f64>().ok()? / 2.0;
+        if n == 0 || two != "2" || (j - l as f64).abs() != 0.5 || j <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            excitation,
+            l,
+            j,
+            nodes: n - 1,
+            ..Default::default()
+        })
+    }
+
+    /// Shell-model orbit of the transferred nucleon, e.g. `2p3/2` for l = 1, j = 3/2 and one
+    /// node.
+    pub fn orbit(&self) -> String {
+        let letter = ORBIT_LETTERS.chars().nth(self.l as usize).unwrap_or('?');
+        format!(
+            "{}{letter}{}/2",
+            self.nodes + 1,
+            (2.0 * self.j).round() as i64
+        )
+    }
+}
+
+/// Nucleon bound in a Woods–Saxon well, on the grid of the radial integrals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundState {
+    pub binding: f64, // MeV
+    pub depth: f64,   // MeV, of the central well
+    u: Vec<f64>,      // r R(r), normalized to ∫ u² dr = 1
+}
+
+impl BoundState {
+    /// `nucleon` bound to `core` by `binding` MeV in the orbit `l`, `j` with `nodes` nodes;
+    /// the grid has `points` + 1 points `step` fm apart.
+    pub fn new(
+        core: &Particle,
+        nucleon: &Particle,
+        state: &TransferState,
+        binding: f64,
+        step: f64,
+        points: usize,
+    ) -> Result<Self, TransferError> {
+        if binding <= 0.0 {
+            return Err(TransferError::Unbound(binding));
+        }
+        let l_sigma = l_sigma(state.l, state.j)?;
+        let a13 = (core.a as f64).powf(1.0 / 3.0);
+        let z1z2 = (core.z * nucleon.z) as f64;
+        let kappa = 2.0 * core.mass * nucleon.mass / (core.mass + nucleon.mass) / HBARC.powi(2);
+        let l = state.l as f64;
+        // u'' = w u with w = fixed − depth · well, linear in the depth of the central well
+        let parameters = bound_parameters(0.0);
+        let mut fixed = vec![0.0; points + 1];
+        let mut well = vec![0.0; points + 1];
+        for i in 1..=points {
+            let r = i as f64 * step;
+            fixed[i] = kappa
+                * (parameters.central(r, a13, z1z2).re
+                    + parameters.spin_orbit(r, a13).re * l_sigma
+                    + binding)
+                + l * (l + 1.0) / r.powi(2);
+            well[i] = kappa * parameters.real.shape(r, a13);
+        }
+        let potential = |depth: f64| {
+            fixed
+                .iter()
+                .zip(&well)
+                .map(|(fixed, well)| fixed - depth * well)
+                .collect::<Vec<_>>()
+        };
+
+        // Bisection on the depth where the outward solution gains its (nodes + 1)th node
+        let (mut shallow, mut deep) = (0.0, MAX_DEPTH);
+        if outward(&potential(deep), step, state.l, points).1 <= state.nodes as usize {
+            return Err(TransferError::NoBoundState);
+        }
+        while deep - shallow > DEPTH_ACCURACY {
+            let depth = (shallow + deep) / 2.0;
+            match outward(&potential(depth), step, state.l, points).1 > state.nodes as usize {
+                true => deep = depth,
+                false => shallow = depth,
+            }
+        }
+        let depth = (shallow + deep) / 2.0;
+
+        // Outward and inward solutions joined at the well radius
+        let w = potential(depth);
+        let join = ((BOUND_RADIUS * a13 / step) as usize).clamp(2, points - 2);
+        let (mut u, _) = outward(&w, step, state.l, join + 1);
+        let decay = (kappa * binding).sqrt();
+        let mut inner = vec![0.0; points + 1];
+        inner[points] = 1e-20;
+        inner[points - 1] = 1e-20 * (decay * step).exp();
+        for i in (join..points - 1).rev() {
+            inner[i] = numerov_step(inner[i + 2], inner[i + 1], w[i + 2], w[i + 1], w[i], step);
+        }
+        let scale = u[join] / inner[join];
+        u.truncate(join);
+        u.extend(inner[join..].iter().map(|value| value * scale));
+        let norm = u.iter().map(|value| value * value).sum::<f64>() * step;
+        for value in &mut u {
+            *value /= norm.sqrt();
+        }
+        Ok(Self { binding, depth, u })
+    }
+}
+
+/// Woods–Saxon parameters of the bound nucleon for a central depth of `depth` MeV.
+fn bound_parameters(depth: f64) -> OpticalParameters {
+    OpticalParameters {
+        real: WoodsSaxon {
+            depth,
+            radius: BOUND_RADIUS,
+            diffuseness: BOUND_DIFFUSENESS,
+        },
+        spin_orbit: WoodsSaxon {
+            depth: BOUND_SPIN_ORBIT,
+            radius: BOUND_SPIN_ORBIT_RADIUS,
+            diffuseness: BOUND_DIFFUSENESS,
+        },
+        coulomb_radius: BOUND_RADIUS,
+        ..Default::default()
+    }
+}
+
+/// l·σ of the orbit, l for j = l + ½ and −(l + 1) for j = l − ½.
+fn l_sigma(l: u32, j: f64) -> Result<f64, TransferError> {
+    let l = l as f64;
+    if j == l + 0.5 {
+        Ok(l)
+    } else if j == l - 0.5 && l > 0.0 {
+        Ok(-l - 1.0)
+    } else {
+        Err(TransferError::InvalidSpin { l: l as u32, j })
+    }
+}
+
+/// u'' = w u at grid point i from points i − 1 (`current`) and i − 2 (`previous`).
+fn numerov_step(
+    previous: f64,
+    current: f64,
+    w_previous: f64,
+    w_current: f64,
+    w: f64,
+    step: f64,
+) -> f64 {
+    let h2 = step.powi(2) / 12.0;
+    (2.0 * current * (1.0 + 5.0 * h2 * w_current) - previous * (1.0 - h2 * w_previous))
+        / (1.0 - h2 * w)
+}
+
+/// Regular solution of u'' = w u up to grid point `end` and its number of nodes.
+fn outward(w: &[f64], step: f64, l: u32, end: usize) -> (Vec<f64>, usize) {
+    let mut u = vec![0.0; end + 1];
+    u[1] = step.powi(l as i32 + 1);
+    let mut nodes = 0;
+    for i in 2..=end {
+        let previous = if i == 2 { 0.0 } else { u[i - 2] };
+        u[i] = numerov_step(previous, u[i - 1], w[i - 2], w[i - 1], w[i], step);
+        if u[i].abs() > 1e100 {
+            for value in &mut u[..=i] {
+                *value /= 1e100;
+            }
+        }
+        if u[i] * u[i - 1] < 0.0 {
+            nodes += 1;
+        }
+    }
+    (u, nodes)
+}
+
+/// Wave function `u` on a grid `step` fm apart at `r` fm, by four-point Lagrange
+/// interpolation.
+fn interpolate(u: &[Complex], step: f64, r: f64) -> Complex {
+    let x = r / step;
+    let first = (x.floor() as usize).saturating_sub(1).min(u.len() - 4);
+    let mut value = Complex::ZERO;
+    for (i, &point) in u.iter().enumerate().skip(first).take(4) {
+        let weight = (first..first + 4)
+            .filter(|&k| k != i)
+            .map(|k| (x - k as f64) / (i as f64 - k as f64))
+            .product::<f64>();
+        value += point * weight;
+    }
+    value
+}
+
+/// Centre-of-mass momentum in MeV/c of two particles of masses `m1` and `m2` with the
+/// invariant mass squared `s`.
+fn cm_momentum(s: f64, m1: f64, m2: f64) -> f64 {
+    ((s - (m1 + m2).powi(2)) * (s - (m1 - m2).powi(2))).sqrt() / (2.0 * s.sqrt())
+}
+
+/// ln n! for n = 0, 1, ..., `max`.
+fn ln_factorials(max: usize) -> Vec<f64> {
+    let mut table = vec![0.0; max + 1];
+    for n in 1..=max {
+        table[n] = table[n - 1] + (n as f64).ln();
+    }
+    table
+}
+
+/// Clebsch–Gordan coefficient ⟨j1 m1 j2 m2|j m⟩ for integer angular momenta, by the Racah
+/// formula.
+fn clebsch_gordan(ln_fact: &[f64], j1: i64, m1: i64, j2: i64, m2: i64, j: i64, m: i64) -> f64 {
+    if m1 + m2 != m || j < (j1 - j2).abs() || j > j1 + j2 || m1.abs() > j1 || m2.abs() > j2 {
+        return 0.0;
+    }
+    let f = |n: i64| ln_fact[n as usize];
+    let prefactor = 0.5 * ((2 * j + 1) as f64).ln()
+        + 0.5
+            * (f(j1 + j2 - j) + f(j1 - j2 + j) + f(-j1 + j2 + j) - f(j1 + j2 + j + 1)
+                + f(j1 + m1)
+                + f(j1 - m1)
+                + f(j2 + m2)
+                + f(j2 - m2)
+                + f(j + m)
+                + f(j - m));
+    let first = 0.max(j2 - j - m1).max(j1 + m2 - j);
+    let last = (j1 + j2 - j).min(j1 - m1).min(j2 + m2);
+    (first..=last)
+        .map(|k| {
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            sign * (prefactor
+                - f(k)
+                - f(j1 + j2 - j - k)
+                - f(j1 - m1 - k)
+                - f(j2 + m2 - k)
+                - f(j - j2 + m1 + k)
+                - f(j - j1 - m2 + k))
+            .exp()
+        })
+        .sum()
+}
+
+/// Spherical harmonics Y_LM(θ, 0) for L = 0, 1, ..., `l_max` at fixed M ≥ 0, by the stable
+/// recurrence of the normalized associated Legendre functions.
+fn spherical_harmonics(m: usize, l_max: usize, theta: f64) -> Vec<f64> {
+    let (x, sin) = (theta.cos(), theta.sin());
+    let mut y = vec![0.0; l_max + 1];
+    if m > l_max {
+        return y;
+    }
+    let mut ymm = (1.0 / (4.0 * PI)).sqrt();
+    for k in 1..=m {
+        ymm *= -((2 * k + 1) as f64 / (2 * k) as f64).sqrt() * sin;
+    }
+    y[m] = ymm;
+    if m < l_max {
+        y[m + 1] = x * ((2 * m + 3) as f64).sqrt() * ymm;
+    }
+    for l in m + 2..=l_max {
+        let (lf, mf) = (l as f64, m as f64);
+        let a = ((4.0 * lf * lf - 1.0) / (lf * lf - mf * mf)).sqrt();
+        let b = (((lf - 1.0).powi(2) - mf * mf) / (4.0 * (lf - 1.0).powi(2) - 1.0)).sqrt();
+        y[l] = a * (x * y[l - 1] - b * y[l - 2]);
+    }
+    y
+}
+
+/// Light-particle side of a transfer reaction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vertex {
+    pub stripping: bool,
+    pub nucleon: Particle, // transferred
+    pub d0_squared: f64,   // MeV² fm³
+    pub spins: (f64, f64), // of beam and ejectile
+}
+
+impl Vertex {
+    /// The vertex of `reaction`, if it is one of the transfers the DWBA handles.
+    pub fn of(reaction: &Reaction) -> Result<Self, TransferError> {
+        let not_transfer = || TransferError::NotTransfer(reaction.name());
+        let (beam, ejectile) = (reaction.beam, reaction.ejectile);
+        let (stripping, nucleon, d0_squared, spins) =
+            match ((beam.z, beam.a), (ejectile.z, ejectile.a)) {
+                ((1, 2), (1, 1)) => (true, (0, 1), D0_SQUARED_DEUTERON, (1.0, 0.5)),
+                ((1, 1), (1, 2)) => (false, (0, 1), D0_SQUARED_DEUTERON, (0.5, 1.0)),
+                ((2, 3), (1, 2)) => (true, (1, 1), D0_SQUARED_HELION, (0.5, 1.0)),
+                ((1, 2), (2, 3)) => (false, (1, 1), D0_SQUARED_HELION, (1.0, 0.5)),
+                _ => return Err(not_transfer()),
+            };
+        Ok(Self {
+            stripping,
+            nucleon: Particle::find(nucleon.0, nucleon.1).map_err(|_| not_transfer())?,
+            d0_squared,
+            spins,
+        })
+    }
+
+    /// Binding energy in MeV of the nucleon in the heavier of target and recoil, for the
+    /// recoil excited to `excitation` MeV.
+    pub fn binding(&self, reaction: &Reaction, excitation: f64) -> f64 {
+        let (target, excited) = (reaction.target.mass, reaction.recoil.mass + excitation);
+        match self.stripping {
+            true => target + self.nucleon.mass - excited,
+            false => excited + self.nucleon.mass - target,
+        }
+    }
+}
+
+/// Lab energy in MeV of the ejectile on the recoil at rest with the centre-of-mass energy of the
+/// exit channel, for the recoil excited to `excitation` MeV.
+pub fn exit_energy(
+    reaction: &Reaction,
+    beam_energy: f64,
+    excitation: f64,
+) -> Result<f64, TransferError> {
+    let (beam, target) = (reaction.beam.mass, reaction.target.mass);
+    let s = (beam + target).powi(2) + 2.0 * target * beam_energy;
+    let (m3, m4) = (reaction.ejectile.mass, reaction.recoil.mass + excitation);
+    if s.sqrt() <= m3 + m4 {
+        return Err(TransferError::Closed(excitation));
+    }
+    Ok((s.sqrt() - m3 - m4) * (m3 + m4) / m4)
+}
+
+/// Cross section of the transfer at one spectrograph angle.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct TransferPoint {
+    pub angle: f64,    // deg, lab
+    pub cm_angle: f64, // deg
+    pub cm: f64,       // mb/sr, dσ/dΩ_cm
+    pub lab: f64,      // mb/sr, dσ/dΩ_lab
+}
+
+/// Zero-range DWBA of one state at one beam energy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transfer {
+    pub reaction: Reaction,
+    pub state: TransferState,
+    pub beam_energy: f64, // MeV, lab
+    pub stripping: bool,
+    pub entrance: GlobalPotential,
+    pub exit: GlobalPotential,
+    pub entrance_parameters: OpticalParameters,
+    pub exit_parameters: OpticalParameters,
+    pub exit_energy: f64, // MeV, lab energy of the ejectile on the recoil at rest
+    pub bound_state: BoundState,
+    pub d0_squared: f64,  // MeV² fm³
+    pub spin_factor: f64, // of the light particles
+    pub partial_waves: usize,
+    coefficients: Vec<Vec<Complex>>, // [m + l][L_b], amplitude per Y_{L_b,−m}
+    norm: f64,                       // mb/sr per Σ_m |T_m|²
+}
+
+impl Transfer {
+    /// DWBA of `reaction` at `beam_energy` MeV for the recoil in `state`.
+    pub fn new(
+        reaction: Reaction,
+        beam_energy: f64,
+        state: &TransferState,
+    ) -> Result<Self, TransferError> {
+        Self::with_parameters(
+            reaction,
+            beam_energy,
+            state,
+            |potential, nucleus, energy| potential.parameters(nucleus.z, nucleus.a, energy),
+        )
+    }
+
+    /// DWBA with the optical parameters `parameters` gives a global potential on a nucleus at
+    /// a lab energy.
+    fn with_parameters(
+        reaction: Reaction,
+        beam_energy: f64,
+        state: &TransferState,
+        parameters: impl Fn(GlobalPotential, &Particle, f64) -> OpticalParameters,
+    ) -> Result<Self, TransferError> {
+        let Reaction {
+            beam,
+            target,
+            ejectile,
+            recoil,
+        } = reaction;
+        let vertex = Vertex::of(&reaction)?;
+        let Vertex {
+            stripping,
+            nucleon,
+            d0_squared,
+            spins: (spin_a, spin_b),
+        } = vertex;
+        let spin_factor = match stripping {
+            true => 0.5,
+            false => (2.0 * spin_b + 1.0) / ((2.0 * spin_a + 1.0) * 2.0),
+        };
+        let not_transfer = || TransferError::NotTransfer(reaction.name());
+        let entrance = GlobalPotential::for_beam(beam.z, beam.a).ok_or_else(not_transfer)?;
+        let exit = GlobalPotential::for_beam(ejectile.z, ejectile.a).ok_or_else(not_transfer)?;
+
+        // Exit channel with the recoil excited
+        let excited = Particle {
+            mass: recoil.mass + state.excitation,
+            ..recoil
+        };
+        let s = (beam.mass + target.mass).powi(2) + 2.0 * target.mass * beam_energy;
+        let (m3, m4) = (ejectile.mass, excited.mass);
+        let exit_energy = exit_energy(&reaction, beam_energy, state.excitation)?;
+
+        let entrance_parameters = parameters(entrance, &target, beam_energy);
+        let exit_parameters = parameters(exit, &recoil, exit_energy);
+        let a_channel = Channel::with_momentum(
+            &entrance_parameters,
+            &beam,
+            &target,
+            cm_momentum(s, beam.mass, target.mass),
+            INTEGRATION_RADIUS,
+        )?;
+        let b_channel = Channel::with_momentum(
+            &exit_parameters,
+            &ejectile,
+            &excited,
+            cm_momentum(s, m3, m4),
+            INTEGRATION_RADIUS,
+        )?;
+
+        // Nucleon bound to the lighter of target and recoil
+        let core = match stripping {
+            true => target,
+            false => excited,
+        };
+        let binding = vertex.binding(&reaction, state.excitation);
+        let step = a_channel.step;
+        let points = (INTEGRATION_RADIUS / step).round() as usize;
+        let bound_state = BoundState::new(&core, &nucleon, state, binding, step, points)?;
+        let (scale_a, scale_b) = match stripping {
+            true => (1.0, target.mass / excited.mass),
+            false => (excited.mass / target.mass, 1.0),
+        };
+
+        let l = state.l as usize;
+        let l_max = a_channel.l_max.min(b_channel.l_max.saturating_sub(l));
+        // Distorted waves at s_a r and s_b r on the grid of the bound state
+        let scaled = |channel: &Channel, l: usize, scale: f64| {
+            let u = channel.wave(l, 0.0).u;
+            (0..=points)
+                .map(|i| interpolate(&u, step, scale * i as f64 * step))
+                .collect::<Vec<_>>()
+        };
+        let waves_a: Vec<_> = (0..=l_max)
+            .map(|la| scaled(&a_channel, la, scale_a))
+            .collect();
+        let waves_b: Vec<_> = (0..=l_max + l)
+            .map(|lb| scaled(&b_channel, lb, scale_b))
+            .collect();
+        let phases_a = coulomb_phases(a_channel.eta, l_max);
+        let phases_b = coulomb_phases(b_channel.eta, l_max + l);
+        let ln_fact = ln_factorials(2 * (l_max + l) + 2);
+
+        // Radial integrals ∫ u_La(s_a r) w(r) u_Lb(s_b r) dr / r
+        let form_factor: Vec<_> = (0..=points)
+            .map(|i| match i {
+                0 => 0.0,
+                _ => bound_state.u[i] / (i as f64 * step),
+            })
+            .collect();
+        let radial = |la: usize, lb: usize| {
+            waves_a[la]
+                .iter()
+                .zip(&waves_b[lb])
+                .zip(&form_factor)
+                .fold(Complex::ZERO, |sum, ((a, b), w)| sum + *a * *b * *w)
+                * step
+        };
+        let coulomb_phase = |la: usize, lb: usize| phases_a[la] + phases_b[lb];
+
+        let mut coefficients = vec![vec![Complex::ZERO; l_max + l + 1]; 2 * l + 1];
+        for la in 0..=l_max {
+            for lb in la.saturating_sub(l)..=la + l {
+                if (la + lb + l) % 2 == 1 {
+                    continue;
+                }
+                let parity = clebsch_gordan(&ln_fact, lb as i64, 0, l as i64, 0, la as i64, 0);
+                if parity == 0.0 {
+                    continue;
+                }
+                let integral = radial(la, lb);
+                let phase =
+                    Complex::from_phase((la as f64 - lb as f64) * PI / 2.0 + coulomb_phase(la, lb));
+                let common = phase
+                    * integral
+                    * (((2 * lb + 1) * (2 * l + 1)) as f64 / (4.0 * PI)).sqrt()
+                    * parity;
+                for m in -(l as i64)..=l as i64 {
+                    let cg = clebsch_gordan(&ln_fact, lb as i64, -m, l as i64, m, la as i64, 0);
+                    coefficients[(m + l as i64) as usize][lb] += common * cg;
+                }
+            }
+        }
+
+        let (k_a, k_b) = (a_channel.k, b_channel.k);
+        let norm = a_channel.kappa * b_channel.kappa / (16.0 * PI * PI) * k_b / k_a
+            * d0_squared
+            * (4.0 * PI).powi(3)
+            / (k_a * k_b * scale_a * scale_b).powi(2)
+            * spin_factor
+            / (2 * l + 1) as f64
+            * MB_PER_FM2;
+
+        Ok(Self {
+            reaction,
+            state: state.clone(),
+            beam_energy,
+            stripping,
+            entrance,
+            exit,
+            entrance_parameters,
+            exit_parameters,
+            exit_energy,
+            bound_state,
+            d0_squared,
+            spin_factor,
+            partial_waves: l_max + 1,
+            coefficients,
+            norm,
+        })
+    }
+
+    /// Single-particle cross section σ_sp in mb/sr at `cm_angle` degrees.
+    pub fn single_particle(&self, cm_angle: f64) -> f64 {
+        let theta = cm_angle.to_radians();
+        let l = self.state.l as i64;
+        let l_max = self.coefficients[0].len() - 1;
+        let harmonics: Vec<_> = (0..=l as usize)
+            .map(|m| spherical_harmonics(m, l_max, theta))
+            .collect();
+        // Y_{L,−m} = ±Y_{L,|m|} at φ = 0, with a sign common to every L of the same m
+        let sum: f64 = (-l..=l)
+            .map(|m| {
+                self.coefficients[(m + l) as usize]
+                    .iter()
+                    .zip(&harmonics[m.unsigned_abs() as usize])
+                    .fold(Complex::ZERO, |sum, (c, y)| sum + *c * *y)
+                    .norm_sqr()
+            })
+            .sum();
+        sum * self.norm
+    }
+
+    /// Statistical factor of the state: (2J_f + 1)/(2J_i + 1) in stripping, 1 in pickup.
+    pub fn statistical_factor(&self) -> f64 {
+        match self.stripping {
+            true => (2.0 * self.state.spin() + 1.0) / (2.0 * self.state.initial_spin + 1.0),
+            false => 1.0,
+        }
+    }
+
+    /// Cross section of the state in mb/sr at `cm_angle` degrees.
+    pub fn cross_section(&self, cm_angle: f64) -> f64 {
+        self.statistical_factor() * self.state.spectroscopic_factor * self.single_particle(cm_angle)
+    }
+
+    /// Cross sections with the spectrograph at `angle` degrees in the lab.
+    pub fn at_angle(&self, angle: f64) -> Result<TransferPoint, KinematicsError> {
+        let state = self
+            .reaction
+            .solve(self.beam_energy, angle, self.state.excitation)
+            .ok_or(KinematicsError::NotReachable(self.state.excitation))?;
+        let cm = self.cross_section(state.cm_angle);
+        Ok(TransferPoint {
+            angle,
+            cm_angle: state.cm_angle,
+            cm,
+            lab: cm * state.jacobian,
+        })
+    }
+
+    /// Cross sections every [`CURVE_STEP`] degrees in the lab, where the state can be reached.
+    pub fn curve(&self) -> Vec<TransferPoint> {
+        (1..)
+            .map(|i| i as f64 * CURVE_STEP)
+            .take_while(|&angle| angle < 180.0)
+            .filter_map(|angle| self.at_angle(angle).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reaction(beam: (i32, i32), target: (i32, i32), ejectile: (i32, i32)) -> Reaction {
+        let [beam, target, ejectile] =
+            [beam, target, ejectile].map(|(z, a)| Particle::find(z, a).unwrap());
+        let (z, a) = Reaction::recoil_of(&beam, &target, &ejectile).unwrap();
+        Reaction::new(beam, target, ejectile, Particle::find(z, a).unwrap()).unwrap()
+    }
+
+    fn transfer(target: (i32, i32), state: &TransferState) -> Transfer {
+        Transfer::new(reaction((1, 2), target, (1, 1)), 16.0, state).unwrap()
+    }
+
+    #[test]
+    fn stripping_statistical_factor_uses_both_spins() {
+        let state = TransferState::from_orbit(0.0, "2p3/2").unwrap();
+        let spin_zero = transfer((20, 40), &state);
+        assert_eq!(spin_zero.statistical_factor(), 4.0);
+
+        // 43Ca(d,p)44Ca to a 2+ state from the 7/2- ground state
+        let state = TransferState {
+            initial_spin: 3.5,
+            final_spin: Some(2.0),
+            ..state
+        };
+        let odd = transfer((20, 43), &state);
+        assert_eq!(odd.statistical_factor(), 5.0 / 8.0);
+        assert!(
+            (odd.cross_section(20.0) - 5.0 / 8.0 * odd.single_particle(20.0)).abs()
+                < 1e-12 * odd.single_particle(20.0)
+        );
+    }
+
+    #[test]
+    fn undistorted_waves_give_the_plane_wave_born_cross_section() {
+        // 40Ca(d,p)41Ca to the 1f7/2 ground state with the charges and optical potentials
+        // switched off. The amplitude then has the closed form
+        // Σ_m |T_m|² = 4π (2l + 1) D₀² |∫ j_l(qr) u(r) r dr|², q = k_a − (A/B) k_b.
+        let [target, recoil] = [(20, 40), (20, 41)].map(|(z, a)| Particle {
+            z: 0,
+            ..Particle::find(z, a).unwrap()
+        });
+        let [deuteron, proton] = [(1, 2), (1, 1)].map(|(z, a)| Particle::find(z, a).unwrap());
+        let reaction = Reaction::new(deuteron, target, proton, recoil).unwrap();
+        let state = TransferState::from_orbit(0.0, "1f7/2").unwrap();
+        let transfer = Transfer::with_parameters(reaction, 16.0, &state, |_, _, _| {
+            OpticalParameters::default()
+        })
+        .unwrap();
+
+        let s = (deuteron.mass + target.mass).powi(2) + 2.0 * target.mass * 16.0;
+        let channel = |m1: f64, m2: f64| {
+            let p = cm_momentum(s, m1, m2);
+            let (e1, e2) = (p.hypot(m1), p.hypot(m2));
+            (p / HBARC, e1 * e2 / (e1 + e2))
+        };
+        let (k_a, mu_a) = channel(deuteron.mass, target.mass);
+        let (k_b, mu_b) = channel(proton.mass, recoil.mass);
+        let scale_b = target.mass / recoil.mass;
+        let norm = mu_a * mu_b / (2.0 * PI * HBARC.powi(2)).powi(2) * k_b / k_a
+            * 0.5 // spin factor of (d,p)
+            * 4.0
+            * PI
+            * D0_SQUARED_DEUTERON
+            * MB_PER_FM2;
+
+        let u = &transfer.bound_state.u;
+        let step = INTEGRATION_RADIUS / (u.len() - 1) as f64;
+        let j3 = |x: f64| {
+            let (sin, cos) = (x.sin(), x.cos());
+            (15.0 / x.powi(3) - 6.0 / x) * sin / x - (15.0 / x.powi(2) - 1.0) * cos / x
+        };
+        for cm_angle in [5.0, 20.0, 40.0] {
+            let q = (k_a.powi(2) + (scale_b * k_b).powi(2)
+                - 2.0 * k_a * scale_b * k_b * f64::to_radians(cm_angle).cos())
+            .sqrt();
+            let overlap: f64 = (1..u.len())
+                .map(|i| (i as f64 * step, u[i]))
+                .map(|(r, u)| j3(q * r) * u * r)
+                .sum::<f64>()
+                * step;
+            let expected = norm * overlap.powi(2);
+            let actual = transfer.single_particle(cm_angle);
+            assert!(
+                (actual / expected - 1.0).abs() < 1e-3,
+                "{cm_angle}: {actual} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn bound_state_is_normalized_and_bound() {
+        let state = TransferState::from_orbit(0.0, "1f7/2").unwrap();
+        let transfer = transfer((20, 40), &state);
+        let bound = &transfer.bound_state;
+        let step = INTEGRATION_RADIUS / (bound.u.len() - 1) as f64;
+        let norm: f64 = bound.u.iter().map(|u| u * u).sum::<f64>() * step;
+        assert!((norm - 1.0).abs() < 1e-3, "{norm}");
+        // S_n(41Ca) = 8.363 MeV in a well of the usual depth
+        assert!((bound.binding - 8.363).abs() < 1e-2);
+        assert!((40.0..60.0).contains(&bound.depth));
+    }
+
+    #[test]
+    fn stripping_and_pickup_obey_detailed_balance() {
+        // 40Ca(d,p)41Ca and 41Ca(p,d)40Ca between the same ground states at the same
+        // centre-of-mass energies: (2s_p + 1)(2J_B + 1) k_p² σ_pd = (2s_d + 1)(2J_A + 1) k_d² σ_dp
+        let state = TransferState {
+            final_spin: Some(3.5),
+            ..TransferState::from_orbit(0.0, "1f7/2").unwrap()
+        };
+        let stripping = Transfer::new(reaction((1, 2), (20, 40), (1, 1)), 16.0, &state).unwrap();
+        let pickup = TransferState {
+            initial_spin: 3.5,
+            final_spin: Some(0.0),
+            ..state.clone()
+        };
+        let pickup = Transfer::new(
+            reaction((1, 1), (20, 41), (1, 2)),
+            stripping.exit_energy,
+            &pickup,
+        )
+        .unwrap();
+        assert!((pickup.exit_energy / 16.0 - 1.0).abs() < 1e-3);
+
+        let k = |transfer: &Transfer| {
+            let Reaction { beam, target, .. } = transfer.reaction;
+            let s = (beam.mass + target.mass).powi(2) + 2.0 * target.mass * transfer.beam_energy;
+            cm_momentum(s, beam.mass, target.mass) / HBARC
+        };
+        let (k_d, k_p) = (k(&stripping), k(&pickup));
+        for cm_angle in [10.0, 30.0, 60.0] {
+            let ratio = pickup.cross_section(cm_angle) / stripping.cross_section(cm_angle);
+            let expected = 3.0 * k_d.powi(2) / (2.0 * 8.0 * k_p.powi(2));
+            assert!(
+                (ratio / expected - 1.0).abs() < 1e-2,
+                "{cm_angle}: {ratio} != {expected}"
+            );
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use super::dwba::TransferError;
+use super::kinematics::KinematicsError;
+use super::listing::ListingError;
+use super::optical::OpticalError;
+use super::stopping::StoppingError;
+use super::target::TargetError;
+
+/// Reasons an estimate cannot be calculated from the given inputs.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EstimateError {
+    /// The quantity must be strictly greater than zero.
+    NotPositive { parameter: &'static str, value: f64 },
+    /// The quantity must not be negative.
+    Negative { parameter: &'static str, value: f64 },
+    /// The angular momentum is not a multiple of ½.
+    NotHalfInteger { parameter: &'static str, value: f64 },
+    /// The quantity lies outside its physical range.
+    OutOfRange {
+        parameter: &'static str,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    /// The target formula or reaction nuclide is invalid.
+    Target(TargetError),
+    /// The reaction for the kinematics is incomplete or not possible.
+    Kinematics(KinematicsError),
+    /// The optical-model calculation is not possible.
+    OpticalModel(OpticalError),
+    /// The DWBA calculation of the transfer is not possible.
+    Transfer(TransferError),
+    /// The imported angular distribution does not give a cross section.
+    Listing(ListingError),
+    /// The stopping powers of the target are not available.
+    Stopping(StoppingError),
+}
+
+impl std::fmt::Display for EstimateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EstimateError::NotPositive { parameter, value } => {
+                write!(f, "{parameter} must be greater than zero (got {value})")
+            }
+            EstimateError::Negative { parameter, value } => {
+                write!(f, "{parameter} must not be negative (got {value})")
+            }
+            EstimateError::NotHalfInteger { parameter, value } => {
+                write!(f, "{parameter} must be a multiple of 1/2 (got {value})")
+            }
+            EstimateError::OutOfRange {
+                parameter,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "{parameter} must be between {min} and {max} (got {value})"
+            ),
+            EstimateError::Target(e) => write!(f, "{e}"),
+            EstimateError::Kinematics(e) => write!(f, "{e}"),
+            EstimateError::OpticalModel(e) => write!(f, "{e}"),
+            EstimateError::Transfer(e) => write!(f, "{e}"),
+            EstimateError::Listing(e) => write!(f, "{e}"),
+            EstimateError::Stopping(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for EstimateError {}
+
+impl From<TargetError> for EstimateError {
+    fn from(e: TargetError) -> Self {
+        EstimateError::Target(e)
+    }
+}
+
+impl From<KinematicsError> for EstimateError {
+    fn from(e: KinematicsError) -> Self {
+        EstimateError::Kinematics(e)
+    }
+}
+
+impl From<OpticalError> for EstimateError {
+    fn from(e: OpticalError) -> Self {
+        EstimateError::OpticalModel(e)
+    }
+}
+
+impl From<TransferError> for EstimateError {
+    fn from(e: TransferError) -> Self {
+        EstimateError::Transfer(e)
+    }
+}
+
+impl From<ListingError> for EstimateError {
+    fn from(e: ListingError) -> Self {
+        EstimateError::Listing(e)
+    }
+}
+
+impl From<StoppingError> for EstimateError {
+    fn from(e: StoppingError) -> Self {
+        EstimateError::Stopping(e)
+    }
+}
+
+pub(crate) fn require_positive(parameter: &'static str, value: f64) -> Result<f64, EstimateError> {
+    if value > 0.0 && value.is_finite() {
+        Ok(value)
+    } else {
+        Err(EstimateError::NotPositive { parameter, value })
+    }
+}
+
+pub(crate) fn require_non_negative(
+    parameter: &'static str,
+    value: f64,
+) -> Result<f64, EstimateError> {
+    if value >= 0.0 && value.is_finite() {
+        Ok(value)
+    } else {
+        Err(EstimateError::Negative { parameter, value })
+    }
+}
+
+pub(crate) fn require_spin(parameter: &'static str, value: f64) -> Result<f64, EstimateError> {
+    require_non_negative(parameter, value)?;
+    if (2.0 * value).fract() == 0.0 {
+        Ok(value)
+    } else {
+        Err(EstimateError::NotHalfInteger { parameter, value })
+    }
+}
+
+pub(crate) fn require_in_range(
+    parameter: &'static str,
+    value: f64,
+    min: f64,
+    max: f64,
+) -> Result<f64, EstimateError> {
+    if (min..=max).contains(&value) {
+        Ok(value)
+    } else {
+        Err(EstimateError::OutOfRange {
+            parameter,
+            value,
+            min,
+            max,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirements_return_the_value_or_name_the_parameter() {
+        assert_eq!(require_positive("Current", 2.5), Ok(2.5));
+        for value in [0.0, -1.0, f64::INFINITY, f64::NAN] {
+            match require_positive("Current", value) {
+                Err(EstimateError::NotPositive { parameter, .. }) => {
+                    assert_eq!(parameter, "Current");
+                }
+                other => panic!("{value}: {other:?}"),
+            }
+        }
+
+        assert_eq!(require_non_negative("Counts", 0.0), Ok(0.0));
+        assert_eq!(
+            require_non_negative("Counts", -3.0),
+            Err(EstimateError::Negative {
+                parameter: "Counts",
+                value: -3.0
+            })
+        );
+
+        assert_eq!(require_spin("Spin", 2.5), Ok(2.5));
+        assert_eq!(require_spin("Spin", 0.0), Ok(0.0));
+        assert_eq!(
+            require_spin("Spin", 1.3),
+            Err(EstimateError::NotHalfInteger {
+                parameter: "Spin",
+                value: 1.3
+            })
+        );
+        assert_eq!(
+            require_spin("Spin", -0.5),
+            Err(EstimateError::Negative {
+                parameter: "Spin",
+                value: -0.5
+            })
+        );
+
+        assert_eq!(require_in_range("Ratio", 100.0, 0.0, 100.0), Ok(100.0));
+        let error = require_in_range("Ratio", 100.5, 0.0, 100.0).unwrap_err();
+        assert_eq!(
+            error,
+            EstimateError::OutOfRange {
+                parameter: "Ratio",
+                value: 100.5,
+                min: 0.0,
+                max: 100.0
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Ratio must be between 0 and 100 (got 100.5)"
+        );
+    }
+}
@@ -1,4 +1,7 @@
+use super::error::{require_in_range, require_non_negative, EstimateError};
+
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct ICESPICERunTimeSettings {
     pub n_particle_counts: i64,
     pub transmission_prob: f64,   // in percentage
@@ -19,69 +22,126 @@ impl Default for ICESPICERunTimeSettings {
     }
 }
 
+/// Conversion electrons expected for the particle counts in the excited state.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ICESPICEEstimate {
+    pub gamma_rays: f64,
+    pub conversion_electrons: f64,
+    pub detected_conversion_electrons: f64,
+}
+
 impl ICESPICERunTimeSettings {
-    // Method to calculate the number of conversion electrons
-    pub fn calculate_conversion_electrons(&self) -> f64 {
-        let gamma_rays = self.n_particle_counts as f64 * (self.branching_ratio / 100.0);
-        let conversion_electrons = gamma_rays * self.conversion_coefficient;
-        conversion_electrons * (self.transmission_prob / 100.0) * (self.detector_efficiency / 100.0)
+    pub fn validate(&self) -> Result<(), EstimateError> {
+        require_non_negative("Particle counts", self.n_particle_counts as f64)?;
+        require_in_range(
+            "Transmission probability",
+            self.transmission_prob,
+            0.0,
+            100.0,
+        )?;
+        require_in_range("Detector efficiency", self.detector_efficiency, 0.0, 100.0)?;
+        require_in_range("Branching ratio", self.branching_ratio, 0.0, 100.0)?;
+        require_non_negative("Conversion coefficient", self.conversion_coefficient)?;
+        Ok(())
     }
 
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
-        egui::Grid::new("icespice_runtime_settings_grid")
-            .striped(true)
-            .show(ui, |ui| {
-                ui.heading("ICESPICE");
-                ui.end_row();
+    // Method to calculate the number of conversion electrons
+    pub fn calculate_conversion_electrons(&self) -> Result<ICESPICEEstimate, EstimateError> {
+        self.validate()?;
 
-                ui.label("Particle Counts:")
-                    .on_hover_text("Number of particles detected in the excited state.");
-                ui.add(
-                    egui::DragValue::new(&mut self.n_particle_counts)
-                        .speed(1.0)
-                        .range(0..=i64::MAX),
-                );
-                ui.end_row();
+        let gamma_rays = self.n_particle_counts as f64 * (self.branching_ratio / 100.0);
+        let conversion_electrons = gamma_rays * self.conversion_coefficient;
+        let detected_conversion_electrons = conversion_electrons
+            * (self.transmission_prob / 100.0)
+            * (self.detector_efficiency / 100.0);
 
-                ui.label("Transmission Probability:").on_hover_text(
-                    "Probability of the particle passing through ICESPICE to the detector in 4π.",
-                );
-                ui.add(egui::DragValue::new(&mut self.transmission_prob)
-                    .suffix(" %")
-                    .speed(0.1)
-                    .range(0.0..=100.0),
-                );
-                ui.end_row();
+        Ok(ICESPICEEstimate {
+            gamma_rays,
+            conversion_electrons,
+            detected_conversion_electrons,
+        })
+    }
+}
 
-                ui.label("Detector Efficiency:").on_hover_text(
-                    "Efficiency of the detector for an electron to deposit its full energy.",
-                );
-                ui.add(
-                    egui::DragValue::new(&mut self.detector_efficiency)
-                    .suffix(" %")
-                    .speed(0.1)
-                    .range(0.0..=100.0),
-                );
-                ui.end_row();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                ui.label("Branching Ratio:").on_hover_text("The branching ratio is the fraction of decays that proceed through a particular transition.");
-                ui.add(
-                    egui::DragValue::new(&mut self.branching_ratio)
-                    .suffix(" %")
-                    .speed(1.0)
-                    .range(0.0..=100.0),
-                );
-                ui.end_row();
+    #[test]
+    fn default_settings_detect_forty_electrons() {
+        let estimate = ICESPICERunTimeSettings::default()
+            .calculate_conversion_electrons()
+            .unwrap();
+        assert_eq!(estimate.gamma_rays, 10000.0);
+        assert_eq!(estimate.conversion_electrons, 10000.0);
+        // 10000 · 1.29 % · 30.7 %
+        assert!((estimate.detected_conversion_electrons - 39.603).abs() < 1e-9);
+    }
 
-                ui.label("Conversion Coefficient (α):").on_hover_text("The internal conversion coefficient (α) is the ratio of the number of conversion electrons emitted to the number of gamma rays emitted for a particular transition. ICCs depend on the energy of the transition and the atomic number of the nucleus. These coefficients can be obtained from tables or calculated using theoretical models.");
-                ui.add(egui::DragValue::new(&mut self.conversion_coefficient));
-                ui.end_row();
+    #[test]
+    fn electrons_scale_with_branching_ratio_and_conversion_coefficient() {
+        let settings = ICESPICERunTimeSettings {
+            branching_ratio: 40.0,
+            conversion_coefficient: 0.5,
+            ..Default::default()
+        };
+        let estimate = settings.calculate_conversion_electrons().unwrap();
+        assert_eq!(estimate.gamma_rays, 4000.0);
+        assert_eq!(estimate.conversion_electrons, 2000.0);
+    }
 
-                let conversion_electrons = self.calculate_conversion_electrons();
-                ui.label("Estimated Number of\nDetected Conversion Electrons:")
-                .on_hover_text("Formula: Particle Counts * (Branching Ratio [%] / 100) * α * (Transmission Probability [%] / 100) * (Detector Efficiency [%] / 100)");
-                ui.label(format!("{:.0}", conversion_electrons));
-                ui.end_row();
-            });
+    #[test]
+    fn invalid_inputs_name_the_parameter() {
+        let percent = |parameter, value| EstimateError::OutOfRange {
+            parameter,
+            value,
+            min: 0.0,
+            max: 100.0,
+        };
+        for (settings, error) in [
+            (
+                ICESPICERunTimeSettings {
+                    n_particle_counts: -10,
+                    ..Default::default()
+                },
+                EstimateError::Negative {
+                    parameter: "Particle counts",
+                    value: -10.0,
+                },
+            ),
+            (
+                ICESPICERunTimeSettings {
+                    transmission_prob: 101.0,
+                    ..Default::default()
+                },
+                percent("Transmission probability", 101.0),
+            ),
+            (
+                ICESPICERunTimeSettings {
+                    detector_efficiency: -1.0,
+                    ..Default::default()
+                },
+                percent("Detector efficiency", -1.0),
+            ),
+            (
+                ICESPICERunTimeSettings {
+                    branching_ratio: 150.0,
+                    ..Default::default()
+                },
+                percent("Branching ratio", 150.0),
+            ),
+            (
+                ICESPICERunTimeSettings {
+                    conversion_coefficient: -0.1,
+                    ..Default::default()
+                },
+                EstimateError::Negative {
+                    parameter: "Conversion coefficient",
+                    value: -0.1,
+                },
+            ),
+        ] {
+            assert_eq!(settings.calculate_conversion_electrons(), Err(error));
+        }
     }
 }
@@ -0,0 +1,383 @@
+//! Relativistic two-body kinematics of a reaction A(a,b)B on a target at rest, as seen by the
+//! SE-SPS at a fixed laboratory angle.
+//!
+//! Energies are in MeV, momenta in MeV/c and angles in degrees. Masses are nuclear masses,
+//! i.e. the atomic masses of the nuclide table minus the electrons.
+
+use super::nuclide::{self, Nuclide};
+
+pub const ATOMIC_MASS_UNIT: f64 = 931.49410242; // MeV/c^2
+pub const ELECTRON_MASS: f64 = 0.51099895; // MeV/c^2
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum KinematicsError {
+    /// The target has more than one nuclide and no reaction nuclide is chosen.
+    NoTargetNuclide,
+    /// The ejectile has more protons or nucleons than beam and target together.
+    NoRecoil,
+    /// Proton or mass number differ between the entrance and exit channel.
+    NotConserved,
+    /// The nuclide is not in the nuclide table, so its mass has to be entered.
+    UnknownMass(String),
+    /// The state at this excitation energy cannot be reached at the spectrograph angle.
+    NotReachable(f64),
+    /// The ejectile has no charge and cannot be bent by the spectrograph.
+    NeutralEjectile,
+    /// Converting between lab and centre-of-mass frame needs the reaction.
+    NoEjectile,
+}
+
+impl std::fmt::Display for KinematicsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KinematicsError::NoTargetNuclide => write!(
+                f,
+                "choose the reaction nuclide of the target for the kinematics"
+            ),
+            KinematicsError::NoRecoil => {
+                write!(f, "the ejectile is heavier than beam and target together")
+            }
+            KinematicsError::NotConserved => {
+                write!(
+                    f,
+                    "proton and mass numbers are not conserved in the reaction"
+                )
+            }
+            KinematicsError::UnknownMass(name) => write!(
+                f,
+                "{name} is not in the nuclide table, enter its mass by hand"
+            ),
+            KinematicsError::NotReachable(excitation) => write!(
+                f,
+                "the state at {excitation} MeV cannot be reached at this angle"
+            ),
+            KinematicsError::NoEjectile => write!(
+                f,
+                "choose an ejectile to convert cross sections between lab and CM"
+            ),
+            KinematicsError::NeutralEjectile => {
+                write!(
+                    f,
+                    "a neutral ejectile cannot be analysed by the spectrograph"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for KinematicsError {}
+
+/// Frame a cross section or angle is given in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Frame {
+    #[default]
+    Lab,
+    CenterOfMass,
+}
+
+impl Frame {
+    pub const ALL: [Frame; 2] = [Frame::Lab, Frame::CenterOfMass];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Frame::Lab => "Lab",
+            Frame::CenterOfMass => "CM",
+        }
+    }
+}
+
+/// Nucleus taking part in the reaction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    pub z: i32,
+    pub a: i32,
+    pub mass: f64, // MeV/c^2, ground state
+}
+
+impl Particle {
+    /// Particle from the atomic mass in u of its neutral atom.
+    pub fn from_atomic_mass(z: i32, a: i32, atomic_mass: f64) -> Self {
+        Self {
+            z,
+            a,
+            mass: atomic_mass * ATOMIC_MASS_UNIT - z as f64 * ELECTRON_MASS,
+        }
+    }
+
+    pub fn from_nuclide(nuclide: &Nuclide) -> Self {
+        Self::from_atomic_mass(nuclide.z, nuclide.a, nuclide.mass)
+    }
+
+    /// Particle `(z, a)` with its mass from the nuclide table.
+    pub fn find(z: i32, a: i32) -> Result<Self, KinematicsError> {
+        nuclide::find(z, a)
+            .map(Self::from_nuclide)
+            .ok_or_else(|| KinematicsError::UnknownMass(nucleus_name(z, a)))
+    }
+
+    /// Name like `208Pb`.
+    pub fn name(&self) -> String {
+        nucleus_name(self.z, self.a)
+    }
+
+    /// Short name of the light ions (`p`, `d`, `t`, `α`, ...) as used in reaction notation.
+    pub fn short_name(&self) -> String {
+        match (self.z, self.a) {
+            (0, 1) => "n".to_string(),
+            (1, 1) => "p".to_string(),
+            (1, 2) => "d".to_string(),
+            (1, 3) => "t".to_string(),
+            (2, 4) => "α".to_string(),
+            _ => self.name(),
+        }
+    }
+}
+
+/// Name like `211Pb` for any proton and mass number, whether or not it is in the table.
+pub fn nucleus_name(z: i32, a: i32) -> String {
+    match z {
+        0 if a == 1 => "n".to_string(),
+        _ => format!("{a}{}", nuclide::element_symbol(z).unwrap_or("?")),
+    }
+}
+
+/// Energies and momenta of one state of the recoil, i.e. one peak on the focal plane.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct EjectileKinematics {
+    pub excitation: f64,        // MeV, of the recoil
+    pub ejectile_energy: f64,   // MeV, kinetic
+    pub ejectile_momentum: f64, // MeV/c
+    pub kinematic_factor: f64,  // keV/deg, dE/dθ of the ejectile
+    pub recoil_energy: f64,     // MeV, kinetic
+    pub recoil_angle: f64,      // deg
+    pub cm_angle: f64,          // deg, of the ejectile in the centre-of-mass frame
+    pub jacobian: f64,          // dΩ_cm/dΩ_lab, so dσ/dΩ_lab = jacobian · dσ/dΩ_cm
+}
+
+/// Reaction `target(beam, ejectile)recoil` with the target at rest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reaction {
+    pub beam: Particle,
+    pub target: Particle,
+    pub ejectile: Particle,
+    pub recoil: Particle,
+}
+
+impl Reaction {
+    pub fn new(
+        beam: Particle,
+        target: Particle,
+        ejectile: Particle,
+        recoil: Particle,
+    ) -> Result<Self, KinematicsError> {
+        if beam.z + target.z != ejectile.z + recoil.z || beam.a + target.a != ejectile.a + recoil.a
+        {
+            return Err(KinematicsError::NotConserved);
+        }
+        Ok(Self {
+            beam,
+            target,
+            ejectile,
+            recoil,
+        })
+    }
+
+    /// Proton and mass number of the recoil that conserve those of the reaction.
+    pub fn recoil_of(
+        beam: &Particle,
+        target: &Particle,
+        ejectile: &Particle,
+    ) -> Result<(i32, i32), KinematicsError> {
+        let z = beam.z + target.z - ejectile.z;
+        let a = beam.a + target.a - ejectile.a;
+        if z < 0 || a < 1 || z > a {
+            return Err(KinematicsError::NoRecoil);
+        }
+        Ok((z, a))
+    }
+
+    /// Reaction notation, e.g. `208Pb(d,p)209Pb`.
+    pub fn name(&self) -> String {
+        format!(
+            "{}({},{}){}",
+            self.target.name(),
+            self.beam.short_name(),
+            self.ejectile.short_name(),
+            self.recoil.name()
+        )
+    }
+
+    /// Ground-state Q-value in MeV.
+    pub fn q_value(&self) -> f64 {
+        self.beam.mass + self.target.mass - self.ejectile.mass - self.recoil.mass
+    }
+
+    /// Ejectile and recoil for the recoil excited to `excitation` MeV, with the ejectile at
+    /// `angle` degrees in the laboratory. Takes the faster ejectile where two are possible;
+    /// `None` if the state cannot be reached at this angle.
+    pub fn solve(
+        &self,
+        beam_energy: f64,
+        angle: f64,
+        excitation: f64,
+    ) -> Option<EjectileKinematics> {
+        let m1 = self.beam.mass;
+        let m3 = self.ejectile.mass;
+        let m4 = self.recoil.mass + excitation;
+        let theta = angle.to_radians();
+
+        let total_energy = beam_energy + m1 + self.target.mass;
+        let beam_momentum = (beam_energy * (beam_energy + 2.0 * m1)).sqrt();
+        let s = total_energy.powi(2) - beam_momentum.powi(2);
+        if s.sqrt() < m3 + m4 {
+            return None;
+        }
+
+        // Energy conservation with the recoil's invariant mass:
+        // E_tot E3 - p1 p3 cosθ = (s + m3² - m4²) / 2
+        let k = (s + m3.powi(2) - m4.powi(2)) / 2.0;
+        let b = beam_momentum * theta.cos();
+        let a = total_energy.powi(2) - b.powi(2);
+        let discriminant = k.powi(2) - a * m3.powi(2);
+        if discriminant < 0.0 {
+            return None;
+        }
+        let p3 = [1.0, -1.0]
+            .into_iter()
+            .map(|sign| (k * b + sign * total_energy * discriminant.sqrt()) / a)
+            .find(|&p3| p3 >= 0.0 && k + b * p3 > 0.0)?;
+        let e3 = (p3.powi(2) + m3.powi(2)).sqrt();
+
+        // Differentiating the same relation with respect to θ
+        let dp3_dtheta = -beam_momentum * p3 * theta.sin()
+            / (total_energy * p3 / e3 - beam_momentum * theta.cos());
+        let de3_dtheta = p3 / e3 * dp3_dtheta; // MeV/rad
+
+        let recoil_energy = total_energy - e3 - m4;
+        let recoil_angle = (p3 * theta.sin())
+            .atan2(beam_momentum - p3 * theta.cos())
+            .to_degrees();
+
+        // Boost of the ejectile into the centre-of-mass frame, where its momentum is fixed
+        let beta = beam_momentum / total_energy;
+        let gamma = total_energy / s.sqrt();
+        let cm_momentum =
+            ((s - (m3 + m4).powi(2)) * (s - (m3 - m4).powi(2))).sqrt() / (2.0 * s.sqrt());
+        let cm_angle = (p3 * theta.sin())
+            .atan2(gamma * (p3 * theta.cos() - beta * e3))
+            .to_degrees();
+        let jacobian = p3.powi(2) / (gamma * cm_momentum * (p3 - beta * e3 * theta.cos()));
+
+        Some(EjectileKinematics {
+            excitation,
+            ejectile_energy: e3 - m3,
+            ejectile_momentum: p3,
+            kinematic_factor: de3_dtheta * 1e3 * std::f64::consts::PI / 180.0,
+            recoil_energy,
+            recoil_angle,
+            cm_angle,
+            jacobian,
+        })
+    }
+
+    /// Excitation energy of the recoil for an ejectile of `momentum` MeV/c at `angle` degrees,
+    /// the inverse of [`Reaction::solve`]. Negative above the ground-state momentum; `None` if
+    /// no recoil mass fits.
+    pub fn excitation_for(&self, beam_energy: f64, angle: f64, momentum: f64) -> Option<f64> {
+        let m1 = self.beam.mass;
+        let m3 = self.ejectile.mass;
+        let total_energy = beam_energy + m1 + self.target.mass;
+        let beam_momentum = (beam_energy * (beam_energy + 2.0 * m1)).sqrt();
+        let s = total_energy.powi(2) - beam_momentum.powi(2);
+        let e3 = (momentum.powi(2) + m3.powi(2)).sqrt();
+
+        let m4_squared = s + m3.powi(2)
+            - 2.0 * (total_energy * e3 - beam_momentum * momentum * angle.to_radians().cos());
+        (m4_squared > 0.0).then(|| m4_squared.sqrt() - self.recoil.mass)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reaction(target: (i32, i32), beam: (i32, i32), ejectile: (i32, i32)) -> Reaction {
+        let beam = Particle::find(beam.0, beam.1).unwrap();
+        let target = Particle::find(target.0, target.1).unwrap();
+        let ejectile = Particle::find(ejectile.0, ejectile.1).unwrap();
+        let (z, a) = Reaction::recoil_of(&beam, &target, &ejectile).unwrap();
+        Reaction::new(beam, target, ejectile, Particle::find(z, a).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn q_values_match_the_mass_evaluation() {
+        for (target, beam, ejectile, q_value) in [
+            ((82, 206), (1, 2), (1, 1), 4.5132), // 206Pb(d,p)207Pb
+            ((6, 12), (1, 2), (1, 1), 2.7219),   // 12C(d,p)13C
+            ((6, 13), (1, 1), (1, 2), -2.7217),  // 13C(p,d)12C
+            ((82, 208), (2, 4), (2, 4), 0.0),    // elastic
+        ] {
+            let reaction = reaction(target, beam, ejectile);
+            assert!(
+                (reaction.q_value() - q_value).abs() < 1e-3,
+                "{}: {}",
+                reaction.name(),
+                reaction.q_value()
+            );
+        }
+    }
+
+    #[test]
+    fn elastic_energy_follows_the_two_body_formula() {
+        // Non-relativistic E'/E = ((m₁ cos θ + √(m₂² − m₁² sin² θ)) / (m₁ + m₂))²
+        let elastic = reaction((6, 12), (1, 1), (1, 1));
+        let (m1, m2) = (elastic.beam.mass, elastic.target.mass);
+        let theta = 60f64.to_radians();
+        let ratio = ((m1 * theta.cos() + (m2.powi(2) - (m1 * theta.sin()).powi(2)).sqrt())
+            / (m1 + m2))
+            .powi(2);
+        let state = elastic.solve(1.0, 60.0, 0.0).unwrap();
+        assert!((state.ejectile_energy / ratio - 1.0).abs() < 1e-3);
+        assert!((state.recoil_energy + state.ejectile_energy - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn excitation_from_the_momentum_inverts_the_kinematics() {
+        let reaction = reaction((82, 206), (1, 2), (1, 1));
+        let state = reaction.solve(16.0, 25.0, 0.570).unwrap();
+        let excitation = reaction
+            .excitation_for(16.0, 25.0, state.ejectile_momentum)
+            .unwrap();
+        assert!((excitation - 0.570).abs() < 1e-6);
+        assert!(reaction.solve(16.0, 25.0, 25.0).is_none());
+    }
+
+    #[test]
+    fn jacobian_is_one_on_an_infinitely_heavy_target() {
+        let mut elastic = reaction((82, 208), (1, 1), (1, 1));
+        elastic.target.mass *= 1e5;
+        elastic.recoil.mass *= 1e5;
+        let state = elastic.solve(16.0, 40.0, 0.0).unwrap();
+        assert!((state.jacobian - 1.0).abs() < 1e-6, "{}", state.jacobian);
+        assert!((state.cm_angle - 40.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn elastic_jacobian_follows_the_two_body_formula() {
+        // Non-relativistic tan θ_lab = sin θ_cm / (cos θ_cm + g) and
+        // dΩ_cm/dΩ_lab = (1 + 2g cos θ_cm + g²)^(3/2) / |1 + g cos θ_cm| with g = m₁/m₂
+        let elastic = reaction((6, 12), (1, 2), (1, 2));
+        let g = elastic.beam.mass / elastic.target.mass;
+        for angle in [10.0, 30.0, 60.0, 90.0, 120.0, 170.0] {
+            let state = elastic.solve(1.0, angle, 0.0).unwrap();
+            let (sin, cos) = state.cm_angle.to_radians().sin_cos();
+            assert!((sin.atan2(cos + g).to_degrees() - angle).abs() < 1e-2);
+            let jacobian = (1.0 + 2.0 * g * cos + g * g).powf(1.5) / (1.0 + g * cos).abs();
+            assert!(
+                (state.jacobian / jacobian - 1.0).abs() < 1e-3,
+                "{angle}: {} != {jacobian}",
+                state.jacobian
+            );
+        }
+    }
+}
@@ -1,7 +1,38 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+#[cfg(feature = "gui")]
 mod app;
 pub mod cebra;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+mod complex;
+pub mod coulomb;
+pub mod deck;
+pub mod dwba;
+pub mod error;
 pub mod icespice;
+pub mod kinematics;
+pub mod listing;
+pub mod nuclide;
+pub mod optical;
+#[cfg(feature = "gui")]
+mod persistence;
+pub mod planner;
+pub mod project;
+pub mod report;
+pub mod resolution;
+pub mod rutherford;
+pub mod scenario;
+pub mod spectrograph;
 pub mod sps;
+pub mod stopping;
+pub mod target;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "gui")]
+mod ui;
+pub mod units;
+
+#[cfg(feature = "gui")]
 pub use app::BeamTimeApp;
+pub use error::EstimateError;
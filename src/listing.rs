@@ -0,0 +1,463 @@
+//! Angular distributions read from the output of DWBA codes, so that a calculation already
+//! made for a state can replace the entered cross section.
+//!
+//! Three listings are understood:
+//! - Ptolemy output, whose tables start at the `C.M.  REACTION` header and end at `TOTAL:`,
+//!   labelled by the preceding `REACTION:` line;
+//! - FRESCO fort.201-style plot files (xmgrace), one set per state ended by `&`, labelled by
+//!   their `legend`;
+//! - DWUCK4 output, whose tables start at a `Theta` header naming the unit of the cross
+//!   section, `fm**2/sr` or `mb/sr`.
+//!
+//! Every state becomes a table of dσ/dΩ_cm in mb/sr against θ_cm in degrees, which is
+//! interpolated at the centre-of-mass angle of the spectrograph. The excitation energy of a
+//! state is read from its label where the code writes it there; otherwise it has to be entered
+//! before the state can be used.
+
+use super::rutherford::MB_PER_FM2;
+
+const HEADER_LINES: usize = 5; // lines between a table header and its first row
+
+/// Output listing of a DWBA code.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ListingFormat {
+    #[default]
+    Ptolemy,
+    /// fort.201-style xmgrace plot file.
+    Fresco,
+    Dwuck4,
+}
+
+impl ListingFormat {
+    pub const ALL: [ListingFormat; 3] = [
+        ListingFormat::Ptolemy,
+        ListingFormat::Fresco,
+        ListingFormat::Dwuck4,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ListingFormat::Ptolemy => "Ptolemy",
+            ListingFormat::Fresco => "FRESCO",
+            ListingFormat::Dwuck4 => "DWUCK4",
+        }
+    }
+
+    /// Format of a listing from its contents, `None` if it does not look like any of them.
+    pub fn detect(text: &str) -> Option<Self> {
+        let upper = text.to_uppercase();
+        if upper.contains("PTOLEMY") || upper.contains("C.M.  REACTION") {
+            Some(ListingFormat::Ptolemy)
+        } else if upper.contains("DWUCK") {
+            Some(ListingFormat::Dwuck4)
+        } else if text
+            .lines()
+            .any(|line| line.trim_start().starts_with(['@', '&']))
+        {
+            Some(ListingFormat::Fresco)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ListingError {
+    /// The listing looks like none of the supported codes.
+    UnknownFormat,
+    /// The listing holds no angular distribution in this format.
+    Empty(ListingFormat),
+    /// A line of a plot file that is not a pair of numbers.
+    InvalidRow { line: usize, text: String },
+    /// No state has been imported at this index.
+    NoState(usize),
+    /// The angle lies outside the tabulated range of the state.
+    OutsideTable { cm_angle: f64, min: f64, max: f64 },
+    /// The label of the state holds no excitation energy and none has been entered.
+    NoExcitation(String),
+}
+
+impl std::fmt::Display for ListingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListingError::UnknownFormat => write!(
+                f,
+                "the listing is not recognized as Ptolemy, FRESCO or DWUCK4 output"
+            ),
+            ListingError::Empty(format) => write!(
+                f,
+                "no angular distribution found in the {} listing",
+                format.name()
+            ),
+            ListingError::InvalidRow { line, text } => {
+                write!(
+                    f,
+                    "line {line} is not an angle and a cross section: '{text}'"
+                )
+            }
+            ListingError::NoState(index) => {
+                write!(f, "no imported angular distribution number {}", index + 1)
+            }
+            ListingError::OutsideTable { cm_angle, min, max } => write!(
+                f,
+                "θcm = {cm_angle:.2} deg is outside the imported angles ({min} to {max} deg)"
+            ),
+            ListingError::NoExcitation(label) => write!(
+                f,
+                "the listing gives no excitation energy for '{label}', enter it by hand"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ListingError {}
+
+/// Angular distribution of one state.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ListingState {
+    pub label: String,
+    pub excitation: Option<f64>, // MeV, of the recoil, for the kinematics; None until known
+    pub cm_angles: Vec<f64>,     // deg, increasing
+    pub cross_sections: Vec<f64>, // mb/sr, dσ/dΩ_cm
+}
+
+impl ListingState {
+    fn new(label: String, mut points: Vec<(f64, f64)>) -> Self {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        points.dedup_by(|a, b| a.0 == b.0);
+        Self {
+            excitation: excitation_of(&label),
+            label,
+            cm_angles: points.iter().map(|point| point.0).collect(),
+            cross_sections: points.iter().map(|point| point.1).collect(),
+        }
+    }
+
+    /// Excitation energy in MeV, read from the label or entered.
+    pub fn excitation(&self) -> Result<f64, ListingError> {
+        self.excitation
+            .ok_or_else(|| ListingError::NoExcitation(self.label.clone()))
+    }
+
+    /// dσ/dΩ_cm in mb/sr at `cm_angle` degrees, interpolated linearly in the logarithm of the
+    /// cross section (linearly where it is not positive).
+    pub fn cm_at(&self, cm_angle: f64) -> Result<f64, ListingError> {
+        let outside = || ListingError::OutsideTable {
+            cm_angle,
+            min: self.cm_angles.first().copied().unwrap_or(f64::NAN),
+            max: self.cm_angles.last().copied().unwrap_or(f64::NAN),
+        };
+        let upper = self.cm_angles.partition_point(|&angle| angle < cm_angle);
+        if upper == self.cm_angles.len() {
+            return Err(outside());
+        }
+        if self.cm_angles[upper] == cm_angle {
+            return Ok(self.cross_sections[upper]);
+        }
+        if upper == 0 {
+            return Err(outside());
+        }
+        let (x0, x1) = (self.cm_angles[upper - 1], self.cm_angles[upper]);
+        let (y0, y1) = (self.cross_sections[upper - 1], self.cross_sections[upper]);
+        let t = (cm_angle - x0) / (x1 - x0);
+        Ok(match y0 > 0.0 && y1 > 0.0 {
+            true => y0 * (y1 / y0).powf(t),
+            false => y0 + t * (y1 - y0),
+        })
+    }
+}
+
+/// Angular distributions imported from one listing, with the state the estimate uses.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ImportedCrossSections {
+    pub file: String, // name of the listing, for reference
+    pub format: ListingFormat,
+    pub states: Vec<ListingState>,
+    pub selected: usize, // index into states
+}
+
+impl ImportedCrossSections {
+    /// Every angular distribution of `text` read as a `format` listing from `file`.
+    pub fn import(file: &str, format: ListingFormat, text: &str) -> Result<Self, ListingError> {
+        let states = match format {
+            ListingFormat::Ptolemy => parse_ptolemy(text),
+            ListingFormat::Fresco => parse_fresco(text)?,
+            ListingFormat::Dwuck4 => parse_dwuck4(text),
+        };
+        if states.is_empty() {
+            return Err(ListingError::Empty(format));
+        }
+        Ok(Self {
+            file: file.to_string(),
+            format,
+            states,
+            selected: 0,
+        })
+    }
+
+    pub fn state(&self) -> Result<&ListingState, ListingError> {
+        self.states
+            .get(self.selected)
+            .ok_or(ListingError::NoState(self.selected))
+    }
+}
+
+/// Imported cross section at one spectrograph angle.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct ListingPoint {
+    pub state: String,
+    pub excitation: f64, // MeV, of the state
+    pub angle: f64,      // deg, lab
+    pub cm_angle: f64,   // deg
+    pub cm: f64,         // mb/sr, dσ/dΩ_cm
+    pub lab: f64,        // mb/sr, dσ/dΩ_lab
+}
+
+/// Number in Fortran notation, where the exponent may be written with D.
+fn number(token: &str) -> Option<f64> {
+    token.replace(['D', 'd'], "E").parse().ok()
+}
+
+/// Angle and cross section from the first two columns of a table row.
+fn row(line: &str) -> Option<(f64, f64)> {
+    let mut tokens = line.split_whitespace();
+    Some((number(tokens.next()?)?, number(tokens.next()?)?))
+}
+
+/// Excitation energy from the last number in the last parentheses of a state label, e.g.
+/// `48Ca(d,p)49Ca(3/2- 2.023)`, if there is one.
+fn excitation_of(label: &str) -> Option<f64> {
+    let start = label.rfind('(')?;
+    let inside = label[start + 1..].split(')').next()?;
+    inside
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter_map(|token| token.parse::<f64>().ok())
+        .last()
+}
+
+/// Rows of the table after a header: up to `HEADER_LINES` lines of column titles and blank
+/// lines before the first row are skipped, and the table ends at the first line that is not a
+/// row.
+fn table<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Vec<(f64, f64)> {
+    let mut points = vec![];
+    let mut skipped = 0;
+    for line in lines {
+        match row(line) {
+            Some(point) => points.push(point),
+            None if points.is_empty() && skipped < HEADER_LINES => skipped += 1,
+            None => break,
+        }
+    }
+    points
+}
+
+fn parse_ptolemy(text: &str) -> Vec<ListingState> {
+    let mut states = vec![];
+    let mut label = None;
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let upper = line.to_uppercase();
+        if let Some(start) = upper.find("REACTION:") {
+            label = Some(line[start + "REACTION:".len()..].trim().to_string());
+        } else if upper.contains("C.M.") && upper.contains("REACTION") {
+            let points = table(&mut lines);
+            if !points.is_empty() {
+                let label = label
+                    .clone()
+                    .unwrap_or_else(|| format!("State {}", states.len() + 1));
+                states.push(ListingState::new(label, points));
+            }
+        }
+    }
+    states
+}
+
+fn parse_fresco(text: &str) -> Result<Vec<ListingState>, ListingError> {
+    let mut states = vec![];
+    let mut label = None;
+    let mut points = vec![];
+    let mut finish = |label: &mut Option<String>, points: &mut Vec<(f64, f64)>| {
+        if !points.is_empty() {
+            let label = label
+                .take()
+                .unwrap_or_else(|| format!("Set {}", states.len() + 1));
+            states.push(ListingState::new(label, std::mem::take(points)));
+        }
+    };
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('&') || line.eq_ignore_ascii_case("END") {
+            finish(&mut label, &mut points);
+        } else if let Some(directive) = line.strip_prefix('@') {
+            if directive.contains("legend") {
+                if let Some((_, quoted)) = directive.split_once('"') {
+                    label = Some(quoted.trim_end_matches('"').trim().to_string());
+                }
+            }
+        } else {
+            points.push(row(line).ok_or_else(|| ListingError::InvalidRow {
+                line: number + 1,
+                text: line.to_string(),
+            })?);
+        }
+    }
+    finish(&mut label, &mut points);
+    Ok(states)
+}
+
+/// mb/sr per unit of the cross section of a DWUCK4 table header, `None` if the line is not a
+/// `Theta` header with an explicit `fm**2/sr` or `mb/sr` column.
+fn dwuck4_unit(line: &str) -> Option<f64> {
+    let upper = line.to_uppercase();
+    let mut tokens = upper
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')'))
+        .filter(|token| !token.is_empty());
+    if !tokens.clone().any(|token| token == "THETA") {
+        return None;
+    }
+    tokens.find_map(|token| match token {
+        "FM**2/SR" => Some(MB_PER_FM2),
+        "MB/SR" => Some(1.0),
+        _ => None,
+    })
+}
+
+fn parse_dwuck4(text: &str) -> Vec<ListingState> {
+    let mut states = vec![];
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        if let Some(scale) = dwuck4_unit(line) {
+            let points: Vec<_> = table(&mut lines)
+                .into_iter()
+                .map(|(angle, sigma)| (angle, sigma * scale))
+                .collect();
+            if !points.is_empty() {
+                states.push(ListingState::new(
+                    format!("State {}", states.len() + 1),
+                    points,
+                ));
+            }
+        }
+    }
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DWUCK4: &str = "\
+ DWUCK4 - DISTORTED WAVES U.COLORADO
+ Theta dependence of the form factor sig
+   1.00 2.00
+ 0ANGLE INTEGRATED CROSS SECTION
+     Theta   Inelsig,fm**2/sr   Polz    Asymm
+     10.00   2.0000E+00   0.100   0.000
+     20.00   1.0000E+00   0.200   0.000
+
+     Theta   Sigma (mb/sr)
+     10.00   5.0000E+00
+     20.00   4.0000E+00
+";
+
+    #[test]
+    fn dwuck4_tables_need_an_explicit_unit() {
+        let imported =
+            ImportedCrossSections::import("x.out", ListingFormat::Dwuck4, DWUCK4).unwrap();
+        assert_eq!(imported.states.len(), 2);
+        assert_eq!(imported.states[0].cm_angles, [10.0, 20.0]);
+        assert_eq!(
+            imported.states[0].cross_sections,
+            [2.0 * MB_PER_FM2, MB_PER_FM2]
+        );
+        assert_eq!(imported.states[1].cross_sections, [5.0, 4.0]);
+    }
+
+    #[test]
+    fn excitation_is_read_from_the_label_or_left_to_the_user() {
+        assert_eq!(excitation_of("48Ca(d,p)49Ca(3/2- 2.023)"), Some(2.023));
+        assert_eq!(excitation_of("State 1"), None);
+
+        let state = ListingState::new("State 1".to_string(), vec![(10.0, 1.0)]);
+        assert_eq!(
+            state.excitation(),
+            Err(ListingError::NoExcitation("State 1".to_string()))
+        );
+    }
+
+    const PTOLEMY: &str = "\
+ PTOLEMY   VERSION OF 1 MAR 2010
+ REACTION: 48CA(D,P)49CA(3/2- 2.023)   ELAB= 16.000
+           C.M.  REACTION     REACTION  LOC   LAB     LAB
+           ANGLE    SIGMA        /RUTH  ANGLE   SIGMA
+           0.00   1.0000D+01     0.00   0.00   1.0D+01
+          10.00   2.0000D+01     0.00   9.80   2.0D+01
+          20.00   5.0000D+00     0.00  19.61   5.0D+00
+ TOTAL:   1.2345D+01
+";
+
+    const FRESCO: &str = "\
+# 48Ca(d,p) at 16 MeV
+@legend on
+@s0 legend \"49Ca(3/2- 2.023)\"
+  10.000  2.0E+01
+   0.000  1.0E+01
+&
+@s1 legend \"49Ca(1/2- 3.991)\"
+  10.000  4.0
+  20.000  2.0
+END
+";
+
+    #[test]
+    fn formats_are_detected_from_the_contents() {
+        assert_eq!(ListingFormat::detect(PTOLEMY), Some(ListingFormat::Ptolemy));
+        assert_eq!(ListingFormat::detect(FRESCO), Some(ListingFormat::Fresco));
+        assert_eq!(ListingFormat::detect(DWUCK4), Some(ListingFormat::Dwuck4));
+        assert_eq!(ListingFormat::detect("10 1\n20 2\n"), None);
+    }
+
+    #[test]
+    fn ptolemy_tables_are_labelled_by_their_reaction() {
+        let imported =
+            ImportedCrossSections::import("49ca.out", ListingFormat::Ptolemy, PTOLEMY).unwrap();
+        let [state] = imported.states.as_slice() else {
+            panic!("{:?}", imported.states);
+        };
+        assert_eq!(state.label, "48CA(D,P)49CA(3/2- 2.023)   ELAB= 16.000");
+        assert_eq!(state.excitation, Some(2.023));
+        assert_eq!(state.cm_angles, [0.0, 10.0, 20.0]);
+        assert_eq!(state.cross_sections, [10.0, 20.0, 5.0]);
+        // halfway between 20 and 5 mb/sr in the logarithm
+        assert!((state.cm_at(15.0).unwrap() - 10.0).abs() < 1e-12);
+        assert!(state.cm_at(25.0).is_err());
+    }
+
+    #[test]
+    fn fresco_sets_are_sorted_and_labelled_by_their_legend() {
+        let imported =
+            ImportedCrossSections::import("fort.201", ListingFormat::Fresco, FRESCO).unwrap();
+        assert_eq!(imported.states.len(), 2);
+        assert_eq!(imported.states[0].label, "49Ca(3/2- 2.023)");
+        assert_eq!(imported.states[0].cm_angles, [0.0, 10.0]);
+        assert_eq!(imported.states[0].cross_sections, [10.0, 20.0]);
+        assert_eq!(imported.states[1].excitation, Some(3.991));
+
+        assert_eq!(
+            ImportedCrossSections::import("fort.201", ListingFormat::Fresco, "10 1\nten 2\n"),
+            Err(ListingError::InvalidRow {
+                line: 2,
+                text: "ten 2".to_string()
+            })
+        );
+        assert_eq!(
+            ImportedCrossSections::import("x", ListingFormat::Ptolemy, FRESCO),
+            Err(ListingError::Empty(ListingFormat::Ptolemy))
+        );
+    }
+}
@@ -1,11 +1,57 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-// When compiling natively:
+// Headless command-line mode, which never opens a window:
 #[cfg(not(target_arch = "wasm32"))]
+fn run_cli() {
+    use clap::Parser;
+    match sps_beam_time_estimator::cli::Cli::parse().run() {
+        Ok(output) => println!("{output}"),
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+// A Windows release build has no console of its own (see `windows_subsystem` above), so the
+// command-line mode writes to the console it was started from.
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // SAFETY: AttachConsole takes no pointers. Without a parent console, e.g. when started from
+    // Explorer, it fails and the output is lost as before.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+// When compiling natively without the GUI:
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "gui")))]
+fn main() {
+    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    #[cfg(all(windows, not(debug_assertions)))]
+    attach_parent_console();
+    run_cli();
+}
+
+// When compiling natively:
+#[cfg(all(not(target_arch = "wasm32"), feature = "gui"))]
 fn main() -> eframe::Result {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+    // Any arguments select the command-line mode.
+    if std::env::args_os().len() > 1 {
+        #[cfg(all(windows, not(debug_assertions)))]
+        attach_parent_console();
+        run_cli();
+        return Ok(());
+    }
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_min_inner_size([400.0, 300.0])
@@ -28,7 +74,7 @@ fn main() -> eframe::Result {
 }
 
 // When compiling to web using trunk:
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "gui"))]
 fn main() {
     // Redirect `log` message to `console.log` and friends:
     eframe::WebLogger::init(log::LevelFilter::Debug).ok();
@@ -0,0 +1,566 @@
+//! Embedded nuclide table: atomic masses and natural abundances of the stable nuclides, of the
+//! long-lived ones used as beams or targets (t, ⁶He, ¹⁴C, actinides, ...) and of the unstable
+//! recoils of common transfer reactions near stability (⁴¹Ca, ⁵³Mn, ²⁰⁹Pb, ...).
+//!
+//! Masses are neutral-atom masses in u (AME), which are also molar masses in g/mol.
+//! Abundances are natural isotopic abundances in % (IUPAC); radioactive nuclides have 0.
+
+/// Element symbols indexed by proton number; Z = 0 is the neutron.
+const ELEMENT_SYMBOLS: [&str; 119] = [
+    "n", "H", "He", "Li", "Be", "B", "C", "N", "O", "F", "Ne", "Na", "Mg", "Al", "Si", "P", "S",
+    "Cl", "Ar", "K", "Ca", "Sc", "Ti", "V", "Cr", "Mn", "Fe", "Co", "Ni", "Cu", "Zn", "Ga", "Ge",
+    "As", "Se", "Br", "Kr", "Rb", "Sr", "Y", "Zr", "Nb", "Mo", "Tc", "Ru", "Rh", "Pd", "Ag", "Cd",
+    "In", "Sn", "Sb", "Te", "I", "Xe", "Cs", "Ba", "La", "Ce", "Pr", "Nd", "Pm", "Sm", "Eu", "Gd",
+    "Tb", "Dy", "Ho", "Er", "Tm", "Yb", "Lu", "Hf", "Ta", "W", "Re", "Os", "Ir", "Pt", "Au", "Hg",
+    "Tl", "Pb", "Bi", "Po", "At", "Rn", "Fr", "Ra", "Ac", "Th", "Pa", "U", "Np", "Pu", "Am", "Cm",
+    "Bk", "Cf", "Es", "Fm", "Md", "No", "Lr", "Rf", "Db", "Sg", "Bh", "Hs", "Mt", "Ds", "Rg", "Cn",
+    "Nh", "Fl", "Mc", "Lv", "Ts", "Og",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Nuclide {
+    pub z: i32,
+    pub a: i32,
+    pub mass: f64,      // u
+    pub abundance: f64, // %
+}
+
+const fn nuclide(z: i32, a: i32, mass: f64, abundance: f64) -> Nuclide {
+    Nuclide {
+        z,
+        a,
+        mass,
+        abundance,
+    }
+}
+
+/// Every nuclide in the table, sorted by Z and then A.
+pub const NUCLIDES: &[Nuclide] = &[
+    nuclide(0, 1, 1.008664916, 0.0),
+    nuclide(1, 1, 1.00782503207, 99.9885),
+    nuclide(1, 2, 2.0141017778, 0.0115),
+    nuclide(1, 3, 3.0160492777, 0.0),
+    nuclide(2, 3, 3.0160293191, 0.000134),
+    nuclide(2, 4, 4.00260325415, 99.999866),
+    nuclide(2, 6, 6.0188891, 0.0),
+    nuclide(3, 6, 6.015122795, 7.59),
+    nuclide(3, 7, 7.01600455, 92.41),
+    nuclide(3, 8, 8.0224862, 0.0),
+    nuclide(4, 7, 7.01692983, 0.0),
+    nuclide(4, 9, 9.0121822, 100.0),
+    nuclide(4, 10, 10.0135338, 0.0),
+    nuclide(4, 11, 11.0216611, 0.0),
+    nuclide(5, 8, 8.0246073, 0.0),
+    nuclide(5, 10, 10.0129370, 19.9),
+    nuclide(5, 11, 11.0093054, 80.1),
+    nuclide(5, 12, 12.0143521, 0.0),
+    nuclide(6, 11, 11.0114336, 0.0),
+    nuclide(6, 12, 12.0, 98.93),
+    nuclide(6, 13, 13.0033548378, 1.07),
+    nuclide(6, 14, 14.003241989, 0.0),
+    nuclide(6, 15, 15.0105993, 0.0),
+    nuclide(7, 13, 13.0057386, 0.0),
+    nuclide(7, 14, 14.0030740048, 99.636),
+    nuclide(7, 15, 15.0001088982, 0.364),
+    nuclide(7, 16, 16.0061019, 0.0),
+    nuclide(8, 15, 15.0030656, 0.0),
+    nuclide(8, 16, 15.99491461956, 99.757),
+    nuclide(8, 17, 16.99913170, 0.038),
+    nuclide(8, 18, 17.9991610, 0.205),
+    nuclide(8, 19, 19.0035780, 0.0),
+    nuclide(9, 17, 17.0020952, 0.0),
+    nuclide(9, 18, 18.0009380, 0.0),
+    nuclide(9, 19, 18.99840322, 100.0),
+    nuclide(9, 20, 19.9999813, 0.0),
+    nuclide(10, 20, 19.9924401754, 90.48),
+    nuclide(10, 21, 20.99384668, 0.27),
+    nuclide(10, 22, 21.991385114, 9.25),
+    nuclide(10, 23, 22.9944669, 0.0),
+    nuclide(11, 22, 21.9944364, 0.0),
+    nuclide(11, 23, 22.9897692809, 100.0),
+    nuclide(11, 24, 23.9909630, 0.0),
+    nuclide(12, 24, 23.985041700, 78.99),
+    nuclide(12, 25, 24.98583692, 10.00),
+    nuclide(12, 26, 25.982592929, 11.01),
+    nuclide(12, 27, 26.9843406, 0.0),
+    nuclide(13, 25, 24.9904277, 0.0),
+    nuclide(13, 26, 25.98689169, 0.0),
+    nuclide(13, 27, 26.98153863, 100.0),
+    nuclide(13, 28, 27.9819102, 0.0),
+    nuclide(14, 28, 27.9769265325, 92.223),
+    nuclide(14, 29, 28.976494700, 4.685),
+    nuclide(14, 30, 29.97377017, 3.092),
+    nuclide(14, 31, 30.9753632, 0.0),
+    nuclide(15, 31, 30.97376163, 100.0),
+    nuclide(15, 32, 31.9739076, 0.0),
+    nuclide(15, 33, 32.9717255, 0.0),
+    nuclide(16, 32, 31.97207100, 94.99),
+    nuclide(16, 33, 32.97145876, 0.75),
+    nuclide(16, 34, 33.96786690, 4.25),
+    nuclide(16, 35, 34.9690321, 0.0),
+    nuclide(16, 36, 35.96708076, 0.01),
+    nuclide(17, 35, 34.96885268, 75.76),
+    nuclide(17, 36, 35.9683068, 0.0),
+    nuclide(17, 37, 36.96590259, 24.24),
+    nuclide(18, 36, 35.967545106, 0.3365),
+    nuclide(18, 37, 36.9667763, 0.0),
+    nuclide(18, 38, 37.9627324, 0.0632),
+    nuclide(18, 39, 38.9643132, 0.0),
+    nuclide(18, 40, 39.9623831225, 99.6003),
+    nuclide(18, 41, 40.9645006, 0.0),
+    nuclide(19, 39, 38.96370668, 93.2581),
+    nuclide(19, 40, 39.96399848, 0.0117),
+    nuclide(19, 41, 40.96182576, 6.7302),
+    nuclide(19, 42, 41.9624023, 0.0),
+    nuclide(20, 40, 39.96259098, 96.941),
+    nuclide(20, 41, 40.9622778, 0.0),
+    nuclide(20, 42, 41.95861801, 0.647),
+    nuclide(20, 43, 42.9587666, 0.135),
+    nuclide(20, 44, 43.9554818, 2.086),
+    nuclide(20, 45, 44.9561863, 0.0),
+    nuclide(20, 46, 45.9536926, 0.004),
+    nuclide(20, 48, 47.952534, 0.187),
+    nuclide(20, 49, 48.9556630, 0.0),
+    nuclide(21, 45, 44.9559119, 100.0),
+    nuclide(22, 46, 45.9526316, 8.25),
+    nuclide(22, 47, 46.9517631, 7.44),
+    nuclide(22, 48, 47.9479463, 73.72),
+    nuclide(22, 49, 48.9478700, 5.41),
+    nuclide(22, 50, 49.9447912, 5.18),
+    nuclide(23, 50, 49.9471585, 0.250),
+    nuclide(23, 51, 50.9439595, 99.750),
+    nuclide(24, 50, 49.9460442, 4.345),
+    nuclide(24, 52, 51.9405075, 83.789),
+    nuclide(24, 53, 52.9406494, 9.501),
+    nuclide(24, 54, 53.9388804, 2.365),
+    nuclide(25, 53, 52.9412884, 0.0),
+    nuclide(25, 55, 54.9380451, 100.0),
+    nuclide(26, 54, 53.9396105, 5.845),
+    nuclide(26, 55, 54.9382916, 0.0),
+    nuclide(26, 56, 55.9349375, 91.754),
+    nuclide(26, 57, 56.9353940, 2.119),
+    nuclide(26, 58, 57.9332756, 0.282),
+    nuclide(26, 59, 58.9348734, 0.0),
+    nuclide(27, 57, 56.9362905, 0.0),
+    nuclide(27, 59, 58.9331950, 100.0),
+    nuclide(27, 60, 59.9338171, 0.0),
+    nuclide(28, 58, 57.9353429, 68.0769),
+    nuclide(28, 59, 58.9343459, 0.0),
+    nuclide(28, 60, 59.9307864, 26.2231),
+    nuclide(28, 61, 60.9310560, 1.1399),
+    nuclide(28, 62, 61.9283451, 3.6345),
+    nuclide(28, 63, 62.9296691, 0.0),
+    nuclide(28, 64, 63.9279660, 0.9256),
+    nuclide(29, 63, 62.9295975, 69.15),
+    nuclide(29, 64, 63.9297639, 0.0),
+    nuclide(29, 65, 64.9277895, 30.85),
+    nuclide(30, 64, 63.9291422, 48.268),
+    nuclide(30, 65, 64.9292405, 0.0),
+    nuclide(30, 66, 65.9260334, 27.975),
+    nuclide(30, 67, 66.9271273, 4.102),
+    nuclide(30, 68, 67.9248442, 19.024),
+    nuclide(30, 70, 69.9253193, 0.631),
+    nuclide(31, 69, 68.9255736, 60.108),
+    nuclide(31, 71, 70.9247013, 39.892),
+    nuclide(32, 70, 69.9242474, 20.38),
+    nuclide(32, 72, 71.9220758, 27.31),
+    nuclide(32, 73, 72.9234589, 7.76),
+    nuclide(32, 74, 73.9211778, 36.72),
+    nuclide(32, 76, 75.9214026, 7.83),
+    nuclide(33, 75, 74.9215965, 100.0),
+    nuclide(34, 74, 73.9224764, 0.89),
+    nuclide(34, 76, 75.9192136, 9.37),
+    nuclide(34, 77, 76.9199140, 7.63),
+    nuclide(34, 78, 77.9173091, 23.77),
+    nuclide(34, 80, 79.9165213, 49.61),
+    nuclide(34, 82, 81.9166994, 8.73),
+    nuclide(35, 79, 78.9183371, 50.69),
+    nuclide(35, 81, 80.9162906, 49.31),
+    nuclide(36, 78, 77.9203648, 0.355),
+    nuclide(36, 80, 79.9163790, 2.286),
+    nuclide(36, 82, 81.9134836, 11.593),
+    nuclide(36, 83, 82.914136, 11.500),
+    nuclide(36, 84, 83.911507, 56.987),
+    nuclide(36, 86, 85.91061073, 17.279),
+    nuclide(37, 85, 84.911789738, 72.17),
+    nuclide(37, 87, 86.909180527, 27.83),
+    nuclide(38, 84, 83.913425, 0.56),
+    nuclide(38, 86, 85.9092602, 9.86),
+    nuclide(38, 87, 86.9088771, 7.00),
+    nuclide(38, 88, 87.9056121, 82.58),
+    nuclide(38, 89, 88.9074507, 0.0),
+    nuclide(39, 89, 88.9058483, 100.0),
+    nuclide(40, 90, 89.9047044, 51.45),
+    nuclide(40, 91, 90.9056458, 11.22),
+    nuclide(40, 92, 91.9050408, 17.15),
+    nuclide(40, 93, 92.9064761, 0.0),
+    nuclide(40, 94, 93.9063152, 17.38),
+    nuclide(40, 96, 95.9082734, 2.80),
+    nuclide(41, 93, 92.9063781, 100.0),
+    nuclide(42, 92, 91.906811, 14.53),
+    nuclide(42, 94, 93.9050883, 9.15),
+    nuclide(42, 95, 94.9058421, 15.84),
+    nuclide(42, 96, 95.9046795, 16.67),
+    nuclide(42, 97, 96.9060215, 9.60),
+    nuclide(42, 98, 97.9054082, 24.39),
+    nuclide(42, 100, 99.907477, 9.82),
+    nuclide(43, 99, 98.9062547, 0.0),
+    nuclide(44, 96, 95.907598, 5.54),
+    nuclide(44, 98, 97.905287, 1.87),
+    nuclide(44, 99, 98.9059393, 12.76),
+    nuclide(44, 100, 99.9042195, 12.60),
+    nuclide(44, 101, 100.9055821, 17.06),
+    nuclide(44, 102, 101.9043493, 31.55),
+    nuclide(44, 104, 103.905433, 18.62),
+    nuclide(45, 103, 102.905504, 100.0),
+    nuclide(46, 102, 101.905609, 1.02),
+    nuclide(46, 104, 103.904036, 11.14),
+    nuclide(46, 105, 104.905085, 22.33),
+    nuclide(46, 106, 105.903486, 27.33),
+    nuclide(46, 108, 107.903892, 26.46),
+    nuclide(46, 110, 109.905153, 11.72),
+    nuclide(47, 107, 106.905097, 51.839),
+    nuclide(47, 109, 108.904752, 48.161),
+    nuclide(48, 106, 105.906459, 1.25),
+    nuclide(48, 108, 107.904184, 0.89),
+    nuclide(48, 110, 109.9030021, 12.49),
+    nuclide(48, 111, 110.9041781, 12.80),
+    nuclide(48, 112, 111.9027578, 24.13),
+    nuclide(48, 113, 112.9044017, 12.22),
+    nuclide(48, 114, 113.9033585, 28.73),
+    nuclide(48, 116, 115.904756, 7.49),
+    nuclide(49, 113, 112.904058, 4.29),
+    nuclide(49, 115, 114.903878, 95.71),
+    nuclide(50, 112, 111.904818, 0.97),
+    nuclide(50, 114, 113.902779, 0.66),
+    nuclide(50, 115, 114.903342, 0.34),
+    nuclide(50, 116, 115.901741, 14.54),
+    nuclide(50, 117, 116.902952, 7.68),
+    nuclide(50, 118, 117.901603, 24.22),
+    nuclide(50, 119, 118.903308, 8.59),
+    nuclide(50, 120, 119.9021947, 32.58),
+    nuclide(50, 122, 121.9034390, 4.63),
+    nuclide(50, 124, 123.9052739, 5.79),
+    nuclide(51, 121, 120.9038157, 57.21),
+    nuclide(51, 123, 122.9042140, 42.79),
+    nuclide(52, 120, 119.904020, 0.09),
+    nuclide(52, 122, 121.9030439, 2.55),
+    nuclide(52, 123, 122.9042700, 0.89),
+    nuclide(52, 124, 123.9028179, 4.74),
+    nuclide(52, 125, 124.9044307, 7.07),
+    nuclide(52, 126, 125.9033117, 18.84),
+    nuclide(52, 128, 127.9044631, 31.74),
+    nuclide(52, 130, 129.9062244, 34.08),
+    nuclide(53, 127, 126.904473, 100.0),
+    nuclide(54, 124, 123.905893, 0.0952),
+    nuclide(54, 126, 125.904274, 0.0890),
+    nuclide(54, 128, 127.9035313, 1.9102),
+    nuclide(54, 129, 128.9047794, 26.4006),
+    nuclide(54, 130, 129.9035080, 4.0710),
+    nuclide(54, 131, 130.9050824, 21.2324),
+    nuclide(54, 132, 131.9041535, 26.9086),
+    nuclide(54, 134, 133.9053945, 10.4357),
+    nuclide(54, 136, 135.907219, 8.8573),
+    nuclide(55, 133, 132.905451933, 100.0),
+    nuclide(56, 130, 129.9063208, 0.106),
+    nuclide(56, 132, 131.9050613, 0.101),
+    nuclide(56, 134, 133.9045084, 2.417),
+    nuclide(56, 135, 134.9056886, 6.592),
+    nuclide(56, 136, 135.9045759, 7.854),
+    nuclide(56, 137, 136.9058274, 11.232),
+    nuclide(56, 138, 137.9052472, 71.698),
+    nuclide(56, 139, 138.9088413, 0.0),
+    nuclide(57, 138, 137.907112, 0.090),
+    nuclide(57, 139, 138.9063533, 99.910),
+    nuclide(58, 136, 135.907172, 0.185),
+    nuclide(58, 138, 137.905991, 0.251),
+    nuclide(58, 140, 139.9054387, 88.450),
+    nuclide(58, 141, 140.9082775, 0.0),
+    nuclide(58, 142, 141.909244, 11.114),
+    nuclide(59, 141, 140.9076528, 100.0),
+    nuclide(60, 142, 141.9077233, 27.2),
+    nuclide(60, 143, 142.9098143, 12.2),
+    nuclide(60, 144, 143.9100873, 23.8),
+    nuclide(60, 145, 144.9125736, 8.3),
+    nuclide(60, 146, 145.9131169, 17.2),
+    nuclide(60, 148, 147.916893, 5.7),
+    nuclide(60, 150, 149.920891, 5.6),
+    nuclide(61, 147, 146.9151385, 0.0),
+    nuclide(62, 144, 143.911999, 3.07),
+    nuclide(62, 147, 146.9148979, 14.99),
+    nuclide(62, 148, 147.9148227, 11.24),
+    nuclide(62, 149, 148.9171847, 13.82),
+    nuclide(62, 150, 149.9172755, 7.38),
+    nuclide(62, 152, 151.9197324, 26.75),
+    nuclide(62, 154, 153.9222093, 22.75),
+    nuclide(63, 151, 150.9198502, 47.81),
+    nuclide(63, 153, 152.9212303, 52.19),
+    nuclide(64, 152, 151.9197910, 0.20),
+    nuclide(64, 154, 153.9208656, 2.18),
+    nuclide(64, 155, 154.9226220, 14.80),
+    nuclide(64, 156, 155.9221227, 20.47),
+    nuclide(64, 157, 156.9239601, 15.65),
+    nuclide(64, 158, 157.9241039, 24.84),
+    nuclide(64, 160, 159.9270541, 21.86),
+    nuclide(65, 159, 158.9253468, 100.0),
+    nuclide(66, 156, 155.924283, 0.06),
+    nuclide(66, 158, 157.924409, 0.10),
+    nuclide(66, 160, 159.9251975, 2.34),
+    nuclide(66, 161, 160.9269334, 18.91),
+    nuclide(66, 162, 161.9267984, 25.51),
+    nuclide(66, 163, 162.9287312, 24.90),
+    nuclide(66, 164, 163.9291748, 28.18),
+    nuclide(67, 165, 164.9303221, 100.0),
+    nuclide(68, 162, 161.928778, 0.139),
+    nuclide(68, 164, 163.929200, 1.601),
+    nuclide(68, 166, 165.9302931, 33.503),
+    nuclide(68, 167, 166.9320482, 22.869),
+    nuclide(68, 168, 167.9323702, 26.978),
+    nuclide(68, 170, 169.9354643, 14.910),
+    nuclide(69, 169, 168.9342133, 100.0),
+    nuclide(70, 168, 167.933897, 0.13),
+    nuclide(70, 170, 169.9347618, 3.04),
+    nuclide(70, 171, 170.9363258, 14.28),
+    nuclide(70, 172, 171.9363815, 21.83),
+    nuclide(70, 173, 172.9382108, 16.13),
+    nuclide(70, 174, 173.9388621, 31.83),
+    nuclide(70, 176, 175.9425717, 12.76),
+    nuclide(71, 175, 174.9407718, 97.41),
+    nuclide(71, 176, 175.9426863, 2.59),
+    nuclide(72, 174, 173.940046, 0.16),
+    nuclide(72, 176, 175.9414086, 5.26),
+    nuclide(72, 177, 176.9432207, 18.60),
+    nuclide(72, 178, 177.9436988, 27.28),
+    nuclide(72, 179, 178.9458161, 13.62),
+    nuclide(72, 180, 179.9465500, 35.08),
+    nuclide(73, 180, 179.9474648, 0.012),
+    nuclide(73, 181, 180.9479958, 99.988),
+    nuclide(74, 180, 179.946704, 0.12),
+    nuclide(74, 182, 181.9482042, 26.50),
+    nuclide(74, 183, 182.9502230, 14.31),
+    nuclide(74, 184, 183.9509312, 30.64),
+    nuclide(74, 186, 185.9543641, 28.43),
+    nuclide(75, 185, 184.9529550, 37.40),
+    nuclide(75, 187, 186.9557531, 62.60),
+    nuclide(76, 184, 183.9524891, 0.02),
+    nuclide(76, 186, 185.9538382, 1.59),
+    nuclide(76, 187, 186.9557505, 1.96),
+    nuclide(76, 188, 187.9558382, 13.24),
+    nuclide(76, 189, 188.9581475, 16.15),
+    nuclide(76, 190, 189.9584470, 26.26),
+    nuclide(76, 192, 191.9614807, 40.78),
+    nuclide(77, 191, 190.9605940, 37.3),
+    nuclide(77, 193, 192.9629264, 62.7),
+    nuclide(78, 190, 189.959932, 0.014),
+    nuclide(78, 192, 191.9610380, 0.782),
+    nuclide(78, 194, 193.9626803, 32.967),
+    nuclide(78, 195, 194.9647911, 33.832),
+    nuclide(78, 196, 195.9649515, 25.242),
+    nuclide(78, 198, 197.967893, 7.163),
+    nuclide(79, 197, 196.9665687, 100.0),
+    nuclide(79, 198, 197.9682435, 0.0),
+    nuclide(80, 196, 195.965833, 0.15),
+    nuclide(80, 198, 197.9667690, 9.97),
+    nuclide(80, 199, 198.9682799, 16.87),
+    nuclide(80, 200, 199.9683260, 23.10),
+    nuclide(80, 201, 200.9703023, 13.18),
+    nuclide(80, 202, 201.9706430, 29.86),
+    nuclide(80, 204, 203.9734939, 6.87),
+    nuclide(81, 203, 202.9723442, 29.52),
+    nuclide(81, 205, 204.9744275, 70.48),
+    nuclide(82, 204, 203.9730436, 1.4),
+    nuclide(82, 205, 204.9744816, 0.0),
+    nuclide(82, 206, 205.9744653, 24.1),
+    nuclide(82, 207, 206.9758969, 22.1),
+    nuclide(82, 208, 207.9766521, 52.4),
+    nuclide(82, 209, 208.9810902, 0.0),
+    nuclide(82, 210, 209.9841885, 0.0),
+    nuclide(83, 209, 208.9803987, 100.0),
+    nuclide(83, 210, 209.9841204, 0.0),
+    nuclide(84, 210, 209.9828737, 0.0),
+    nuclide(85, 211, 210.9874963, 0.0),
+    nuclide(86, 222, 222.0175777, 0.0),
+    nuclide(87, 223, 223.0197359, 0.0),
+    nuclide(88, 226, 226.0254098, 0.0),
+    nuclide(89, 227, 227.0277521, 0.0),
+    nuclide(90, 232, 232.0380553, 100.0),
+    nuclide(90, 233, 233.0415805, 0.0),
+    nuclide(91, 231, 231.0358840, 100.0),
+    nuclide(92, 233, 233.0396352, 0.0),
+    nuclide(92, 234, 234.0409521, 0.0054),
+    nuclide(92, 235, 235.0439299, 0.7204),
+    nuclide(92, 238, 238.0507882, 99.2742),
+    nuclide(92, 239, 239.0542920, 0.0),
+    nuclide(93, 237, 237.0481734, 0.0),
+    nuclide(94, 238, 238.0495599, 0.0),
+    nuclide(94, 239, 239.0521634, 0.0),
+    nuclide(94, 240, 240.0538135, 0.0),
+    nuclide(94, 242, 242.0587426, 0.0),
+    nuclide(94, 244, 244.064204, 0.0),
+    nuclide(95, 241, 241.0568291, 0.0),
+    nuclide(95, 243, 243.0613811, 0.0),
+    nuclide(96, 244, 244.0627526, 0.0),
+    nuclide(96, 248, 248.072349, 0.0),
+    nuclide(98, 252, 252.081626, 0.0),
+];
+
+impl Nuclide {
+    pub fn symbol(&self) -> &'static str {
+        element_symbol(self.z).unwrap_or("?")
+    }
+
+    /// Name like `208Pb`; the neutron is just `n`.
+    pub fn name(&self) -> String {
+        if self.z == 0 {
+            "n".to_string()
+        } else {
+            format!("{}{}", self.a, self.symbol())
+        }
+    }
+
+    pub fn is_natural(&self) -> bool {
+        self.abundance > 0.0
+    }
+}
+
+pub fn element_symbol(z: i32) -> Option<&'static str> {
+    usize::try_from(z)
+        .ok()
+        .and_then(|z| ELEMENT_SYMBOLS.get(z))
+        .copied()
+}
+
+/// Proton number of an element symbol, ignoring case. The neutron is not an element, so `n`
+/// is nitrogen.
+pub fn element_z(symbol: &str) -> Option<i32> {
+    (1..=118).find(|&z| element_symbol(z).is_some_and(|s| s.eq_ignore_ascii_case(symbol)))
+}
+
+pub fn find(z: i32, a: i32) -> Option<&'static Nuclide> {
+    NUCLIDES.iter().find(|n| n.z == z && n.a == a)
+}
+
+/// Nuclides of element `z` in the table.
+pub fn isotopes(z: i32) -> impl Iterator<Item = &'static Nuclide> {
+    NUCLIDES.iter().filter(move |n| n.z == z)
+}
+
+/// The most abundant natural isotope of element `z`, or its first radioactive one in the table
+/// if it has none.
+pub fn most_abundant(z: i32) -> Option<&'static Nuclide> {
+    isotopes(z).reduce(|best, n| {
+        if n.abundance > best.abundance {
+            n
+        } else {
+            best
+        }
+    })
+}
+
+/// Molar mass (g/mol) of the element with natural isotopic composition, or `None` if it has no
+/// naturally occurring nuclides in the table.
+pub fn natural_molar_mass(z: i32) -> Option<f64> {
+    let (mass, abundance) = isotopes(z)
+        .filter(|n| n.is_natural())
+        .fold((0.0, 0.0), |(mass, abundance), n| {
+            (mass + n.mass * n.abundance, abundance + n.abundance)
+        });
+    (abundance > 0.0).then(|| mass / abundance)
+}
+
+/// Splits a query like `208Pb`, `Pb208` or `pb-208` into its element symbol and mass number.
+fn split_query(query: &str) -> (String, String) {
+    let query: String = query
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+    let letters = query.chars().filter(|c| c.is_ascii_alphabetic()).collect();
+    let digits = query.chars().filter(|c| c.is_ascii_digit()).collect();
+    (letters, digits)
+}
+
+/// Looks up a nuclide by name, e.g. `208Pb`, `Pb-208`, or `p`, `d`, `t`, `a` for the light ions.
+pub fn parse(name: &str) -> Option<&'static Nuclide> {
+    match name.trim() {
+        "n" => return find(0, 1),
+        "p" => return find(1, 1),
+        "d" => return find(1, 2),
+        "t" => return find(1, 3),
+        "a" | "α" => return find(2, 4),
+        _ => {}
+    }
+    let (symbol, digits) = split_query(name);
+    find(element_z(&symbol)?, digits.parse().ok()?)
+}
+
+/// Nuclides whose symbol starts with the letters of `query` and whose mass number starts with
+/// its digits, so `pb`, `20pb` and `Pb208` all narrow the list as the user types. A light-ion
+/// name like `d` lists its nuclide first.
+pub fn search(query: &str) -> Vec<&'static Nuclide> {
+    let (symbol, digits) = split_query(query);
+    let mut matches: Vec<&'static Nuclide> = parse(query).into_iter().collect();
+    for nuclide in NUCLIDES {
+        if nuclide
+            .symbol()
+            .to_ascii_lowercase()
+            .starts_with(&symbol.to_ascii_lowercase())
+            && nuclide.a.to_string().starts_with(&digits)
+            && !matches.contains(&nuclide)
+        {
+            matches.push(nuclide);
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn za(nuclide: Option<&Nuclide>) -> Option<(i32, i32)> {
+        nuclide.map(|n| (n.z, n.a))
+    }
+
+    #[test]
+    fn names_parse_in_either_order() {
+        assert_eq!(za(parse("208Pb")), Some((82, 208)));
+        assert_eq!(za(parse("Pb208")), Some((82, 208)));
+        assert_eq!(za(parse("pb-208")), Some((82, 208)));
+        assert_eq!(za(parse("d")), Some((1, 2)));
+        assert_eq!(za(parse("α")), Some((2, 4)));
+        assert_eq!(za(parse("300Pb")), None);
+        assert_eq!(find(82, 208).unwrap().name(), "208Pb");
+    }
+
+    #[test]
+    fn nitrogen_is_not_the_neutron() {
+        assert_eq!(element_z("N"), Some(7));
+        assert_eq!(element_z("n"), Some(7));
+        assert_eq!(element_z("pb"), Some(82));
+        assert_eq!(element_z("Xx"), None);
+        assert_eq!(za(parse("14N")), Some((7, 14)));
+        assert_eq!(za(parse("15N")), Some((7, 15)));
+        assert_eq!(za(parse("n-15")), Some((7, 15)));
+        assert_eq!(za(parse("n")), Some((0, 1)));
+    }
+
+    #[test]
+    fn natural_elements_average_over_the_abundances() {
+        assert!((natural_molar_mass(82).unwrap() - 207.2).abs() < 0.05);
+        assert!((natural_molar_mass(6).unwrap() - 12.011).abs() < 0.001);
+        // Tc has no stable isotope
+        assert_eq!(natural_molar_mass(43), None);
+    }
+
+    #[test]
+    fn search_narrows_by_symbol_and_mass_number() {
+        assert!(search("20pb").iter().all(|n| n.z == 82 && n.a >= 200));
+        assert_eq!(za(search("Pb208").first().copied()), Some((82, 208)));
+        assert_eq!(za(search("15N").first().copied()), Some((7, 15)));
+        let nitrogen: Vec<_> = search("N").into_iter().filter(|n| n.z == 7).collect();
+        assert!(nitrogen.len() >= 2);
+        let n1: Vec<_> = search("n1").iter().map(|n| (n.z, n.a)).collect();
+        assert!(n1.contains(&(7, 14)) && n1.contains(&(7, 15)));
+        assert_eq!(za(search("d").first().copied()), Some((1, 2)));
+    }
+}
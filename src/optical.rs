@@ -0,0 +1,643 @@
+//! Optical-model elastic scattering of light ions, for normalization runs above the Coulomb
+//! barrier where Rutherford scattering no longer holds.
+//!
+//! The radial Schrödinger equation with a global optical potential is integrated outwards by
+//! the Numerov method and matched to Coulomb functions for the S-matrix of every partial wave.
+//! Each beam has one global potential:
+//!
+//! - p: Koning & Delaroche, Nucl. Phys. A 713 (2003) 231
+//! - d: An & Cai, Phys. Rev. C 73 (2006) 054605, without the spin-orbit term
+//! - ³He: Becchetti & Greenlees, Polarization Phenomena in Nuclear Reactions (1971) 682
+//! - α: McFadden & Satchler, Nucl. Phys. 84 (1966) 177
+//!
+//! Protons and ³He have spin ½ and feel the spin-orbit term; deuterons and α are treated as
+//! spinless. Radii are reduced radii, R = r A^⅓ with A of the target.
+
+use super::complex::Complex;
+use super::coulomb::{coulomb_phases, CoulombWaves};
+use super::kinematics::{nucleus_name, KinematicsError, Particle, Reaction};
+use super::rutherford::{COULOMB_CONSTANT, MB_PER_FM2};
+
+pub const HBARC: f64 = 197.3269804; // MeV fm
+const PION_WAVELENGTH_SQUARED: f64 = 2.0; // fm^2, (ħ/m_π c)^2 of the spin-orbit term
+const STEP: f64 = 0.05; // fm, of the Numerov integration
+const MATCHING_DIFFUSENESSES: f64 = 12.0; // matching radius outside the largest potential radius
+const MIN_MATCHING_RADIUS: f64 = 15.0; // fm
+const MATCHING_SEPARATION: usize = 10; // steps between the two matching points
+const MAX_PARTIAL_WAVES: usize = 300;
+const S_MATRIX_ACCURACY: f64 = 1e-8;
+const RESCALE: f64 = 1e100; // largest wave function kept during the integration
+
+/// Lab angles of the elastic curves, in degrees.
+pub const CURVE_STEP: f64 = 5.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OpticalError {
+    /// There is no global potential for the beam.
+    NoPotential(String),
+    /// The target has no charge or mass number the potential can use.
+    InvalidTarget(String),
+    /// The Coulomb functions did not converge at the matching radius.
+    NoCoulombFunctions,
+}
+
+impl std::fmt::Display for OpticalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpticalError::NoPotential(beam) => write!(
+                f,
+                "there is no global optical potential for a {beam} beam (only p, d, 3He and α)"
+            ),
+            OpticalError::InvalidTarget(target) => {
+                write!(f, "{target} is not a target for the optical model")
+            }
+            OpticalError::NoCoulombFunctions => write!(
+                f,
+                "the Coulomb functions did not converge at the matching radius"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OpticalError {}
+
+/// Global optical potential for one kind of beam.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum GlobalPotential {
+    KoningDelaroche,
+    AnCai,
+    BecchettiGreenlees,
+    McFaddenSatchler,
+}
+
+impl GlobalPotential {
+    pub const ALL: [GlobalPotential; 4] = [
+        GlobalPotential::KoningDelaroche,
+        GlobalPotential::AnCai,
+        GlobalPotential::BecchettiGreenlees,
+        GlobalPotential::McFaddenSatchler,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GlobalPotential::KoningDelaroche => "Koning–Delaroche",
+            GlobalPotential::AnCai => "An–Cai",
+            GlobalPotential::BecchettiGreenlees => "Becchetti–Greenlees",
+            GlobalPotential::McFaddenSatchler => "McFadden–Satchler",
+        }
+    }
+
+    /// The potential for a beam of proton number `z` and mass number `a`.
+    pub fn for_beam(z: i32, a: i32) -> Option<Self> {
+        match (z, a) {
+            (1, 1) => Some(GlobalPotential::KoningDelaroche),
+            (1, 2) => Some(GlobalPotential::AnCai),
+            (2, 3) => Some(GlobalPotential::BecchettiGreenlees),
+            (2, 4) => Some(GlobalPotential::McFaddenSatchler),
+            _ => None,
+        }
+    }
+
+    /// Whether the projectile has spin ½ and feels the spin-orbit term.
+    pub fn spin_half(&self) -> bool {
+        matches!(
+            self,
+            GlobalPotential::KoningDelaroche | GlobalPotential::BecchettiGreenlees
+        )
+    }
+
+    /// Parameters for a target of proton number `z` and mass number `a` at a lab energy of
+    /// `energy` MeV.
+    pub fn parameters(&self, z: i32, a: i32, energy: f64) -> OpticalParameters {
+        let mass = a as f64;
+        let asymmetry = (a - 2 * z) as f64 / mass; // (N - Z) / A
+        let a13 = mass.powf(1.0 / 3.0);
+        match self {
+            GlobalPotential::KoningDelaroche => {
+                let v1 = 59.30 + 21.0 * asymmetry - 0.024 * mass;
+                let v2 = 0.007067 + 4.23e-6 * mass;
+                let v3 = 1.729e-5 + 1.136e-8 * mass;
+                let v4 = 7e-9;
+                let w1 = 14.667 + 0.009629 * mass;
+                let w2 = 73.55 + 0.0795 * mass;
+                let d1 = 16.0 + 16.0 * asymmetry;
+                let d2 = 0.0180 + 0.003802 / (1.0 + ((mass - 156.0) / 8.0).exp());
+                let d3: f64 = 11.5;
+                let vso1 = 5.922 + 0.0030 * mass;
+                let vso2 = 0.0040;
+                let wso1 = -3.1;
+                let wso2: f64 = 160.0;
+                let fermi = -8.4075 + 0.01378 * mass;
+                let coulomb_radius = 1.198 + 0.697 / a13.powi(2) + 12.994 / a13.powi(5);
+                let coulomb_correction = 1.73 / coulomb_radius * z as f64 / a13;
+
+                let f = energy - fermi;
+                let real = v1 * (1.0 - v2 * f + v3 * f.powi(2) - v4 * f.powi(3))
+                    + coulomb_correction * v1 * (v2 - 2.0 * v3 * f + 3.0 * v4 * f.powi(2));
+                let radius = 1.3039 - 0.4054 / a13;
+                let diffuseness = 0.6778 - 1.487e-4 * mass;
+                let spin_orbit = WoodsSaxon {
+                    depth: vso1 * (-vso2 * f).exp(),
+                    radius: 1.1854 - 0.647 / a13,
+                    diffuseness: 0.59,
+                };
+                OpticalParameters {
+                    real: WoodsSaxon {
+                        depth: real,
+                        radius,
+                        diffuseness,
+                    },
+                    volume: WoodsSaxon {
+                        depth: w1 * f.powi(2) / (f.powi(2) + w2.powi(2)),
+                        radius,
+                        diffuseness,
+                    },
+                    surface: WoodsSaxon {
+                        depth: d1 * f.powi(2) * (-d2 * f).exp() / (f.powi(2) + d3.powi(2)),
+                        radius: 1.3424 - 0.01585 * a13,
+                        diffuseness: 0.5187 + 5.205e-4 * mass,
+                    },
+                    spin_orbit,
+                    spin_orbit_imaginary: wso1 * f.powi(2) / (f.powi(2) + wso2.powi(2)),
+                    coulomb_radius,
+                }
+            }
+            GlobalPotential::AnCai => OpticalParameters {
+                real: WoodsSaxon {
+                    depth: 91.85 - 0.249 * energy
+                        + 1.16e-4 * energy.powi(2)
+                        + 0.642 * z as f64 / a13,
+                    radius: 1.152 - 0.00776 / a13,
+                    diffuseness: 0.719 + 0.0126 * a13,
+                },
+                volume: WoodsSaxon {
+                    depth: (1.104 + 0.0622 * energy).max(0.0),
+                    radius: 1.305 + 0.0997 / a13,
+                    diffuseness: 0.855 - 0.1 * a13,
+                },
+                surface: WoodsSaxon {
+                    depth: (10.83 - 0.0306 * energy).max(0.0),
+                    radius: 1.334 + 0.152 / a13,
+                    diffuseness: 0.531 + 0.062 * a13,
+                },
+                spin_orbit: WoodsSaxon::default(),
+                spin_orbit_imaginary: 0.0,
+                coulomb_radius: 1.303,
+            },
+            GlobalPotential::BecchettiGreenlees => OpticalParameters {
+                real: WoodsSaxon {
+                    depth: 151.9 - 0.17 * energy + 50.0 * asymmetry,
+                    radius: 1.20,
+                    diffuseness: 0.72,
+                },
+                volume: WoodsSaxon {
+                    depth: (41.7 - 0.33 * energy + 44.0 * asymmetry).max(0.0),
+                    radius: 1.40,
+                    diffuseness: 0.88,
+                },
+                surface: WoodsSaxon::default(),
+                spin_orbit: WoodsSaxon {
+                    depth: 2.5,
+                    radius: 1.20,
+                    diffuseness: 0.72,
+                },
+                spin_orbit_imaginary: 0.0,
+                coulomb_radius: 1.30,
+            },
+            GlobalPotential::McFaddenSatchler => OpticalParameters {
+                real: WoodsSaxon {
+                    depth: 185.0,
+                    radius: 1.40,
+                    diffuseness: 0.52,
+                },
+                volume: WoodsSaxon {
+                    depth: 25.0,
+                    radius: 1.40,
+                    diffuseness: 0.52,
+                },
+                surface: WoodsSaxon::default(),
+                spin_orbit: WoodsSaxon::default(),
+                spin_orbit_imaginary: 0.0,
+                coulomb_radius: 1.30,
+            },
+        }
+    }
+}
+
+/// Woods–Saxon form factor of one term of the potential.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct WoodsSaxon {
+    pub depth: f64,       // MeV
+    pub radius: f64,      // fm, reduced
+    pub diffuseness: f64, // fm
+}
+
+impl WoodsSaxon {
+    /// f(r) = 1 / (1 + exp((r − R)/a)) for R = radius · `a13`.
+    pub fn shape(&self, r: f64, a13: f64) -> f64 {
+        1.0 / (1.0 + ((r - self.radius * a13) / self.diffuseness).exp())
+    }
+
+    /// df/dr
+    pub fn derivative(&self, r: f64, a13: f64) -> f64 {
+        let x = ((r - self.radius * a13) / self.diffuseness).exp();
+        if x.is_infinite() {
+            return 0.0;
+        }
+        -x / (self.diffuseness * (1.0 + x).powi(2))
+    }
+
+    fn extent(&self, a13: f64) -> f64 {
+        if self.depth == 0.0 {
+            return 0.0;
+        }
+        self.radius * a13 + MATCHING_DIFFUSENESSES * self.diffuseness
+    }
+}
+
+/// Parameters of an optical potential
+/// U(r) = −V f_V − i W f_W + 4 i a_D W_D df_D/dr + (ħ/m_π c)² (V_so + i W_so) (1/r) df_so/dr l·σ
+/// plus the Coulomb potential of a uniformly charged sphere.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct OpticalParameters {
+    pub real: WoodsSaxon,
+    pub volume: WoodsSaxon,  // imaginary
+    pub surface: WoodsSaxon, // imaginary, derivative form
+    pub spin_orbit: WoodsSaxon,
+    pub spin_orbit_imaginary: f64, // MeV, with the geometry of spin_orbit
+    pub coulomb_radius: f64,       // fm, reduced
+}
+
+impl OpticalParameters {
+    /// Central potential in MeV at `r` fm, Coulomb included.
+    pub(crate) fn central(&self, r: f64, a13: f64, z1z2: f64) -> Complex {
+        let coulomb_radius = self.coulomb_radius * a13;
+        let coulomb = if r < coulomb_radius {
+            z1z2 * COULOMB_CONSTANT / (2.0 * coulomb_radius) * (3.0 - (r / coulomb_radius).powi(2))
+        } else {
+            z1z2 * COULOMB_CONSTANT / r
+        };
+        let real = -self.real.depth * self.real.shape(r, a13) + coulomb;
+        let imaginary = -self.volume.depth * self.volume.shape(r, a13)
+            + 4.0 * self.surface.diffuseness * self.surface.depth * self.surface.derivative(r, a13);
+        Complex::new(real, imaginary)
+    }
+
+    /// Spin-orbit potential in MeV at `r` fm per unit of l·σ.
+    pub(crate) fn spin_orbit(&self, r: f64, a13: f64) -> Complex {
+        let radial = PION_WAVELENGTH_SQUARED * self.spin_orbit.derivative(r, a13) / r;
+        Complex::new(self.spin_orbit.depth, self.spin_orbit_imaginary) * radial
+    }
+
+    fn extent(&self, a13: f64) -> f64 {
+        [
+            self.real.extent(a13),
+            self.volume.extent(a13),
+            self.surface.extent(a13),
+            self.spin_orbit.extent(a13),
+            self.coulomb_radius * a13,
+        ]
+        .into_iter()
+        .fold(MIN_MATCHING_RADIUS, f64::max)
+    }
+}
+
+/// Radial wave function of one partial wave, normalized to (i/2)(H⁻ − S H⁺) outside the
+/// potential, i.e. to F_L without the nuclear potential.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PartialWave {
+    pub u: Vec<Complex>,
+    pub s: Complex,
+}
+
+/// Relative motion of projectile and target in an optical potential on the integration grid.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Channel {
+    pub k: f64,     // fm^-1
+    pub eta: f64,   // Sommerfeld parameter
+    pub kappa: f64, // 2μ/ħ² in MeV^-1 fm^-2
+    pub step: f64,  // fm
+    pub l_max: usize,
+    central: Vec<Complex>,      // MeV
+    spin_orbit: Vec<Complex>,   // MeV per unit of l·σ
+    coulomb: [CoulombWaves; 2], // at the two matching points
+}
+
+impl Channel {
+    /// Channel of `projectile` hitting `target` at rest with `energy` MeV in the lab.
+    pub fn new(
+        parameters: &OpticalParameters,
+        projectile: &Particle,
+        target: &Particle,
+        energy: f64,
+    ) -> Result<Self, OpticalError> {
+        let (m1, m2) = (projectile.mass, target.mass);
+        let s = (m1 + m2).powi(2) + 2.0 * m2 * energy;
+        let momentum =
+            ((s - (m1 + m2).powi(2)) * (s - (m1 - m2).powi(2))).sqrt() / (2.0 * s.sqrt());
+        Self::with_momentum(parameters, projectile, target, momentum, 0.0)
+    }
+
+    /// Channel with a centre-of-mass momentum of `momentum` MeV/c, integrated out to at least
+    /// `radius` fm. The momentum sets k, and μ = E₁E₂/(E₁ + E₂) of the centre-of-mass total
+    /// energies the reduced mass.
+    pub fn with_momentum(
+        parameters: &OpticalParameters,
+        projectile: &Particle,
+        target: &Particle,
+        momentum: f64,
+        radius: f64,
+    ) -> Result<Self, OpticalError> {
+        if target.a < 1 {
+            return Err(OpticalError::InvalidTarget(target.name()));
+        }
+        let (m1, m2) = (projectile.mass, target.mass);
+        let (e1, e2) = (momentum.hypot(m1), momentum.hypot(m2));
+        let mu = e1 * e2 / (e1 + e2);
+
+        let k = momentum / HBARC;
+        let kappa = 2.0 * mu / HBARC.powi(2);
+        let z1z2 = (projectile.z * target.z) as f64;
+        let eta = z1z2 * COULOMB_CONSTANT * kappa / (2.0 * k);
+
+        let a13 = (target.a as f64).powf(1.0 / 3.0);
+        let points = (parameters.extent(a13).max(radius) / STEP).ceil() as usize;
+        let radius = |i: usize| i as f64 * STEP;
+        let central = (0..=points)
+            .map(|i| parameters.central(radius(i), a13, z1z2))
+            .collect();
+        let spin_orbit = (0..=points)
+            .map(|i| match i {
+                0 => Complex::ZERO,
+                _ => parameters.spin_orbit(radius(i), a13),
+            })
+            .collect();
+
+        let l_max = ((k * radius(points)) as usize + 40).min(MAX_PARTIAL_WAVES);
+        let coulomb = [points - MATCHING_SEPARATION, points].map(|i| {
+            CoulombWaves::new(eta, k * radius(i), l_max).ok_or(OpticalError::NoCoulombFunctions)
+        });
+        let [inner, outer] = coulomb;
+        Ok(Self {
+            k,
+            eta,
+            kappa,
+            step: STEP,
+            l_max,
+            central,
+            spin_orbit,
+            coulomb: [inner?, outer?],
+        })
+    }
+
+    /// Radius in fm of grid point `i`.
+    pub fn radius(&self, i: usize) -> f64 {
+        i as f64 * self.step
+    }
+
+    /// Partial wave `l` with `l_sigma` = l·σ, i.e. l for j = l + ½, −(l + 1) for j = l − ½ and
+    /// 0 without spin.
+    pub fn wave(&self, l: usize, l_sigma: f64) -> PartialWave {
+        let h2 = self.step.powi(2);
+        let centrifugal = (l * (l + 1)) as f64;
+        let w = |i: usize| {
+            let r = self.radius(i);
+            Complex::from(self.k.powi(2) - centrifugal / r.powi(2))
+                - (self.central[i] + self.spin_orbit[i] * l_sigma) * self.kappa
+        };
+
+        // Numerov from the regular solution u(0) = 0
+        let points = self.central.len() - 1;
+        let mut u = vec![Complex::ZERO; points + 1];
+        u[1] = Complex::from(1e-10);
+        let mut w_previous = Complex::ZERO; // u(0) = 0 makes its term vanish
+        let mut w_current = w(1);
+        for i in 1..points {
+            let w_next = w(i + 1);
+            let previous = match i {
+                1 => Complex::ZERO,
+                _ => u[i - 1] * (Complex::ONE + w_previous * (h2 / 12.0)),
+            };
+            u[i + 1] = (u[i] * (Complex::ONE - w_current * (5.0 * h2 / 12.0)) * 2.0 - previous)
+                / (Complex::ONE + w_next * (h2 / 12.0));
+            if u[i + 1].abs() > RESCALE {
+                for value in &mut u[..=i + 1] {
+                    *value = *value / RESCALE;
+                }
+            }
+            w_previous = w_current;
+            w_current = w_next;
+        }
+
+        // u = C (i/2)(H⁻ − S H⁺) at both matching points
+        let (a, b) = (points - MATCHING_SEPARATION, points);
+        let [inner, outer] = &self.coulomb;
+        let (minus_a, plus_a) = (inner.h_minus(l), inner.h_plus(l));
+        let (minus_b, plus_b) = (outer.h_minus(l), outer.h_plus(l));
+        let s = (u[a] * minus_b - u[b] * minus_a) / (u[a] * plus_b - u[b] * plus_a);
+        let norm = Complex::I * (minus_b - s * plus_b) * 0.5 / u[b];
+        for value in &mut u {
+            *value = *value * norm;
+        }
+        PartialWave { u, s }
+    }
+}
+
+/// Elastic cross section at one spectrograph angle.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct ElasticPoint {
+    pub angle: f64,    // deg, lab
+    pub cm_angle: f64, // deg
+    pub cm: f64,       // mb/sr, dσ/dΩ_cm
+    pub lab: f64,      // mb/sr, dσ/dΩ_lab
+    pub ratio: f64,    // to Rutherford
+}
+
+/// S-matrix of optical-model elastic scattering of a beam on a target at one energy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElasticScattering {
+    pub potential: GlobalPotential,
+    pub parameters: OpticalParameters,
+    pub beam_energy: f64, // MeV, lab
+    pub k: f64,           // fm^-1
+    pub eta: f64,
+    reaction: Reaction,
+    coulomb_phases: Vec<f64>,
+    s_matrix: Vec<[Complex; 2]>, // j = l + ½ and j = l − ½, equal without spin
+}
+
+impl ElasticScattering {
+    /// Scattering of `beam` at `beam_energy` MeV on `target` with the beam's global potential.
+    pub fn new(beam: Particle, target: Particle, beam_energy: f64) -> Result<Self, OpticalError> {
+        let potential = GlobalPotential::for_beam(beam.z, beam.a)
+            .ok_or_else(|| OpticalError::NoPotential(nucleus_name(beam.z, beam.a)))?;
+        let parameters = potential.parameters(target.z, target.a, beam_energy);
+        let channel = Channel::new(&parameters, &beam, &target, beam_energy)?;
+
+        let mut s_matrix = vec![];
+        for l in 0..=channel.l_max {
+            let s = match potential.spin_half() {
+                true => [
+                    channel.wave(l, l as f64).s,
+                    match l {
+                        0 => channel.wave(0, 0.0).s,
+                        _ => channel.wave(l, -(l as f64) - 1.0).s,
+                    },
+                ],
+                false => [channel.wave(l, 0.0).s; 2],
+            };
+            s_matrix.push(s);
+            let converged = s
+                .iter()
+                .all(|s| (*s - Complex::ONE).abs() < S_MATRIX_ACCURACY);
+            if converged && l as f64 > channel.k * channel.radius(channel.central.len() - 1) {
+                break;
+            }
+        }
+
+        Ok(Self {
+            potential,
+            parameters,
+            beam_energy,
+            k: channel.k,
+            eta: channel.eta,
+            reaction: Reaction {
+                beam,
+                target,
+                ejectile: beam,
+                recoil: target,
+            },
+            coulomb_phases: coulomb_phases(channel.eta, s_matrix.len()),
+            s_matrix,
+        })
+    }
+
+    /// Number of partial waves the cross sections sum over.
+    pub fn partial_waves(&self) -> usize {
+        self.s_matrix.len()
+    }
+
+    /// Rutherford cross section in mb/sr at `cm_angle` degrees.
+    pub fn rutherford(&self, cm_angle: f64) -> f64 {
+        let sin2 = (cm_angle.to_radians() / 2.0).sin().powi(2);
+        (self.eta / (2.0 * self.k * sin2)).powi(2) * MB_PER_FM2
+    }
+
+    /// Elastic cross section in mb/sr at `cm_angle` degrees, summed over the spin states.
+    pub fn cross_section(&self, cm_angle: f64) -> f64 {
+        let theta = cm_angle.to_radians();
+        let x = theta.cos();
+        let sin2 = (theta / 2.0).sin().powi(2);
+        let sigma0 = self.coulomb_phases[0];
+        let mut non_flip = Complex::from_phase(2.0 * sigma0 - self.eta * sin2.ln())
+            * (-self.eta / (2.0 * self.k * sin2));
+        let mut flip = Complex::ZERO;
+
+        // Legendre P_l and associated P_l^1 by upward recurrence
+        let (mut p_previous, mut p) = (0.0, 1.0);
+        let (mut p1_previous, mut p1) = (0.0, 0.0);
+        for (l, [s_up, s_down]) in self.s_matrix.iter().enumerate() {
+            let lf = l as f64;
+            let coulomb = Complex::from_phase(2.0 * self.coulomb_phases[l]);
+            let nuclear = (*s_up - Complex::ONE) * (lf + 1.0) + (*s_down - Complex::ONE) * lf;
+            non_flip += coulomb * nuclear * p / (Complex::I * 2.0 * self.k);
+            flip += coulomb * (*s_up - *s_down) * p1 / (2.0 * self.k);
+
+            let p_next = ((2.0 * lf + 1.0) * x * p - lf * p_previous) / (lf + 1.0);
+            let p1_next = match l {
+                0 => (1.0 - x * x).sqrt(),
+                _ => ((2.0 * lf + 1.0) * x * p1 - (lf + 1.0) * p1_previous) / lf,
+            };
+            (p_previous, p) = (p, p_next);
+            (p1_previous, p1) = (p1, p1_next);
+        }
+        (non_flip.norm_sqr() + flip.norm_sqr()) * MB_PER_FM2
+    }
+
+    /// Cross sections with the spectrograph at `angle` degrees in the lab.
+    pub fn at_angle(&self, angle: f64) -> Result<ElasticPoint, KinematicsError> {
+        let state = self
+            .reaction
+            .solve(self.beam_energy, angle, 0.0)
+            .ok_or(KinematicsError::NotReachable(0.0))?;
+        let cm = self.cross_section(state.cm_angle);
+        Ok(ElasticPoint {
+            angle,
+            cm_angle: state.cm_angle,
+            cm,
+            lab: cm * state.jacobian,
+            ratio: cm / self.rutherford(state.cm_angle),
+        })
+    }
+
+    /// Cross sections every [`CURVE_STEP`] degrees in the lab, where the beam can scatter to.
+    pub fn curve(&self) -> Vec<ElasticPoint> {
+        (1..)
+            .map(|i| i as f64 * CURVE_STEP)
+            .take_while(|&angle| angle < 180.0)
+            .filter_map(|angle| self.at_angle(angle).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn elastic(beam: (i32, i32), target: (i32, i32), energy: f64) -> ElasticScattering {
+        ElasticScattering::new(
+            Particle::find(beam.0, beam.1).unwrap(),
+            Particle::find(target.0, target.1).unwrap(),
+            energy,
+        )
+        .unwrap()
+    }
+
+    /// σ_R = π/k² Σ_l [(l + 1)(1 − |S_l+|²) + l (1 − |S_l−|²)] in mb.
+    fn reaction_cross_section(elastic: &ElasticScattering) -> f64 {
+        let sum: f64 = elastic
+            .s_matrix
+            .iter()
+            .enumerate()
+            .map(|(l, [s_up, s_down])| {
+                let l = l as f64;
+                (l + 1.0) * (1.0 - s_up.norm_sqr()) + l * (1.0 - s_down.norm_sqr())
+            })
+            .sum();
+        PI / elastic.k.powi(2) * sum * MB_PER_FM2
+    }
+
+    #[test]
+    fn reaction_cross_sections_match_measured_ones() {
+        // Measured proton reaction cross sections (R. F. Carlson, At. Data Nucl. Data Tables
+        // 63 (1996) 93), which Koning–Delaroche was fitted to
+        for (target, energy, measured) in [
+            ((82, 208), 30.0, 1730.0),
+            ((20, 40), 30.0, 870.0),
+            ((28, 58), 20.0, 1060.0),
+        ] {
+            let elastic = elastic((1, 1), target, energy);
+            let sigma = reaction_cross_section(&elastic);
+            assert!(
+                (sigma / measured - 1.0).abs() < 0.1,
+                "{target:?} at {energy} MeV: {sigma} mb"
+            );
+            assert!(elastic
+                .s_matrix
+                .iter()
+                .flatten()
+                .all(|s| s.abs() <= 1.0 + 1e-9));
+        }
+    }
+
+    #[test]
+    fn scattering_far_below_the_barrier_is_rutherford() {
+        let elastic = elastic((2, 4), (82, 208), 5.0);
+        assert!(reaction_cross_section(&elastic) < 1e-3);
+        for angle in [20.0, 60.0, 150.0] {
+            let point = elastic.at_angle(angle).unwrap();
+            assert!((point.ratio - 1.0).abs() < 1e-3, "{angle}: {}", point.ratio);
+        }
+    }
+}
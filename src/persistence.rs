@@ -0,0 +1,360 @@
+//! Versioned app state in eframe storage.
+//!
+//! The state is stored as `(version: N, app: (...))`. Blobs written by older versions are
+//! upgraded one step at a time by [`MIGRATIONS`] before being deserialized, so adding a field
+//! no longer wipes the user's saved detectors and settings.
+
+use super::app::BeamTimeApp;
+use super::project::guess_beam_mass_number;
+use ron::{Map, Number, Value};
+
+/// Version of the layout written by [`save`].
+pub const STATE_VERSION: usize = 4;
+
+/// Storage key holding a blob that could not be restored, so it is never lost.
+pub const BACKUP_KEY: &str = "unrestored_app_state";
+
+/// Upgrades a state by one version, adding a note for every value it has to guess.
+type Migration = fn(Map, &mut Vec<String>) -> Result<Map, String>;
+
+/// `MIGRATIONS[n]` upgrades a version `n` state to version `n + 1`.
+const MIGRATIONS: [Migration; STATE_VERSION] = [v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4];
+
+#[derive(serde::Serialize)]
+struct PersistedState<'a> {
+    version: usize,
+    app: &'a BeamTimeApp,
+}
+
+#[derive(serde::Deserialize)]
+struct RestoredState {
+    app: BeamTimeApp,
+}
+
+fn key(name: &str) -> Value {
+    Value::String(name.to_string())
+}
+
+fn field<'a>(map: &'a Map, name: &str) -> Option<&'a Value> {
+    map.iter().find(|(k, _)| **k == key(name)).map(|(_, v)| v)
+}
+
+fn field_mut<'a>(map: &'a mut Map, name: &str) -> Option<&'a mut Value> {
+    map.iter_mut()
+        .find(|(k, _)| **k == key(name))
+        .map(|(_, v)| v)
+}
+
+/// The map at `path` of nested fields below `map`, if every step is a map.
+fn nested_mut<'a>(map: &'a mut Map, path: &[&str]) -> Option<&'a mut Map> {
+    path.iter()
+        .try_fold(map, |map, name| match field_mut(map, name) {
+            Some(Value::Map(inner)) => Some(inner),
+            _ => None,
+        })
+}
+
+/// Version 0 is the unversioned `BeamTimeApp` written directly under `APP_KEY`. It also
+/// carried the SPS result in `time_s`/`time_h`/`time_d`, which are now calculated on demand.
+#[allow(clippy::ptr_arg)] // same signature as every `Migration`
+fn v0_to_v1(app: Map, _notes: &mut Vec<String>) -> Result<Map, String> {
+    let mut app = app;
+    if let Some(Value::Map(mut sps)) = app.remove(&key("sps_settings")) {
+        for obsolete in ["time_s", "time_h", "time_d"] {
+            sps.remove(&key(obsolete));
+        }
+        app.insert(key("sps_settings"), Value::Map(sps));
+    }
+
+    let mut state = Map::new();
+    state.insert(key("version"), Value::Number(Number::Integer(1)));
+    state.insert(key("app"), Value::Map(app));
+    Ok(state)
+}
+
+/// Version 1 held one set of settings; version 2 keeps a list of named scenarios.
+#[allow(clippy::ptr_arg)] // same signature as every `Migration`
+fn v1_to_v2(state: Map, _notes: &mut Vec<String>) -> Result<Map, String> {
+    let mut state = state;
+    let Some(Value::Map(mut app)) = state.remove(&key("app")) else {
+        return Err("saved state has no app".to_string());
+    };
+
+    let mut scenario = Map::new();
+    scenario.insert(key("name"), key("Scenario 1"));
+    for (old, new) in [
+        ("sps_settings", "sps"),
+        ("icespice_settings", "icespice"),
+        ("cebra_settings", "cebra"),
+    ] {
+        if let Some(settings) = app.remove(&key(old)) {
+            scenario.insert(key(new), settings);
+        }
+    }
+    app.insert(key("scenarios"), Value::Seq(vec![Value::Map(scenario)]));
+    app.insert(key("active_scenario"), Value::Number(Number::Integer(0)));
+
+    state.insert(key("version"), Value::Number(Number::Integer(2)));
+    state.insert(key("app"), Value::Map(app));
+    Ok(state)
+}
+
+/// Version 3 divides the beam current by the charge state `q_beam` instead of `z_beam`, so
+/// older states get `q_beam = z_beam` to keep their estimates. The mass number `a_beam` is
+/// guessed from Z, with a note.
+fn v2_to_v3(state: Map, notes: &mut Vec<String>) -> Result<Map, String> {
+    let mut state = state;
+    let Some(Value::Map(mut app)) = state.remove(&key("app")) else {
+        return Err("saved state has no app".to_string());
+    };
+
+    if let Some(Value::Seq(scenarios)) = app.remove(&key("scenarios")) {
+        let scenarios = scenarios
+            .into_iter()
+            .map(|scenario| {
+                let Value::Map(mut scenario) = scenario else {
+                    return scenario;
+                };
+                let name = match field(&scenario, "name") {
+                    Some(Value::String(name)) => name.clone(),
+                    _ => "Scenario".to_string(),
+                };
+                if let Some(Value::Map(mut sps)) = scenario.remove(&key("sps")) {
+                    let z_beam = match field(&sps, "z_beam") {
+                        Some(Value::Number(z)) => z.as_i64().unwrap_or(1),
+                        _ => 1,
+                    };
+                    if field(&sps, "q_beam").is_none() {
+                        sps.insert(key("q_beam"), Value::Number(Number::Integer(z_beam)));
+                    }
+                    if field(&sps, "a_beam").is_none() {
+                        let (a_beam, note) = guess_beam_mass_number(z_beam, &name);
+                        sps.insert(key("a_beam"), Value::Number(Number::Integer(a_beam)));
+                        notes.push(note);
+                    }
+                    scenario.insert(key("sps"), Value::Map(sps));
+                }
+                Value::Map(scenario)
+            })
+            .collect();
+        app.insert(key("scenarios"), Value::Seq(scenarios));
+    }
+
+    state.insert(key("version"), Value::Number(Number::Integer(3)));
+    state.insert(key("app"), Value::Map(app));
+    Ok(state)
+}
+
+/// Version 4 leaves the excitation energy of an imported state empty when the listing does
+/// not give it, so the saved ones become `Some`.
+#[allow(clippy::ptr_arg)] // same signature as every `Migration`
+fn v3_to_v4(state: Map, _notes: &mut Vec<String>) -> Result<Map, String> {
+    let mut state = state;
+    let Some(Value::Map(mut app)) = state.remove(&key("app")) else {
+        return Err("saved state has no app".to_string());
+    };
+
+    if let Some(Value::Seq(scenarios)) = app.remove(&key("scenarios")) {
+        let scenarios = scenarios
+            .into_iter()
+            .map(|mut scenario| {
+                let states = match &mut scenario {
+                    Value::Map(scenario) => nested_mut(scenario, &["sps", "imported"])
+                        .and_then(|imported| field_mut(imported, "states")),
+                    _ => None,
+                };
+                if let Some(Value::Seq(states)) = states {
+                    for listing_state in states {
+                        let Value::Map(listing_state) = listing_state else {
+                            continue;
+                        };
+                        if let Some(excitation) = listing_state.remove(&key("excitation")) {
+                            listing_state.insert(
+                                key("excitation"),
+                                Value::Option(Some(Box::new(excitation))),
+                            );
+                        }
+                    }
+                }
+                scenario
+            })
+            .collect();
+        app.insert(key("scenarios"), Value::Seq(scenarios));
+    }
+
+    state.insert(key("version"), Value::Number(Number::Integer(4)));
+    state.insert(key("app"), Value::Map(app));
+    Ok(state)
+}
+
+fn version_of(state: &Map) -> Result<usize, String> {
+    match (field(state, "version"), field(state, "app")) {
+        (Some(Value::Number(version)), Some(_)) => match version.as_i64() {
+            Some(version) if version >= 0 => Ok(version as usize),
+            _ => Err(format!("invalid state version {version:?}")),
+        },
+        (Some(_), _) => Err("invalid state version".to_string()),
+        (None, _) => Ok(0),
+    }
+}
+
+/// Upgrades and deserializes a blob read from eframe storage. Also returns the notes of the
+/// migrations on values they had to guess.
+pub fn restore(blob: &str) -> Result<(BeamTimeApp, Vec<String>), String> {
+    let Value::Map(mut state) = ron::from_str::<Value>(blob).map_err(|e| e.to_string())? else {
+        return Err("saved state is not a struct".to_string());
+    };
+
+    let version = version_of(&state)?;
+    if version > STATE_VERSION {
+        return Err(format!(
+            "saved state version {version} is newer than this program supports ({STATE_VERSION})"
+        ));
+    }
+    let mut notes = vec![];
+    // `Value` drops enum variant names, so a state that needs no upgrade is read directly.
+    if version == STATE_VERSION {
+        let state: RestoredState = ron::from_str(blob).map_err(|e| e.to_string())?;
+        return Ok((state.app, notes));
+    }
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version) {
+        state = migrate(state, &mut notes)
+            .map_err(|e| format!("upgrading from version {from}: {e}"))?;
+    }
+
+    let app = state
+        .remove(&key("app"))
+        .ok_or_else(|| "saved state has no app".to_string())?;
+    Ok((app.into_rust().map_err(|e| e.to_string())?, notes))
+}
+
+pub fn save(storage: &mut dyn eframe::Storage, app: &BeamTimeApp) {
+    eframe::set_value(
+        storage,
+        eframe::APP_KEY,
+        &PersistedState {
+            version: STATE_VERSION,
+            app,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::Project;
+
+    #[test]
+    fn unversioned_state_is_upgraded_to_the_current_layout() {
+        let blob = r#"(
+            sps_settings: (cross_section: 50.0, z_beam: 2, time_s: 1.0, time_h: 2.0, time_d: 3.0),
+            show_cebra: false,
+        )"#;
+        let project = restore(blob).unwrap().0.project();
+        let [scenario] = project.scenarios.as_slice() else {
+            panic!("{} scenarios", project.scenarios.len());
+        };
+        assert_eq!(scenario.name, "Scenario 1");
+        assert_eq!(scenario.sps.cross_section, 50.0);
+        assert_eq!(
+            (
+                scenario.sps.z_beam,
+                scenario.sps.a_beam,
+                scenario.sps.q_beam
+            ),
+            (2, 4, 2)
+        );
+        assert!(!project.view.show_cebra);
+        assert!(project.view.show_sps);
+    }
+
+    #[test]
+    fn saved_state_is_restored_unchanged() {
+        let mut project = Project::default();
+        project.scenarios[0].sps.beam_current = 35.0;
+        project.view.show_icespice = false;
+        let mut app = BeamTimeApp::default();
+        app.apply_project(project);
+        let blob = ron::to_string(&PersistedState {
+            version: STATE_VERSION,
+            app: &app,
+        })
+        .unwrap();
+        assert_eq!(
+            restore(&blob)
+                .unwrap()
+                .0
+                .project()
+                .to_toml_string()
+                .unwrap(),
+            app.project().to_toml_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn every_migration_ends_at_the_current_version() {
+        let mut state = Map::new();
+        for migrate in MIGRATIONS {
+            state = migrate(state, &mut vec![]).unwrap();
+        }
+        assert_eq!(version_of(&state), Ok(STATE_VERSION));
+    }
+
+    #[test]
+    fn newer_states_are_not_restored() {
+        let error = restore("(version: 99, app: ())").unwrap_err();
+        assert!(error.contains("newer"), "{error}");
+    }
+
+    fn sps_of(state: &Map) -> &Map {
+        let Some(Value::Map(app)) = field(state, "app") else {
+            panic!("no app");
+        };
+        let Some(Value::Seq(scenarios)) = field(app, "scenarios") else {
+            panic!("no scenarios");
+        };
+        let Some(Value::Map(scenario)) = scenarios.first() else {
+            panic!("no scenario");
+        };
+        let Some(Value::Map(sps)) = field(scenario, "sps") else {
+            panic!("no sps");
+        };
+        sps
+    }
+
+    fn integer(map: &Map, name: &str) -> Option<i64> {
+        match field(map, name) {
+            Some(Value::Number(number)) => number.as_i64(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn version_2_proton_beam_stays_a_proton() {
+        let Value::Map(state) = ron::from_str::<Value>(
+            r#"(version: 2, app: (scenarios: [(name: "Protons", sps: (z_beam: 1))]))"#,
+        )
+        .unwrap() else {
+            panic!("not a map");
+        };
+        let mut notes = vec![];
+        let state = v2_to_v3(state, &mut notes).unwrap();
+
+        let sps = sps_of(&state);
+        assert_eq!(integer(sps, "a_beam"), Some(1));
+        assert_eq!(integer(sps, "q_beam"), Some(1));
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].starts_with("Protons: "));
+    }
+
+    #[test]
+    fn version_3_imported_excitation_energies_are_kept() {
+        let blob = r#"(version: 3, app: (scenarios: [(name: "DWUCK", sps: (imported: (
+            states: [(label: "State 1", excitation: 2.5, cm_angles: [10.0], cross_sections: [1.0])]
+        )))]))"#;
+        let (app, notes) = restore(blob).unwrap();
+        let states = &app.project().scenarios[0].sps.imported.states;
+        assert_eq!(states[0].excitation, Some(2.5));
+        assert!(notes.is_empty());
+    }
+}
@@ -0,0 +1,405 @@
+//! Planning of measurements that need more than one run: several SE-SPS field settings to
+//! cover an excitation-energy range, or several angles for an angular distribution.
+
+use super::error::{require_in_range, require_non_negative, require_positive, EstimateError};
+use super::kinematics::KinematicsError;
+use super::spectrograph::{magnetic_rigidity, MEV_PER_TESLA_METER};
+use super::sps::{CrossSectionSource, SPSRunTime, SPSRunTimeSettings};
+
+/// Most field settings a plan may propose.
+pub const MAX_FIELD_SETTINGS: usize = 100;
+
+/// State of the recoil with the cross section it is expected to have.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PlanState {
+    pub excitation: f64,    // MeV
+    pub cross_section: f64, // units.cross_section and cross_section_frame of the SPS settings
+}
+
+/// Excitation-energy range to cover and how much neighbouring settings overlap.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct FieldPlan {
+    pub min_excitation: f64, // MeV
+    pub max_excitation: f64, // MeV, no plan unless above min_excitation
+    pub overlap: f64,        // fraction of the focal plane shared by neighbouring settings
+    pub states: Vec<PlanState>,
+}
+
+impl Default for FieldPlan {
+    fn default() -> Self {
+        Self {
+            min_excitation: 0.0,
+            max_excitation: 0.0,
+            overlap: 0.2,
+            states: vec![],
+        }
+    }
+}
+
+/// One field setting of a plan with the beam time it needs.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct PlannedSetting {
+    pub field: f64,                 // T, central field
+    pub nmr_field: f64,             // T
+    pub min_excitation: f64,        // MeV, at the high-rigidity end of the detector
+    pub max_excitation: f64,        // MeV, at the low-rigidity end of the detector
+    pub weakest_state: Option<f64>, // MeV, state the time is based on
+    pub cross_section: f64,         // units.cross_section
+    pub run_time: SPSRunTime,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct FieldPlanResult {
+    pub settings: Vec<PlannedSetting>,
+    pub total: SPSRunTime,
+}
+
+impl SPSRunTimeSettings {
+    /// Field settings covering the plan's excitation-energy range, from the lowest Ex at the
+    /// high-rigidity end of the detector upwards. Each setting needs the desired counts in the
+    /// weakest plan state on its part of the focal plane, or at the SPS cross section if none
+    /// falls there. `None` without an ejectile or while the range is empty.
+    pub fn plan_field_settings(&self) -> Result<Option<FieldPlanResult>, EstimateError> {
+        let plan = &self.field_plan;
+        if plan.max_excitation <= plan.min_excitation {
+            return Ok(None);
+        }
+        let Some(reaction) = self.reaction()? else {
+            return Ok(None);
+        };
+        let beam_energy = require_positive("Beam energy", self.reaction.beam_energy)?;
+        let angle = require_in_range("Spectrograph angle", self.reaction.angle, 0.0, 180.0)?;
+        require_non_negative("Minimum excitation energy", plan.min_excitation)?;
+        require_in_range("Overlap", plan.overlap, 0.0, 0.9)?;
+        for state in &plan.states {
+            require_non_negative("Excitation energy", state.excitation)?;
+            require_positive("Cross section", state.cross_section)?;
+        }
+        let spectrograph = &self.spectrograph;
+        require_positive("Spectrograph radius", spectrograph.radius)?;
+        require_positive("Dispersion", spectrograph.dispersion)?;
+        require_positive("Detector length", spectrograph.detector_length)?;
+        require_positive("NMR calibration", spectrograph.field_calibration)?;
+        if reaction.ejectile.z == 0 {
+            return Err(KinematicsError::NeutralEjectile.into());
+        }
+
+        let rigidity = |excitation: f64| {
+            reaction
+                .solve(beam_energy, angle, excitation)
+                .map(|state| magnetic_rigidity(state.ejectile_momentum, reaction.ejectile.z))
+                .ok_or(KinematicsError::NotReachable(excitation))
+        };
+        let excitation = |rigidity: f64| {
+            let momentum = rigidity * MEV_PER_TESLA_METER * reaction.ejectile.z as f64;
+            reaction
+                .excitation_for(beam_energy, angle, momentum)
+                .unwrap_or(f64::INFINITY)
+        };
+        let (min_radius, max_radius) = spectrograph.radius_range();
+        let min_radius = require_positive("Smallest radius on the detector", min_radius / 100.0)?;
+        let max_radius = max_radius / 100.0;
+        let lowest_rigidity = rigidity(plan.max_excitation)?;
+
+        // Each setting starts where the previous one leaves off, less the overlap
+        let step = (min_radius + plan.overlap * (max_radius - min_radius)) / max_radius;
+        let mut field = rigidity(plan.min_excitation)? / max_radius;
+        let mut settings = vec![];
+        loop {
+            if settings.len() == MAX_FIELD_SETTINGS {
+                return Err(EstimateError::OutOfRange {
+                    parameter: "Field settings",
+                    value: (settings.len() + 1) as f64,
+                    min: 1.0,
+                    max: MAX_FIELD_SETTINGS as f64,
+                });
+            }
+            let min_excitation = excitation(field * max_radius);
+            let max_excitation = excitation(field * min_radius);
+            let weakest = plan
+                .states
+                .iter()
+                .filter(|state| (min_excitation..=max_excitation).contains(&state.excitation))
+                .min_by(|a, b| a.cross_section.total_cmp(&b.cross_section));
+
+            let mut setting = self.clone();
+            if let Some(state) = weakest {
+                setting.reaction.center_excitation = state.excitation; // for a CM cross section
+                setting.cross_section = state.cross_section;
+                setting.cross_section_source = CrossSectionSource::Manual;
+            }
+            settings.push(PlannedSetting {
+                field,
+                nmr_field: spectrograph.nmr_field(field),
+                min_excitation,
+                max_excitation,
+                weakest_state: weakest.map(|state| state.excitation),
+                cross_section: setting.cross_section_value()?,
+                run_time: setting.calculate_beam_time()?,
+            });
+
+            if field * min_radius <= lowest_rigidity {
+                break;
+            }
+            field *= step;
+        }
+
+        let total = settings
+            .iter()
+            .map(|setting| setting.run_time.seconds)
+            .sum();
+        Ok(Some(FieldPlanResult {
+            settings,
+            total: SPSRunTime::from_seconds(total),
+        }))
+    }
+}
+
+/// What every angle of an angular-distribution campaign has to reach.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum CampaignGoal {
+    /// The desired counts of the SPS settings.
+    #[default]
+    Counts,
+    /// A relative statistical uncertainty 1/√N.
+    Uncertainty,
+}
+
+impl CampaignGoal {
+    pub const ALL: [CampaignGoal; 2] = [CampaignGoal::Counts, CampaignGoal::Uncertainty];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CampaignGoal::Counts => "Counts",
+            CampaignGoal::Uncertainty => "Statistical Uncertainty",
+        }
+    }
+}
+
+/// Spectrograph angle of the campaign with the cross section expected there.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CampaignAngle {
+    pub angle: f64,         // deg, laboratory
+    pub cross_section: f64, // units.cross_section and cross_section_frame, manual or σ_sp
+}
+
+/// Angles of an angular distribution measured with the same beam and target.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct AngularCampaign {
+    pub angles: Vec<CampaignAngle>,
+    pub goal: CampaignGoal,
+    pub uncertainty: f64, // %, statistical, for CampaignGoal::Uncertainty
+    pub overhead: f64,    // hours per change of the spectrograph angle
+}
+
+impl Default for AngularCampaign {
+    fn default() -> Self {
+        Self {
+            angles: vec![],
+            goal: CampaignGoal::Counts,
+            uncertainty: 5.0,
+            overhead: 0.5,
+        }
+    }
+}
+
+/// Beam time of one angle of the campaign.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct CampaignAngleTime {
+    pub angle: f64,         // deg
+    pub cross_section: f64, // units.cross_section
+    pub counts: f64,
+    pub run_time: SPSRunTime,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct CampaignResult {
+    pub angles: Vec<CampaignAngleTime>,
+    pub overhead: SPSRunTime,
+    pub total: SPSRunTime,
+}
+
+impl SPSRunTimeSettings {
+    /// Counts every angle of the campaign needs.
+    pub fn campaign_counts(&self) -> Result<f64, EstimateError> {
+        match self.campaign.goal {
+            CampaignGoal::Counts => Ok(self.desired_counts as f64),
+            CampaignGoal::Uncertainty => {
+                let uncertainty =
+                    require_positive("Statistical uncertainty", self.campaign.uncertainty)?;
+                Ok((100.0 / uncertainty).powi(2).ceil())
+            }
+        }
+    }
+
+    /// Beam time at every angle of the campaign, plus the overhead of moving the spectrograph
+    /// between consecutive angles. The angles take their cross sections from the chosen model,
+    /// or the entered ones for a manual cross section; a scaled source scales the entered ones
+    /// as σ_sp. `None` without angles.
+    pub fn plan_campaign(&self) -> Result<Option<CampaignResult>, EstimateError> {
+        let campaign = &self.campaign;
+        if campaign.angles.is_empty() {
+            return Ok(None);
+        }
+        require_non_negative("Angle change overhead", campaign.overhead)?;
+        let counts = self.campaign_counts()?;
+
+        let angles = campaign
+            .angles
+            .iter()
+            .map(|angle| {
+                require_in_range("Spectrograph angle", angle.angle, 0.0, 180.0)?;
+                let mut setting = self.clone();
+                setting.reaction.angle = angle.angle; // for a CM or model cross section
+                match setting.cross_section_source {
+                    CrossSectionSource::Manual => setting.cross_section = angle.cross_section,
+                    CrossSectionSource::Scaled => {
+                        setting.scaling.single_particle = angle.cross_section;
+                    }
+                    _ => {}
+                }
+                setting.desired_counts = counts as i64;
+                Ok(CampaignAngleTime {
+                    angle: angle.angle,
+                    cross_section: setting.cross_section_value()?,
+                    counts,
+                    run_time: setting.calculate_beam_time()?,
+                })
+            })
+            .collect::<Result<Vec<_>, EstimateError>>()?;
+
+        let overhead =
+            SPSRunTime::from_seconds((angles.len() - 1) as f64 * campaign.overhead * 3600.0);
+        let beam_time: f64 = angles.iter().map(|angle| angle.run_time.seconds).sum();
+        Ok(Some(CampaignResult {
+            angles,
+            overhead,
+            total: SPSRunTime::from_seconds(beam_time + overhead.seconds),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sps::{SPSSolution, SPSUnknown};
+    use crate::test_util::assert_close;
+
+    /// 206Pb(d,p)207Pb at 16 MeV and 20 degrees.
+    fn pb206_dp() -> SPSRunTimeSettings {
+        let mut settings = SPSRunTimeSettings::default();
+        settings.target = "206Pb".to_string();
+        settings.reaction.ejectile = "p".to_string();
+        settings
+    }
+
+    #[test]
+    fn field_settings_tile_the_range_with_the_requested_overlap() {
+        let mut settings = pb206_dp();
+        settings.field_plan = FieldPlan {
+            min_excitation: 0.0,
+            max_excitation: 14.0,
+            overlap: 0.2,
+            states: vec![PlanState {
+                excitation: 12.0,
+                cross_section: 10.0,
+            }],
+        };
+        let plan = settings.plan_field_settings().unwrap().unwrap();
+        assert!(plan.settings.len() > 1);
+
+        let reaction = settings.reaction().unwrap().unwrap();
+        let rigidity = |excitation: f64| {
+            let state = reaction.solve(16.0, 20.0, excitation).unwrap();
+            magnetic_rigidity(state.ejectile_momentum, 1)
+        };
+        let spectrograph = &settings.spectrograph;
+        let edge = spectrograph.detector_length / 2.0;
+        let first = &plan.settings[0];
+        assert_close(
+            spectrograph.position(rigidity(0.0), first.field),
+            edge,
+            1e-9,
+        );
+        assert!(plan.settings.last().unwrap().max_excitation >= 14.0);
+
+        for setting in &plan.settings {
+            assert_eq!(setting.nmr_field, spectrograph.nmr_field(setting.field));
+            if setting.max_excitation.is_finite() {
+                let low = spectrograph.position(rigidity(setting.max_excitation), setting.field);
+                assert_close(low, -edge, 1e-6);
+            }
+        }
+        for pair in plan.settings.windows(2) {
+            // The next setting's high-rigidity end lies 20 % of the detector into this one
+            let shared = pair[1].field * spectrograph.radius_range().1 / 100.0;
+            let position = spectrograph.position(shared, pair[0].field);
+            assert_close(position, -edge + 0.2 * spectrograph.detector_length, 1e-9);
+            assert!(pair[1].min_excitation < pair[0].max_excitation);
+        }
+
+        let mut total = 0.0;
+        for setting in &plan.settings {
+            let mut single = settings.clone();
+            if setting.weakest_state.is_some() {
+                assert_eq!(setting.weakest_state, Some(12.0));
+                assert!((setting.min_excitation..=setting.max_excitation).contains(&12.0));
+                single.reaction.center_excitation = 12.0;
+                single.cross_section = 10.0;
+            }
+            let time = single.calculate_beam_time().unwrap();
+            assert_eq!(setting.run_time, time);
+            total += time.seconds;
+        }
+        assert!(plan.settings.iter().any(|s| s.weakest_state.is_some()));
+        assert_close(plan.total.seconds, total, 1e-12);
+    }
+
+    #[test]
+    fn campaign_time_is_the_sum_of_the_angles_and_the_overhead() {
+        let mut settings = pb206_dp();
+        settings.campaign = AngularCampaign {
+            angles: [(10.0, 100.0), (20.0, 40.0), (35.0, 5.0)]
+                .map(|(angle, cross_section)| CampaignAngle {
+                    angle,
+                    cross_section,
+                })
+                .to_vec(),
+            goal: CampaignGoal::Counts,
+            uncertainty: 5.0,
+            overhead: 0.5,
+        };
+        let campaign = settings.plan_campaign().unwrap().unwrap();
+
+        let mut beam_time = 0.0;
+        for (planned, angle) in campaign.angles.iter().zip(&settings.campaign.angles) {
+            let mut single = settings.clone();
+            single.reaction.angle = angle.angle;
+            single.cross_section = angle.cross_section;
+            let SPSSolution::RunTime(time) = single.solve_for(SPSUnknown::RunTime).unwrap() else {
+                panic!("not a run time");
+            };
+            assert_eq!(planned.run_time, time);
+            beam_time += time.seconds;
+        }
+        assert_eq!(campaign.overhead.seconds, 2.0 * 0.5 * 3600.0);
+        assert_close(campaign.total.seconds, beam_time + 3600.0, 1e-12);
+
+        // 5 % needs 400 counts at every angle
+        settings.campaign.goal = CampaignGoal::Uncertainty;
+        let uncertain = settings.plan_campaign().unwrap().unwrap();
+        for (planned, counted) in uncertain.angles.iter().zip(&campaign.angles) {
+            assert_eq!(planned.counts, 400.0);
+            let ratio = 400.0 / settings.desired_counts as f64;
+            assert_close(
+                planned.run_time.seconds,
+                ratio * counted.run_time.seconds,
+                1e-12,
+            );
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use super::nuclide;
+use super::scenario::Scenario;
+use std::path::Path;
+
+/// Version written to new project files. Bump it whenever the layout changes in a way
+/// `#[serde(default)]` cannot absorb and teach `Project::from_toml_str` to upgrade older files.
+pub const PROJECT_FORMAT_VERSION: i64 = 3;
+
+/// Which estimator panels are shown.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ViewSettings {
+    pub show_sps: bool,
+    pub show_cebra: bool,
+    pub show_icespice: bool,
+    pub show_comparison: bool,
+}
+
+impl Default for ViewSettings {
+    fn default() -> Self {
+        Self {
+            show_sps: true,
+            show_cebra: true,
+            show_icespice: true,
+            show_comparison: false,
+        }
+    }
+}
+
+/// A complete experiment plan, saved as a human-readable TOML file.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Project {
+    pub format_version: i64,
+    pub view: ViewSettings,
+    pub active_scenario: usize,
+    pub scenarios: Vec<Scenario>,
+    /// Guesses made while upgrading an older file, for the user to check.
+    #[serde(skip)]
+    pub migration_notes: Vec<String>,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self {
+            format_version: PROJECT_FORMAT_VERSION,
+            view: ViewSettings::default(),
+            active_scenario: 0,
+            scenarios: vec![Scenario::default()],
+            migration_notes: vec![],
+        }
+    }
+}
+
+/// Beam mass number for settings saved before it existed: that of the most abundant isotope of
+/// `z_beam`, so p → 1 and α → 4. Returns it with a note asking to check the guess.
+pub(crate) fn guess_beam_mass_number(z_beam: i64, scenario: &str) -> (i64, String) {
+    let nuclide = i32::try_from(z_beam).ok().and_then(nuclide::most_abundant);
+    let a_beam = nuclide.map_or(2 * z_beam, |n| n.a as i64);
+    let beam = nuclide.map_or_else(|| format!("A = {a_beam}"), |n| n.name());
+    (
+        a_beam,
+        format!(
+            "{scenario}: the beam mass number was not saved, assumed {beam} for Z = {z_beam}; \
+             re-enter it for e.g. a d or 3He beam"
+        ),
+    )
+}
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    NewerFormat(i64),
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectError::Io(e) => write!(f, "{e}"),
+            ProjectError::Parse(e) => write!(f, "invalid project file: {e}"),
+            ProjectError::Serialize(e) => write!(f, "could not write project: {e}"),
+            ProjectError::NewerFormat(version) => write!(
+                f,
+                "project format version {version} is newer than this program supports ({PROJECT_FORMAT_VERSION})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl From<std::io::Error> for ProjectError {
+    fn from(e: std::io::Error) -> Self {
+        ProjectError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ProjectError {
+    fn from(e: toml::de::Error) -> Self {
+        ProjectError::Parse(e)
+    }
+}
+
+impl From<toml::ser::Error> for ProjectError {
+    fn from(e: toml::ser::Error) -> Self {
+        ProjectError::Serialize(e)
+    }
+}
+
+/// Version 1 held a single set of settings in top-level `sps`, `icespice` and `cebra`
+/// tables; version 2 keeps a list of named scenarios.
+fn upgrade_v1(table: &mut toml::Table) {
+    let mut scenario = toml::Table::new();
+    scenario.insert("name".to_string(), "Scenario 1".into());
+    for instrument in ["sps", "icespice", "cebra"] {
+        if let Some(settings) = table.remove(instrument) {
+            scenario.insert(instrument.to_string(), settings);
+        }
+    }
+    table.insert(
+        "scenarios".to_string(),
+        toml::Value::Array(vec![toml::Value::Table(scenario)]),
+    );
+}
+
+/// Version 2 divided the beam current by `z_beam`; version 3 divides by the separate charge
+/// state `q_beam`, so older files get `q_beam = z_beam` to keep their estimates. The mass
+/// number is guessed from Z, with a note in `notes`.
+fn upgrade_v2(table: &mut toml::Table, notes: &mut Vec<String>) {
+    let Some(toml::Value::Array(scenarios)) = table.get_mut("scenarios") else {
+        return;
+    };
+    for scenario in scenarios {
+        let name = scenario
+            .get("name")
+            .and_then(toml::Value::as_str)
+            .unwrap_or("Scenario")
+            .to_string();
+        let Some(toml::Value::Table(sps)) = scenario.get_mut("sps") else {
+            continue;
+        };
+        let z_beam = sps
+            .get("z_beam")
+            .and_then(toml::Value::as_integer)
+            .unwrap_or(1);
+        sps.entry("q_beam").or_insert(z_beam.into());
+        if !sps.contains_key("a_beam") {
+            let (a_beam, note) = guess_beam_mass_number(z_beam, &name);
+            sps.insert("a_beam".to_string(), a_beam.into());
+            notes.push(note);
+        }
+    }
+}
+
+impl Project {
+    pub fn from_toml_str(contents: &str) -> Result<Self, ProjectError> {
+        let mut table: toml::Table = contents.parse()?;
+        // Files without a version predate the field and share the first layout.
+        let version = table
+            .get("format_version")
+            .and_then(toml::Value::as_integer)
+            .unwrap_or(1);
+        if version > PROJECT_FORMAT_VERSION {
+            return Err(ProjectError::NewerFormat(version));
+        }
+        if version < 2 {
+            upgrade_v1(&mut table);
+        }
+        let mut notes = vec![];
+        if version < 3 {
+            upgrade_v2(&mut table, &mut notes);
+        }
+
+        let mut project: Project = table.try_into()?;
+        project.format_version = PROJECT_FORMAT_VERSION;
+        project.migration_notes = notes;
+        if project.scenarios.is_empty() {
+            project.scenarios.push(Scenario::default());
+        }
+        project.active_scenario = project.active_scenario.min(project.scenarios.len() - 1);
+        Ok(project)
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, ProjectError> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ProjectError> {
+        Self::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ProjectError> {
+        std::fs::write(path, self.to_toml_string()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_projects_read_back_unchanged() {
+        let mut project = Project::default();
+        project.view.show_cebra = false;
+        project.scenarios[0].sps.cross_section = 25.0;
+        project.scenarios.push(Scenario::new("Elastic"));
+        let read = Project::from_toml_str(&project.to_toml_string().unwrap()).unwrap();
+        assert_eq!(read.view, project.view);
+        assert_eq!(read.scenarios.len(), 2);
+        assert_eq!(read.scenarios[0].sps.cross_section, 25.0);
+        assert_eq!(read.scenarios[1].name, "Elastic");
+        assert_eq!(read.format_version, PROJECT_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn unversioned_files_get_one_scenario() {
+        let project = Project::from_toml_str(
+            "active_scenario = 3\n[sps]\ncross_section = 50.0\n[icespice]\nbranching_ratio = 40.0\n",
+        )
+        .unwrap();
+        let [scenario] = project.scenarios.as_slice() else {
+            panic!("{} scenarios", project.scenarios.len());
+        };
+        assert_eq!(scenario.name, "Scenario 1");
+        assert_eq!(scenario.sps.cross_section, 50.0);
+        assert_eq!(scenario.icespice.branching_ratio, 40.0);
+        assert_eq!(project.active_scenario, 0);
+        assert_eq!(project.format_version, PROJECT_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn newer_formats_are_refused() {
+        let newer = format!("format_version = {}\n", PROJECT_FORMAT_VERSION + 1);
+        assert!(matches!(
+            Project::from_toml_str(&newer),
+            Err(ProjectError::NewerFormat(_))
+        ));
+        // Missing fields take their defaults
+        let project = Project::from_toml_str("[view]\nshow_sps = false\n").unwrap();
+        assert!(!project.view.show_sps);
+        assert!(project.view.show_icespice);
+    }
+
+    #[test]
+    fn version_2_beams_get_the_mass_number_of_the_most_abundant_isotope() {
+        let project = Project::from_toml_str(
+            r#"
+            format_version = 2
+
+            [[scenarios]]
+            name = "Protons"
+            [scenarios.sps]
+            z_beam = 1
+
+            [[scenarios]]
+            name = "Alphas"
+            [scenarios.sps]
+            z_beam = 2
+            "#,
+        )
+        .unwrap();
+
+        let beams: Vec<_> = project
+            .scenarios
+            .iter()
+            .map(|s| (s.sps.z_beam, s.sps.a_beam, s.sps.q_beam))
+            .collect();
+        assert_eq!(beams, [(1, 1, 1), (2, 4, 2)]);
+        assert_eq!(project.migration_notes.len(), 2);
+        assert!(project.migration_notes[0].starts_with("Protons: "));
+        assert!(project.migration_notes[0].contains("1H"));
+    }
+
+    #[test]
+    fn saved_mass_numbers_are_kept_without_a_note() {
+        let project = Project::from_toml_str(
+            "format_version = 2\n[[scenarios]]\n[scenarios.sps]\nz_beam = 2\na_beam = 3\n",
+        )
+        .unwrap();
+        assert_eq!(project.scenarios[0].sps.a_beam, 3);
+        assert!(project.migration_notes.is_empty());
+    }
+}
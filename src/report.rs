@@ -0,0 +1,1235 @@
+//! Beam-time proposal reports built from a [`Project`].
+//!
+//! Every number comes from the same calculation methods the panels use. The report is first
+//! assembled as a list of [`Block`]s and then rendered as Markdown, HTML or LaTeX.
+
+use super::dwba::{BOUND_DIFFUSENESS, BOUND_RADIUS};
+use super::kinematics::Frame;
+use super::planner::CampaignGoal;
+use super::project::Project;
+use super::scenario::Scenario;
+use super::sps::{
+    spin_name, CrossSectionSource, SPSSolution, SPSUnknown, TransferType, SHIFT_HOURS,
+};
+use super::units::{ThicknessUnit, Unit};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+    Latex,
+}
+
+impl ReportFormat {
+    pub const ALL: [ReportFormat; 3] = [
+        ReportFormat::Markdown,
+        ReportFormat::Html,
+        ReportFormat::Latex,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "Markdown",
+            ReportFormat::Html => "HTML",
+            ReportFormat::Latex => "LaTeX",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+            ReportFormat::Latex => "tex",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Block {
+    /// One scenario of the project.
+    Section(String),
+    Heading(String),
+    Paragraph(String),
+    /// Plain-text formula together with its LaTeX form.
+    Formula {
+        text: String,
+        latex: String,
+    },
+    Table {
+        header: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+fn row(cells: &[&str]) -> Vec<String> {
+    cells.iter().map(|c| c.to_string()).collect()
+}
+
+/// Unit symbol with typeset exponents, e.g. µg/cm².
+fn unit_symbol(unit: impl Unit) -> String {
+    unit.symbol().replace("^2", "²")
+}
+
+fn sps_blocks(scenario: &Scenario, blocks: &mut Vec<Block>) {
+    let sps = &scenario.sps;
+    blocks.push(Block::Heading("SE-SPS".to_string()));
+
+    let mut inputs = vec![];
+    if sps.unknown != SPSUnknown::CrossSection {
+        let cross_section = match sps.cross_section_source {
+            CrossSectionSource::Manual => sps.cross_section.to_string(),
+            source => sps.cross_section_value().map_or_else(
+                |e| e.to_string(),
+                |value| format!("{value:.4e} ({})", source.name()),
+            ),
+        };
+        inputs.push(row(&[
+            "Cross section",
+            &cross_section,
+            &unit_symbol(sps.units.cross_section),
+        ]));
+    }
+    if sps.cross_section_frame != Frame::Lab {
+        inputs.push(row(&[
+            "Cross section frame",
+            sps.cross_section_frame.name(),
+            "",
+        ]));
+    }
+    if sps.unknown != SPSUnknown::TargetDensity {
+        inputs.push(row(&[
+            "Target density",
+            &sps.target_density.to_string(),
+            &unit_symbol(sps.units.target_density),
+        ]));
+    }
+    if sps.units.target_density == ThicknessUnit::Nanometer {
+        inputs.push(row(&[
+            "Target mass density",
+            &sps.target_mass_density.to_string(),
+            "g/cm³",
+        ]));
+    }
+    if !sps.target.is_empty() {
+        inputs.push(row(&["Target", &sps.target, ""]));
+    }
+    if !sps.reaction_nuclide.is_empty() {
+        inputs.push(row(&["Reaction nuclide", &sps.reaction_nuclide, ""]));
+    }
+    let molar_mass = match sps.formula() {
+        Ok(None) => sps.target_molar_mass.to_string(),
+        _ => sps
+            .molar_mass()
+            .map_or_else(|e| e.to_string(), |m| format!("{m:.4}")),
+    };
+    inputs.push(row(&["Target molar mass", &molar_mass, "g/mol"]));
+    if sps.unknown != SPSUnknown::BeamCurrent {
+        inputs.push(row(&[
+            "Beam current",
+            &sps.beam_current.to_string(),
+            &unit_symbol(sps.units.beam_current),
+        ]));
+    }
+    inputs.push(row(&["Z beam", &sps.z_beam.to_string(), ""]));
+    inputs.push(row(&["A beam", &sps.a_beam.to_string(), ""]));
+    inputs.push(row(&["Beam charge state", &sps.q_beam.to_string(), ""]));
+    inputs.push(row(&[
+        "Slit settings",
+        &sps.slit_settings.to_string(),
+        &unit_symbol(sps.units.slit_settings),
+    ]));
+    if sps.unknown != SPSUnknown::Counts {
+        inputs.push(row(&["Counts", &sps.desired_counts.to_string(), "counts"]));
+    }
+    if sps.unknown != SPSUnknown::RunTime {
+        inputs.push(row(&["Beam time", &sps.beam_time.to_string(), "h"]));
+    }
+    blocks.push(Block::Table {
+        header: row(&["Input", "Value", "Unit"]),
+        rows: inputs,
+    });
+
+    blocks.push(Block::Paragraph(format!(
+        "The {} follows from the counts in the peak of interest, with every input converted to cm²/sr, atoms/cm², sr and particles/s:",
+        sps.unknown.name().to_lowercase()
+    )));
+    blocks.push(Block::Formula {
+        text: "t = N / (dσ/dΩ · n_target · ΔΩ · Φ),  n_target = ν ρ_target N_A / M,  Φ = I / (q e)".to_string(),
+        latex: r"t = \frac{N}{\frac{d\sigma}{d\Omega}\, n_\mathrm{target}\, \Delta\Omega\, \Phi}, \qquad n_\mathrm{target} = \frac{\nu\, \rho_\mathrm{target} N_A}{M}, \qquad \Phi = \frac{I}{q e}".to_string(),
+    });
+
+    blocks.push(Block::Paragraph(
+        "Here ν is the number of reaction nuclei per formula unit of the target (1 when the whole target counts) and q is the charge state of the beam.".to_string(),
+    ));
+    if sps.cross_section_source == CrossSectionSource::Rutherford {
+        match sps.rutherford() {
+            Ok(rutherford) => {
+                blocks.push(Block::Paragraph(format!(
+                    "The cross section is that of Rutherford scattering of {} at {} MeV (Ecm = {:.3} MeV) on {} at {}° in the lab (θcm = {:.2}°):",
+                    sps.beam_species(),
+                    sps.reaction.beam_energy,
+                    rutherford.cm_energy,
+                    sps.target_nuclide().map_or_else(|_| String::new(), |nuclide| nuclide.name()),
+                    sps.reaction.angle,
+                    rutherford.cm_angle
+                )));
+                blocks.push(Block::Formula {
+                    text: "dσ/dΩcm = (Z₁ Z₂ e² / 2pv)² / sin⁴(θcm / 2),  dσ/dΩlab = dΩcm/dΩlab · dσ/dΩcm".to_string(),
+                    latex: r"\frac{d\sigma}{d\Omega_\mathrm{cm}} = \left(\frac{Z_1 Z_2 e^2}{2 p v}\right)^2 \frac{1}{\sin^4(\theta_\mathrm{cm}/2)}, \qquad \frac{d\sigma}{d\Omega_\mathrm{lab}} = \frac{d\Omega_\mathrm{cm}}{d\Omega_\mathrm{lab}} \frac{d\sigma}{d\Omega_\mathrm{cm}}".to_string(),
+                });
+                blocks.push(Block::Table {
+                    header: row(&["Rutherford", "Value"]),
+                    rows: vec![
+                        row(&["dσ/dΩlab", &format!("{:.4} mb/sr", rutherford.lab)]),
+                        row(&["dσ/dΩcm", &format!("{:.4} mb/sr", rutherford.cm)]),
+                        row(&["dΩcm/dΩlab", &format!("{:.4}", rutherford.jacobian)]),
+                        row(&[
+                            "Coulomb barrier (lab)",
+                            &format!("{:.2} MeV", rutherford.barrier),
+                        ]),
+                    ],
+                });
+                if rutherford.above_barrier {
+                    blocks.push(Block::Paragraph(format!(
+                        "Warning: the beam energy is above the Coulomb barrier of {:.2} MeV, so pure Rutherford scattering is unreliable.",
+                        rutherford.barrier
+                    )));
+                }
+            }
+            Err(e) => blocks.push(Block::Paragraph(format!(
+                "Rutherford cross section not available: {e}."
+            ))),
+        }
+    }
+    if sps.cross_section_source == CrossSectionSource::OpticalModel {
+        let optical = sps
+            .elastic_scattering()
+            .and_then(|elastic| Ok((elastic, sps.optical_model()?)));
+        match optical {
+            Ok((elastic, point)) => {
+                blocks.push(Block::Paragraph(format!(
+                    "The cross section is that of elastic scattering of {} at {} MeV on {} in the {} global optical potential, from a Numerov integration of {} partial waves matched to Coulomb functions (k = {:.4} fm⁻¹, η = {:.4}). At {}° in the lab (θcm = {:.2}°) it is {:.4} mb/sr in the lab, {:.4} mb/sr in the CM frame and {:.4} times the Rutherford cross section.",
+                    sps.beam_species(),
+                    elastic.beam_energy,
+                    sps.target_nuclide().map_or_else(|_| String::new(), |nuclide| nuclide.name()),
+                    elastic.potential.name(),
+                    elastic.partial_waves(),
+                    elastic.k,
+                    elastic.eta,
+                    point.angle,
+                    point.cm_angle,
+                    point.lab,
+                    point.cm,
+                    point.ratio
+                )));
+                let parameters = &elastic.parameters;
+                blocks.push(Block::Table {
+                    header: row(&["Potential", "Depth (MeV)", "r (fm)", "a (fm)"]),
+                    rows: [
+                        ("Real volume", parameters.real),
+                        ("Imaginary volume", parameters.volume),
+                        ("Imaginary surface", parameters.surface),
+                        ("Spin-orbit", parameters.spin_orbit),
+                    ]
+                    .iter()
+                    .map(|(name, term)| {
+                        row(&[
+                            name,
+                            &format!("{:.3}", term.depth),
+                            &format!("{:.3}", term.radius),
+                            &format!("{:.3}", term.diffuseness),
+                        ])
+                    })
+                    .chain(std::iter::once(row(&[
+                        "Coulomb",
+                        "",
+                        &format!("{:.3}", parameters.coulomb_radius),
+                        "",
+                    ])))
+                    .collect(),
+                });
+                blocks.push(Block::Table {
+                    header: row(&["θlab (°)", "θcm (°)", "Lab (mb/sr)", "CM (mb/sr)", "σ/σR"]),
+                    rows: elastic
+                        .curve()
+                        .iter()
+                        .map(|point| {
+                            row(&[
+                                &format!("{:.1}", point.angle),
+                                &format!("{:.2}", point.cm_angle),
+                                &format!("{:.4e}", point.lab),
+                                &format!("{:.4e}", point.cm),
+                                &format!("{:.4}", point.ratio),
+                            ])
+                        })
+                        .collect(),
+                });
+            }
+            Err(e) => blocks.push(Block::Paragraph(format!(
+                "Optical-model cross section not available: {e}."
+            ))),
+        }
+    }
+    if sps.cross_section_source == CrossSectionSource::Dwba {
+        let dwba = sps
+            .transfer()
+            .and_then(|transfer| Ok((transfer, sps.dwba()?)));
+        match dwba {
+            Ok((transfer, point)) => {
+                let state = &transfer.state;
+                blocks.push(Block::Paragraph(format!(
+                    "The cross section is a zero-range DWBA estimate of {} at {} MeV to the state at Ex = {} MeV, with the nucleon transferred into {} (l = {}, j = {}/2) with a spectroscopic factor S = {}. The distorted waves of {} partial waves use the {} and {} global potentials without spin-orbit term, and the nucleon is bound by {:.3} MeV in a Woods–Saxon well (r = {} fm, a = {} fm) of depth {:.2} MeV. With D₀² = {:.3e} MeV² fm³:",
+                    transfer.reaction.name(),
+                    transfer.beam_energy,
+                    state.excitation,
+                    state.orbit(),
+                    state.l,
+                    (2.0 * state.j).round(),
+                    state.spectroscopic_factor,
+                    transfer.partial_waves,
+                    transfer.entrance.name(),
+                    transfer.exit.name(),
+                    transfer.bound_state.binding,
+                    BOUND_RADIUS,
+                    BOUND_DIFFUSENESS,
+                    transfer.bound_state.depth,
+                    transfer.d0_squared
+                )));
+                blocks.push(Block::Formula {
+                    text: match transfer.stripping {
+                        true => "dσ/dΩ = (2J_f + 1)/(2J_i + 1) · S · σ_sp,  σ_sp = μa μb / (2πħ²)² · kb/ka · f · Σm |Tm|² / (2l + 1)",
+                        false => "dσ/dΩ = S · σ_sp,  σ_sp = μa μb / (2πħ²)² · kb/ka · f · Σm |Tm|² / (2l + 1)",
+                    }
+                    .to_string(),
+                    latex: format!(
+                        r"\frac{{d\sigma}}{{d\Omega}} = {}S\, \sigma_\mathrm{{sp}}, \qquad \sigma_\mathrm{{sp}} = \frac{{\mu_a \mu_b}}{{(2\pi\hbar^2)^2}} \frac{{k_b}}{{k_a}} \frac{{f}}{{2l + 1}} \sum_m |T_m|^2",
+                        if transfer.stripping { r"\frac{2J_f + 1}{2J_i + 1}\, " } else { "" }
+                    ),
+                });
+                blocks.push(Block::Paragraph(format!(
+                    "Here f = {} is the spin factor of the light particles and J_i = {}, J_f = {} the spins of target and state, giving a statistical factor of {:.4}. At {}° in the lab (θcm = {:.2}°) the state has {:.4} mb/sr in the lab and {:.4} mb/sr in the CM frame:",
+                    transfer.spin_factor,
+                    spin_name(state.initial_spin),
+                    spin_name(state.spin()),
+                    transfer.statistical_factor(),
+                    point.angle,
+                    point.cm_angle,
+                    point.lab,
+                    point.cm
+                )));
+                blocks.push(Block::Table {
+                    header: row(&["θlab (°)", "θcm (°)", "Lab (mb/sr)", "CM (mb/sr)"]),
+                    rows: transfer
+                        .curve()
+                        .iter()
+                        .map(|point| {
+                            row(&[
+                                &format!("{:.1}", point.angle),
+                                &format!("{:.2}", point.cm_angle),
+                                &format!("{:.4e}", point.lab),
+                                &format!("{:.4e}", point.cm),
+                            ])
+                        })
+                        .collect(),
+                });
+            }
+            Err(e) => blocks.push(Block::Paragraph(format!(
+                "DWBA cross section not available: {e}."
+            ))),
+        }
+    }
+    if sps.cross_section_source == CrossSectionSource::Imported {
+        match sps.imported() {
+            Ok(point) => blocks.push(Block::Paragraph(format!(
+                "The cross section is taken from the angular distribution of {} (Ex = {} MeV) in the {} output {}, interpolated logarithmically at θcm = {:.2}° for the spectrograph at {}°: {:.4} mb/sr in the CM frame and {:.4} mb/sr in the lab.",
+                point.state,
+                point.excitation,
+                sps.imported.format.name(),
+                sps.imported.file,
+                point.cm_angle,
+                point.angle,
+                point.cm,
+                point.lab
+            ))),
+            Err(e) => blocks.push(Block::Paragraph(format!(
+                "Imported cross section not available: {e}."
+            ))),
+        }
+    }
+    if sps.cross_section_source == CrossSectionSource::Scaled {
+        let statistical = match sps.scaling.transfer {
+            TransferType::Stripping => format!(
+                "the statistical factor (2J_f + 1)/(2J_i + 1) = {:.4} of stripping from J_i = {} to J_f = {}",
+                sps.scaling.statistical_factor(),
+                spin_name(sps.scaling.initial_spin),
+                spin_name(sps.scaling.final_spin)
+            ),
+            TransferType::Pickup => "no statistical factor in pickup".to_string(),
+        };
+        match sps.scaled_cross_section() {
+            Ok(_) => blocks.push(Block::Paragraph(format!(
+                "The cross section is the reference single-particle cross section scaled by the spectroscopic factor, with {statistical} ({} frame): {}.",
+                sps.cross_section_frame.name(),
+                sps.scaling.explanation(sps.units.cross_section.symbol())
+            ))),
+            Err(e) => blocks.push(Block::Paragraph(format!(
+                "Scaled cross section not available: {e}."
+            ))),
+        }
+    }
+    if !sps.target_assays.is_empty() {
+        blocks.push(Block::Table {
+            header: row(&["Assay", "Abundance (%)"]),
+            rows: sps
+                .target_assays
+                .iter()
+                .flat_map(|assay| {
+                    assay.isotopes.iter().map(|isotope| {
+                        row(&[
+                            &format!("{}{}", isotope.a, assay.element),
+                            &isotope.percent.to_string(),
+                        ])
+                    })
+                })
+                .collect(),
+        });
+    }
+    if let Ok(composition) = sps.target_composition() {
+        if !composition.is_empty() {
+            blocks.push(Block::Table {
+                header: row(&["Target nuclide", "Atoms/cm²", "µg/cm²"]),
+                rows: composition
+                    .iter()
+                    .map(|isotope| {
+                        row(&[
+                            &isotope.nuclide.name(),
+                            &format!("{:.3e}", isotope.atoms_per_cm2),
+                            &format!("{:.3}", isotope.micrograms_per_cm2),
+                        ])
+                    })
+                    .collect(),
+            });
+        }
+    }
+
+    if sps.unknown != SPSUnknown::BeamCurrent {
+        if let Ok(intensity) = sps.beam_intensity() {
+            let intensity: Vec<String> = intensity
+                .iter()
+                .map(|current| current.to_string())
+                .collect();
+            blocks.push(Block::Paragraph(format!(
+                "Beam {}: {}.",
+                sps.beam_species(),
+                intensity.join(" = ")
+            )));
+        }
+    }
+
+    match sps.solve() {
+        Ok(SPSSolution::RunTime(time)) => blocks.push(Block::Table {
+            header: row(&["Estimated time", "Value"]),
+            rows: vec![
+                row(&["Seconds", &format!("{:.0} s", time.seconds)]),
+                row(&["Hours", &format!("{:.2} h", time.hours)]),
+                row(&["Days", &format!("{:.2} d", time.days)]),
+                row(&[
+                    &format!("Shifts ({SHIFT_HOURS} h)"),
+                    &format!("{:.2}", time.shifts),
+                ]),
+            ],
+        }),
+        Ok(solution) => blocks.push(Block::Table {
+            header: row(&["Result", "Value"]),
+            rows: vec![row(&[
+                solution.unknown().name(),
+                &solution.to_string().replace("^2", "²"),
+            ])],
+        }),
+        Err(e) => blocks.push(Block::Paragraph(format!(
+            "{} not available: {e}.",
+            sps.unknown.name()
+        ))),
+    }
+
+    if let Ok(Some(reaction)) = sps.reaction() {
+        let kinematics = sps.kinematics().unwrap_or_default();
+        blocks.push(Block::Paragraph(format!(
+            "Kinematics of {} at {} MeV with the spectrograph at {}° (Q = {:.3} MeV):",
+            reaction.name(),
+            sps.reaction.beam_energy,
+            sps.reaction.angle,
+            reaction.q_value()
+        )));
+        blocks.push(Block::Table {
+            header: row(&[
+                "Ex (MeV)",
+                "Ejectile energy (MeV)",
+                "Momentum (MeV/c)",
+                "dE/dθ (keV/°)",
+                "Recoil energy (MeV)",
+                "Recoil angle (°)",
+            ]),
+            rows: sps
+                .reaction
+                .excitation_energies
+                .iter()
+                .zip(&kinematics)
+                .map(|(excitation, state)| match state {
+                    Some(state) => row(&[
+                        &format!("{:.3}", state.excitation),
+                        &format!("{:.3}", state.ejectile_energy),
+                        &format!("{:.3}", state.ejectile_momentum),
+                        &format!("{:.2}", state.kinematic_factor),
+                        &format!("{:.3}", state.recoil_energy),
+                        &format!("{:.2}", state.recoil_angle),
+                    ]),
+                    None => row(&[&format!("{excitation:.3}"), "not reachable", "", "", "", ""]),
+                })
+                .collect(),
+        });
+
+        match sps.frame_kinematics() {
+            Ok(Some(state)) => blocks.push(Block::Paragraph(format!(
+                "At Ex = {} MeV the ejectile leaves at θcm = {:.2}° in the centre-of-mass frame, and dσ/dΩlab = {:.4} · dσ/dΩcm.{}",
+                state.excitation,
+                state.cm_angle,
+                state.jacobian,
+                match sps.cross_section_frame {
+                    Frame::Lab => "",
+                    Frame::CenterOfMass => " The cross sections are given in the centre-of-mass frame and converted with this factor.",
+                }
+            ))),
+            Ok(None) => {}
+            Err(e) => blocks.push(Block::Paragraph(format!(
+                "Lab/CM conversion not available: {e}."
+            ))),
+        }
+
+        match sps.field_setting() {
+            Ok(Some(setting)) => {
+                let focal_plane = sps.focal_plane().unwrap_or_default();
+                blocks.push(Block::Paragraph(format!(
+                    "A central field of {:.4} T (NMR {:.4} T) puts Ex = {} MeV (Bρ = {:.4} T·m) at the centre of the {} cm focal-plane detector:",
+                    setting.field,
+                    setting.nmr_field,
+                    setting.excitation,
+                    setting.rigidity,
+                    sps.spectrograph.detector_length
+                )));
+                blocks.push(Block::Table {
+                    header: row(&["Ex (MeV)", "Bρ (T·m)", "Position (cm)", "On detector"]),
+                    rows: focal_plane
+                        .iter()
+                        .flatten()
+                        .map(|state| {
+                            row(&[
+                                &format!("{:.3}", state.excitation),
+                                &format!("{:.4}", state.rigidity),
+                                &format!("{:.1}", state.position),
+                                if state.on_detector { "yes" } else { "no" },
+                            ])
+                        })
+                        .collect(),
+                });
+            }
+            Ok(None) => {}
+            Err(e) => blocks.push(Block::Paragraph(format!(
+                "Field setting not available: {e}."
+            ))),
+        }
+
+        match sps.target_energy_loss() {
+            Ok(loss) => {
+                blocks.push(Block::Paragraph(format!(
+                    "In the {:.3} mg/cm² target the beam has a mean energy of {:.4} MeV and deposits {:.3} mW. Energy loss and Bohr straggling (FWHM) of beam and ejectile:",
+                    loss.areal_mass, loss.mean_beam_energy, loss.power
+                )));
+                blocks.push(Block::Table {
+                    header: row(&[
+                        "Ion",
+                        "Energy in (MeV)",
+                        "Energy out (MeV)",
+                        "Energy loss (keV)",
+                        "Straggling (keV)",
+                        "Stopping (keV/(mg/cm²))",
+                        "Source",
+                    ]),
+                    rows: std::iter::once(&loss.beam)
+                        .chain(&loss.ejectile)
+                        .map(|passage| {
+                            row(&[
+                                &passage.ion,
+                                &format!("{:.4}", passage.energy_in),
+                                &format!("{:.4}", passage.energy_out),
+                                &format!("{:.1}", passage.loss),
+                                &format!("{:.1}", passage.straggling),
+                                &format!("{:.2}", passage.stopping),
+                                &passage.source,
+                            ])
+                        })
+                        .collect(),
+                });
+            }
+            Err(e) => blocks.push(Block::Paragraph(format!(
+                "Energy loss in the target not available: {e}."
+            ))),
+        }
+
+        match sps.ex_resolution() {
+            Ok(Some(resolution)) => {
+                blocks.push(Block::Paragraph(format!(
+                    "The expected excitation-energy resolution at Ex = {} MeV is {:.1} keV FWHM for the {:.3} mg/cm² target, {} keV beam energy spread, {:.2}° horizontal acceptance and {} mm position resolution ({:.2} keV/mm). The terms add in quadrature:",
+                    resolution.excitation,
+                    resolution.fwhm,
+                    resolution.areal_mass,
+                    sps.resolution.beam_spread,
+                    resolution.acceptance,
+                    sps.resolution.position_resolution,
+                    resolution.energy_dispersion
+                )));
+                blocks.push(Block::Table {
+                    header: row(&["Term", "Ex FWHM (keV)", "Share (%)"]),
+                    rows: resolution
+                        .contributions
+                        .iter()
+                        .map(|contribution| {
+                            row(&[
+                                contribution.term.name(),
+                                &format!("{:.1}", contribution.fwhm),
+                                &format!("{:.1}", contribution.share * 100.0),
+                            ])
+                        })
+                        .collect(),
+                });
+            }
+            Ok(None) => {}
+            Err(e) => blocks.push(Block::Paragraph(format!(
+                "Ex resolution not available: {e}."
+            ))),
+        }
+    }
+
+    match sps.plan_field_settings() {
+        Ok(Some(plan)) => {
+            let unit = unit_symbol(sps.units.cross_section);
+            blocks.push(Block::Paragraph(format!(
+                "Covering Ex = {}–{} MeV with {} % of the focal plane shared by neighbouring settings takes {} field settings. Each needs the desired counts in the weakest state on the detector:",
+                sps.field_plan.min_excitation,
+                sps.field_plan.max_excitation,
+                sps.field_plan.overlap * 100.0,
+                plan.settings.len()
+            )));
+            let mut rows: Vec<Vec<String>> = plan
+                .settings
+                .iter()
+                .map(|setting| {
+                    let weakest = match setting.weakest_state {
+                        Some(excitation) => format!("{excitation}"),
+                        None => "SPS input".to_string(),
+                    };
+                    row(&[
+                        &format!("{:.4}", setting.field),
+                        &format!("{:.4}", setting.nmr_field),
+                        &format!(
+                            "{:.2}–{:.2}",
+                            setting.min_excitation, setting.max_excitation
+                        ),
+                        &weakest,
+                        &format!("{} {unit}", setting.cross_section),
+                        &format!("{:.2}", setting.run_time.hours),
+                    ])
+                })
+                .collect();
+            rows.push(row(&[
+                "Total",
+                "",
+                "",
+                "",
+                "",
+                &format!("{:.2}", plan.total.hours),
+            ]));
+            blocks.push(Block::Table {
+                header: row(&[
+                    "Field (T)",
+                    "NMR (T)",
+                    "Ex on detector (MeV)",
+                    "Weakest state (MeV)",
+                    "Cross section",
+                    "Beam time (h)",
+                ]),
+                rows,
+            });
+        }
+        Ok(None) => {}
+        Err(e) => blocks.push(Block::Paragraph(format!(
+            "Field-setting plan not available: {e}."
+        ))),
+    }
+
+    match sps.plan_campaign() {
+        Ok(Some(campaign)) => {
+            let unit = unit_symbol(sps.units.cross_section);
+            let counts = sps.campaign_counts().unwrap_or_default();
+            let goal = match sps.campaign.goal {
+                CampaignGoal::Counts => format!("{counts} counts"),
+                CampaignGoal::Uncertainty => format!(
+                    "a statistical uncertainty of {} % (N = (100 / {})² = {counts} counts)",
+                    sps.campaign.uncertainty, sps.campaign.uncertainty
+                ),
+            };
+            blocks.push(Block::Paragraph(format!(
+                "The angular distribution needs {goal} at each of {} angles, with {} h to change the spectrograph angle:",
+                campaign.angles.len(),
+                sps.campaign.overhead
+            )));
+            let mut rows: Vec<Vec<String>> = campaign
+                .angles
+                .iter()
+                .map(|angle| {
+                    let cross_section = match sps.cross_section_source {
+                        CrossSectionSource::Manual => angle.cross_section.to_string(),
+                        _ => format!("{:.4e}", angle.cross_section),
+                    };
+                    row(&[
+                        &format!("{}", angle.angle),
+                        &format!("{cross_section} {unit}"),
+                        &format!("{:.2}", angle.run_time.hours),
+                    ])
+                })
+                .collect();
+            rows.push(row(&[
+                "Angle changes",
+                "",
+                &format!("{:.2}", campaign.overhead.hours),
+            ]));
+            rows.push(row(&["Total", "", &format!("{:.2}", campaign.total.hours)]));
+            blocks.push(Block::Table {
+                header: row(&["Angle (°)", "Cross section", "Beam time (h)"]),
+                rows,
+            });
+        }
+        Ok(None) => {}
+        Err(e) => blocks.push(Block::Paragraph(format!(
+            "Angular distribution plan not available: {e}."
+        ))),
+    }
+
+    if let Ok(yields) = sps.isotope_yields() {
+        if !yields.is_empty() {
+            blocks.push(Block::Paragraph(format!(
+                "Counts from the other nuclides of the target in the same run, assuming the cross section of {}:",
+                sps.reaction_nuclide.trim()
+            )));
+            blocks.push(Block::Table {
+                header: row(&["Target nuclide", "Counts"]),
+                rows: yields
+                    .iter()
+                    .map(|isotope| {
+                        row(&[&isotope.nuclide.name(), &format!("{:.0}", isotope.counts)])
+                    })
+                    .collect(),
+            });
+        }
+    }
+}
+
+fn cebra_blocks(scenario: &Scenario, blocks: &mut Vec<Block>) {
+    let cebra = &scenario.cebra;
+    blocks.push(Block::Heading("CeBrA".to_string()));
+    blocks.push(Block::Table {
+        header: row(&["Input", "Value", "Unit"]),
+        rows: vec![
+            row(&[
+                "Particle counts",
+                &cebra.n_particle_counts.to_string(),
+                "counts",
+            ]),
+            row(&["γ-ray energy", &cebra.decay.energy.to_string(), "keV"]),
+            row(&[
+                "Absolute intensity",
+                &cebra.decay.absolute_intensity.to_string(),
+                "%",
+            ]),
+        ],
+    });
+    blocks.push(Block::Paragraph(
+        "Each detector's efficiency and expected counts are:".to_string(),
+    ));
+    blocks.push(Block::Formula {
+        text: "Efficiency = a * exp(-energy / b) + c * exp(-energy / d)".to_string(),
+        latex: r"\varepsilon(E_\gamma) = a\, e^{-E_\gamma / b} + c\, e^{-E_\gamma / d}".to_string(),
+    });
+    blocks.push(Block::Formula {
+        text: "Estimated counts = Particle counts * Absolute intensity [%] /100 * Efficiency [%] /100"
+            .to_string(),
+        latex: r"N_\gamma = N_\mathrm{particle} \cdot \frac{I_\gamma}{100} \cdot \frac{\varepsilon}{100}".to_string(),
+    });
+
+    match cebra.calculate_counts() {
+        Ok(estimate) => {
+            let mut rows: Vec<Vec<String>> = cebra
+                .detectors
+                .iter()
+                .zip(&estimate.detectors)
+                .map(|(detector, result)| {
+                    let e = &detector.efficiency;
+                    vec![
+                        detector.name.clone(),
+                        e.a.to_string(),
+                        e.b.to_string(),
+                        e.c.to_string(),
+                        e.d.to_string(),
+                        format!("{:.2} %", result.efficiency),
+                        format!("{:.0}", result.counts),
+                    ]
+                })
+                .collect();
+            rows.push(vec![
+                "Total".to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                format!("{:.2} %", estimate.total_efficiency),
+                format!("{:.0}", estimate.total_counts),
+            ]);
+            blocks.push(Block::Table {
+                header: row(&["Detector", "a", "b", "c", "d", "ε(γ)", "Counts"]),
+                rows,
+            });
+        }
+        Err(e) => blocks.push(Block::Paragraph(format!("Counts not available: {e}."))),
+    }
+}
+
+fn icespice_blocks(scenario: &Scenario, blocks: &mut Vec<Block>) {
+    let icespice = &scenario.icespice;
+    blocks.push(Block::Heading("ICESPICE".to_string()));
+    blocks.push(Block::Table {
+        header: row(&["Input", "Value", "Unit"]),
+        rows: vec![
+            row(&[
+                "Particle counts",
+                &icespice.n_particle_counts.to_string(),
+                "counts",
+            ]),
+            row(&[
+                "Transmission probability",
+                &icespice.transmission_prob.to_string(),
+                "%",
+            ]),
+            row(&[
+                "Detector efficiency",
+                &icespice.detector_efficiency.to_string(),
+                "%",
+            ]),
+            row(&[
+                "Branching ratio",
+                &icespice.branching_ratio.to_string(),
+                "%",
+            ]),
+            row(&[
+                "Conversion coefficient α",
+                &icespice.conversion_coefficient.to_string(),
+                "",
+            ]),
+        ],
+    });
+    blocks.push(Block::Formula {
+        text: "Particle Counts * (Branching Ratio [%] / 100) * α * (Transmission Probability [%] / 100) * (Detector Efficiency [%] / 100)".to_string(),
+        latex: r"N_e = N_\mathrm{particle} \cdot \frac{BR}{100} \cdot \alpha \cdot \frac{T}{100} \cdot \frac{\varepsilon}{100}".to_string(),
+    });
+
+    match icespice.calculate_conversion_electrons() {
+        Ok(estimate) => blocks.push(Block::Table {
+            header: row(&["Estimate", "Value"]),
+            rows: vec![
+                row(&["γ rays", &format!("{:.0}", estimate.gamma_rays)]),
+                row(&[
+                    "Conversion electrons",
+                    &format!("{:.0}", estimate.conversion_electrons),
+                ]),
+                row(&[
+                    "Detected conversion electrons",
+                    &format!("{:.0}", estimate.detected_conversion_electrons),
+                ]),
+            ],
+        }),
+        Err(e) => blocks.push(Block::Paragraph(format!(
+            "Conversion electrons not available: {e}."
+        ))),
+    }
+}
+
+fn blocks(project: &Project) -> Vec<Block> {
+    let mut blocks = vec![];
+    for scenario in &project.scenarios {
+        blocks.push(Block::Section(scenario.name.clone()));
+        if project.view.show_sps {
+            sps_blocks(scenario, &mut blocks);
+        }
+        if project.view.show_cebra {
+            cebra_blocks(scenario, &mut blocks);
+        }
+        if project.view.show_icespice {
+            icespice_blocks(scenario, &mut blocks);
+        }
+    }
+    blocks
+}
+
+const TITLE: &str = "Beam Time Estimate";
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut out = format!("# {TITLE}\n");
+    for block in blocks {
+        out.push('\n');
+        match block {
+            Block::Section(text) => out += &format!("## {text}\n"),
+            Block::Heading(text) => out += &format!("### {text}\n"),
+            Block::Paragraph(text) => out += &format!("{text}\n"),
+            Block::Formula { text, .. } => out += &format!("```text\n{text}\n```\n"),
+            Block::Table { header, rows } => {
+                out += &format!("| {} |\n", header.join(" | "));
+                out += &format!("|{}\n", " --- |".repeat(header.len()));
+                for row in rows {
+                    out += &format!("| {} |\n", row.join(" | "));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn render_html(blocks: &[Block]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{TITLE}</title>\n</head>\n<body>\n<h1>{TITLE}</h1>\n"
+    );
+    for block in blocks {
+        match block {
+            Block::Section(text) => out += &format!("<h2>{}</h2>\n", escape_html(text)),
+            Block::Heading(text) => out += &format!("<h3>{}</h3>\n", escape_html(text)),
+            Block::Paragraph(text) => out += &format!("<p>{}</p>\n", escape_html(text)),
+            Block::Formula { text, .. } => {
+                out += &format!("<pre><code>{}</code></pre>\n", escape_html(text));
+            }
+            Block::Table { header, rows } => {
+                out += "<table border=\"1\">\n<tr>";
+                for cell in header {
+                    out += &format!("<th>{}</th>", escape_html(cell));
+                }
+                out += "</tr>\n";
+                for row in rows {
+                    out += "<tr>";
+                    for cell in row {
+                        out += &format!("<td>{}</td>", escape_html(cell));
+                    }
+                    out += "</tr>\n";
+                }
+                out += "</table>\n";
+            }
+        }
+    }
+    out += "</body>\n</html>\n";
+    out
+}
+
+/// Math-mode LaTeX of a symbol that has no text-mode equivalent.
+fn latex_math_symbol(c: char) -> Option<&'static str> {
+    Some(match c {
+        'µ' | 'μ' => r"\mu",
+        'α' => r"\alpha",
+        'γ' => r"\gamma",
+        'ε' => r"\varepsilon",
+        'η' => r"\eta",
+        'ν' => r"\nu",
+        'θ' => r"\theta",
+        'π' => r"\pi",
+        'ρ' => r"\rho",
+        'σ' => r"\sigma",
+        'φ' => r"\phi",
+        'χ' => r"\chi",
+        'Δ' => r"\Delta",
+        'Σ' => r"\Sigma",
+        'Φ' => r"\Phi",
+        'Ω' => r"\Omega",
+        'ħ' => r"\hbar",
+        '°' => r"^\circ",
+        '·' => r"\cdot",
+        '±' => r"\pm",
+        '−' => "-",
+        '→' => r"\to",
+        '≈' => r"\approx",
+        '≥' => r"\geq",
+        '√' => r"\surd",
+        '½' => r"\frac{1}{2}",
+        _ => return None,
+    })
+}
+
+/// Sub- or superscript mark and character of a Unicode sub- or superscript.
+fn latex_script(c: char) -> Option<(char, char)> {
+    const SUPERSCRIPTS: &str = "⁰¹²³⁴⁵⁶⁷⁸⁹";
+    const SUBSCRIPTS: &str = "₀₁₂₃₄₅₆₇₈₉";
+    let digit = |digits: &str| {
+        let index = digits.chars().position(|d| d == c)?;
+        char::from_digit(index as u32, 10)
+    };
+    match c {
+        '⁺' => Some(('^', '+')),
+        '⁻' => Some(('^', '-')),
+        _ => digit(SUPERSCRIPTS)
+            .map(|d| ('^', d))
+            .or_else(|| digit(SUBSCRIPTS).map(|d| ('_', d))),
+    }
+}
+
+/// Escapes `text` for LaTeX. Greek letters, symbols and scripts are set in math mode, with
+/// neighbouring ones sharing one `$…$` and a subscript taking the letter before it, so that
+/// D₀² becomes `$D_0^2$`.
+fn escape_latex(text: &str) -> String {
+    fn script(math: &mut String, mark: char, script: &str) {
+        match script.chars().count() {
+            1 => *math += &format!("{mark}{script}"),
+            _ => *math += &format!("{mark}{{{script}}}"),
+        }
+    }
+
+    let mut out = String::new();
+    let mut math = String::new();
+    let mut scripts: Option<(char, String)> = None;
+    for c in text.chars() {
+        if let Some((mark, character)) = latex_script(c) {
+            match &mut scripts {
+                Some((open, characters)) if *open == mark => characters.push(character),
+                _ => {
+                    if let Some((open, characters)) = scripts.take() {
+                        script(&mut math, open, &characters);
+                    }
+                    if mark == '_' && math.is_empty() {
+                        if let Some(letter) = out.chars().last().filter(char::is_ascii_alphabetic) {
+                            out.pop();
+                            math.push(letter);
+                        }
+                    }
+                    scripts = Some((mark, character.to_string()));
+                }
+            }
+            continue;
+        }
+        if let Some((open, characters)) = scripts.take() {
+            script(&mut math, open, &characters);
+        }
+        if let Some(symbol) = latex_math_symbol(c) {
+            math += symbol;
+            continue;
+        }
+        if !math.is_empty() {
+            out += &format!("${math}$");
+            math.clear();
+        }
+        match c {
+            '\\' => out += r"\textbackslash{}",
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '~' => out += r"\textasciitilde{}",
+            '^' => out += r"\textasciicircum{}",
+            '–' => out += "--",
+            'Å' => out += r"\AA{}",
+            _ => out.push(c),
+        }
+    }
+    if let Some((open, characters)) = scripts {
+        script(&mut math, open, &characters);
+    }
+    if !math.is_empty() {
+        out += &format!("${math}$");
+    }
+    out
+}
+
+fn render_latex(blocks: &[Block]) -> String {
+    let mut out = format!(
+        "\\documentclass{{article}}\n\\usepackage[utf8]{{inputenc}}\n\\usepackage{{amsmath}}\n\\begin{{document}}\n\\section*{{{TITLE}}}\n"
+    );
+    for block in blocks {
+        out.push('\n');
+        match block {
+            Block::Section(text) => out += &format!("\\subsection*{{{}}}\n", escape_latex(text)),
+            Block::Heading(text) => {
+                out += &format!("\\subsubsection*{{{}}}\n", escape_latex(text));
+            }
+            Block::Paragraph(text) => out += &format!("{}\n", escape_latex(text)),
+            Block::Formula { latex, .. } => out += &format!("\\[\n{latex}\n\\]\n"),
+            Block::Table { header, rows } => {
+                out += &format!(
+                    "\\begin{{tabular}}{{{}}}\n\\hline\n",
+                    "l".repeat(header.len())
+                );
+                let line = |cells: &[String]| {
+                    cells
+                        .iter()
+                        .map(|c| escape_latex(c))
+                        .collect::<Vec<_>>()
+                        .join(" & ")
+                        + " \\\\\n"
+                };
+                out += &line(header);
+                out += "\\hline\n";
+                for row in rows {
+                    out += &line(row);
+                }
+                out += "\\hline\n\\end{tabular}\n";
+            }
+        }
+    }
+    out += "\n\\end{document}\n";
+    out
+}
+
+/// Builds the complete report for the project in the requested format.
+pub fn generate(project: &Project, format: ReportFormat) -> String {
+    let blocks = blocks(project);
+    match format {
+        ReportFormat::Markdown => render_markdown(&blocks),
+        ReportFormat::Html => render_html(&blocks),
+        ReportFormat::Latex => render_latex(&blocks),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cebra::{Detector, Efficiency};
+    use crate::dwba::TransferState;
+
+    /// Default scenario with one CeBrA detector of ε = 10 % · e^(−E/1000 keV).
+    fn project() -> Project {
+        let mut project = Project::default();
+        project.scenarios[0].cebra.detectors.push(Detector {
+            name: "Det 0".to_string(),
+            efficiency: Efficiency::new(10.0, 1000.0, 0.0, 1.0),
+        });
+        project
+    }
+
+    /// 40Ca(d,p)41Ca to the 1f7/2 ground state at 16 MeV with the cross section from `source`.
+    fn ca40_dp(source: CrossSectionSource) -> Scenario {
+        let mut scenario = Scenario::new(source.name());
+        scenario.sps.target = "40Ca".to_string();
+        scenario.sps.reaction.ejectile = "p".to_string();
+        scenario.sps.transfer = TransferState::from_orbit(0.0, "1f7/2").unwrap();
+        scenario.sps.cross_section_source = source;
+        scenario
+    }
+
+    #[test]
+    fn markdown_report_has_the_inputs_and_results_of_the_panels() {
+        let report = generate(&project(), ReportFormat::Markdown);
+        for line in [
+            "# Beam Time Estimate",
+            "## Scenario 1",
+            "| Cross section | 100 | µb/sr |",
+            "| Target density | 100 | µg/cm² |",
+            "| Beam current | 20 | nA |",
+            "| Slit settings | 4.62 | msr |",
+            "| Counts | 1000 | counts |",
+            "t = N / (dσ/dΩ · n_target · ΔΩ · Φ),  n_target = ν ρ_target N_A / M,  Φ = I / (q e)",
+            "Beam 2H1+: 20.000 nA = 20.000 pnA = 1.250e11 particles/s.",
+            // 1000 / (100e-30 cm²/sr · 100e-6 g/cm² / 240 g/mol · N_A · 4.62e-3 sr · 20 nA / e)
+            "| Seconds | 69000 s |",
+            "| Hours | 19.17 h |",
+            "| Days | 0.80 d |",
+            "| Shifts (8 h) | 2.40 |",
+            // 10 % · e^−2 of 10000 counts
+            "| Det 0 | 10 | 1000 | 0 | 1 | 1.35 % | 135 |",
+            "| Total |  |  |  |  | 1.35 % | 135 |",
+            // 10000 · 1 · 1.29 % · 30.7 %
+            "| Detected conversion electrons | 40 |",
+        ] {
+            assert!(report.lines().any(|l| l == line), "missing {line:?}");
+        }
+    }
+
+    #[test]
+    fn html_and_latex_reports_render_the_same_blocks() {
+        let mut project = project();
+        project.scenarios.push(ca40_dp(CrossSectionSource::Manual));
+        let html = generate(&project, ReportFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.trim_end().ends_with("</html>"));
+        assert!(html.contains("<tr><td>Seconds</td><td>69000 s</td></tr>"));
+        assert!(html.contains("<tr><td>Det 0</td><td>10</td><td>1000</td>"));
+        assert_eq!(
+            html.matches("<table").count(),
+            html.matches("</table>").count()
+        );
+
+        let latex = generate(&project, ReportFormat::Latex);
+        assert!(latex.starts_with("\\documentclass{article}"));
+        assert!(latex.trim_end().ends_with("\\end{document}"));
+        assert!(latex.contains("Cross section & 100 & $\\mu$b/sr \\\\\n"));
+        assert!(latex.contains("Seconds & 69000 s \\\\\n"));
+        assert!(latex.contains("Absolute intensity & 100 & \\% \\\\\n"));
+        // the energy loss of beam and ejectile in the 100 µg/cm² 40Ca target
+        assert!(latex.contains(
+            "Ion & Energy in (MeV) & Energy out (MeV) & Energy loss (keV) & Straggling (keV) & Stopping (keV/(mg/cm$^2$)) & Source \\\\\n"
+        ));
+        assert!(latex.contains(
+            "2H & 16.0000 & 15.9959 & 4.1 & 6.6 & 40.77 & Built-in parametrization \\\\\n"
+        ));
+        assert_eq!(
+            latex.matches("\\begin{tabular}").count(),
+            latex.matches("\\end{tabular}").count()
+        );
+    }
+
+    #[test]
+    fn latex_sets_symbols_and_scripts_in_math_mode() {
+        assert_eq!(escape_latex("ΔΩ = 4.62 msr"), r"$\Delta\Omega$ = 4.62 msr");
+        assert_eq!(escape_latex("Z₁ Z₂ e²"), "$Z_1$ $Z_2$ e$^2$");
+        assert_eq!(
+            escape_latex("10⁻¹² s, Woods–Saxon"),
+            "10$^{-12}$ s, Woods--Saxon"
+        );
+        assert_eq!(escape_latex("50% of a_b"), r"50\% of a\_b");
+    }
+
+    #[test]
+    fn dwba_reports_describe_the_transfer_and_its_normalisation() {
+        let mut project = project();
+        project.scenarios = vec![ca40_dp(CrossSectionSource::Dwba)];
+        let report = generate(&project, ReportFormat::Markdown);
+        for text in [
+            "zero-range DWBA estimate of 40Ca(d,p)41Ca at 16 MeV to the state at Ex = 0 MeV",
+            "transferred into 1f7/2 (l = 3, j = 7/2)",
+            "With D₀² = 1.550e4 MeV² fm³:",
+            "dσ/dΩ = (2J_f + 1)/(2J_i + 1) · S · σ_sp,",
+            "J_i = 0, J_f = 7/2 the spins of target and state, giving a statistical factor of 8.0000",
+        ] {
+            assert!(report.contains(text), "missing {text:?}");
+        }
+
+        let latex = generate(&project, ReportFormat::Latex);
+        assert!(latex.contains("With $D_0^2$ = 1.550e4 MeV$^2$ fm$^3$:"));
+        assert!(latex.contains(r"\frac{2J_f + 1}{2J_i + 1}\, S\, \sigma_\mathrm{sp}"));
+    }
+
+    #[test]
+    fn latex_reports_are_ascii_for_every_cross_section_source() {
+        let mut project = project();
+        project.scenarios = CrossSectionSource::ALL.into_iter().map(ca40_dp).collect();
+        let latex = generate(&project, ReportFormat::Latex);
+        let non_ascii: String = latex.chars().filter(|c| !c.is_ascii()).collect();
+        assert!(non_ascii.is_empty(), "{non_ascii:?}");
+    }
+}
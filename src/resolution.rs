@@ -0,0 +1,309 @@
+//! Excitation-energy resolution of the SE-SPS for the state at the centre of the focal plane.
+//!
+//! Every term is the spread of the Ex reconstructed from the ejectile momentum for the nominal
+//! beam energy and spectrograph angle, found by solving the kinematics at both edges of the
+//! spread:
+//! - the energy spread of the beam;
+//! - the energy lost in the target, which depends on the depth of the reaction (full width
+//!   of the flat depth distribution), with the stopping powers of [`super::stopping`];
+//! - the Bohr energy straggling of beam and ejectile through half the target;
+//! - the change of the ejectile energy across the horizontal slit acceptance, less the part
+//!   corrected by the focal-plane position or in the analysis;
+//! - the position resolution of the focal-plane detector.
+//!
+//! The terms are added in quadrature. Energies are FWHM in keV.
+
+use super::error::{require_in_range, require_non_negative, require_positive, EstimateError};
+use super::kinematics::KinematicsError;
+use super::sps::SPSRunTimeSettings;
+use super::stopping::bohr_straggling;
+
+const STEP: f64 = 1e-3; // MeV, for the derivatives of Ex
+
+/// Beam, target and detector properties the resolution depends on, besides the SPS settings.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ResolutionSettings {
+    pub beam_spread: f64,          // keV, FWHM of the beam energy
+    pub target_angle: f64,         // deg, of the target normal from the beam toward the SPS
+    pub kinematic_correction: f64, // fraction of the kinematic broadening corrected
+    pub position_resolution: f64,  // mm, FWHM on the focal plane
+}
+
+impl Default for ResolutionSettings {
+    fn default() -> Self {
+        Self {
+            beam_spread: 5.0,
+            target_angle: 0.0,
+            kinematic_correction: 0.0,
+            position_resolution: 1.0,
+        }
+    }
+}
+
+/// Source of a spread of the reconstructed excitation energy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+pub enum ResolutionTerm {
+    BeamSpread,
+    EnergyLoss,
+    BeamStraggling,
+    EjectileStraggling,
+    Kinematic,
+    FocalPlane,
+}
+
+impl ResolutionTerm {
+    pub const ALL: [ResolutionTerm; 6] = [
+        ResolutionTerm::BeamSpread,
+        ResolutionTerm::EnergyLoss,
+        ResolutionTerm::BeamStraggling,
+        ResolutionTerm::EjectileStraggling,
+        ResolutionTerm::Kinematic,
+        ResolutionTerm::FocalPlane,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResolutionTerm::BeamSpread => "Beam Energy Spread",
+            ResolutionTerm::EnergyLoss => "Target Energy Loss",
+            ResolutionTerm::BeamStraggling => "Beam Straggling",
+            ResolutionTerm::EjectileStraggling => "Ejectile Straggling",
+            ResolutionTerm::Kinematic => "Kinematic Broadening",
+            ResolutionTerm::FocalPlane => "Focal-Plane Resolution",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct ResolutionContribution {
+    pub term: ResolutionTerm,
+    pub fwhm: f64,  // keV, of Ex
+    pub share: f64, // fraction of the squared total
+}
+
+/// Expected Ex resolution of the centred state.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct ExResolution {
+    pub excitation: f64,        // MeV
+    pub areal_mass: f64,        // mg/cm^2, of the target
+    pub acceptance: f64,        // deg, full horizontal acceptance of the slits
+    pub energy_dispersion: f64, // keV of Ex per mm of focal plane
+    pub contributions: Vec<ResolutionContribution>,
+    pub fwhm: f64, // keV
+}
+
+impl SPSRunTimeSettings {
+    /// Ex resolution of the state at the centre of the focal plane, term by term. `None`
+    /// without an ejectile.
+    pub fn ex_resolution(&self) -> Result<Option<ExResolution>, EstimateError> {
+        let Some(reaction) = self.reaction()? else {
+            return Ok(None);
+        };
+        let settings = &self.resolution;
+        let beam_energy = require_positive("Beam energy", self.reaction.beam_energy)?;
+        let angle = require_in_range("Spectrograph angle", self.reaction.angle, 0.0, 180.0)?;
+        let excitation =
+            require_non_negative("Centre excitation energy", self.reaction.center_excitation)?;
+        let beam_spread = require_non_negative("Beam energy spread", settings.beam_spread)?;
+        let correction = require_in_range(
+            "Kinematic correction",
+            settings.kinematic_correction,
+            0.0,
+            1.0,
+        )?;
+        let position_resolution =
+            require_non_negative("Position resolution", settings.position_resolution)?;
+        let radius = require_positive("Spectrograph radius", self.spectrograph.radius)?;
+        let dispersion = require_positive("Dispersion", self.spectrograph.dispersion)?;
+        let solid_angle = self
+            .units
+            .slit_settings
+            .to_base(require_non_negative("Slit settings", self.slit_settings)?);
+        if reaction.ejectile.z == 0 {
+            return Err(KinematicsError::NeutralEjectile.into());
+        }
+
+        let not_reachable = || EstimateError::from(KinematicsError::NotReachable(excitation));
+        let state = reaction
+            .solve(beam_energy, angle, excitation)
+            .ok_or_else(not_reachable)?;
+        let mass = reaction.ejectile.mass;
+        let momentum_of = |energy: f64| (energy * (energy + 2.0 * mass)).max(0.0).sqrt();
+        let momentum = state.ejectile_momentum;
+
+        // Ex in keV between the reconstructions for two (beam energy, angle, momentum)
+        let width = |low: (f64, f64, f64), high: (f64, f64, f64)| {
+            let ex = |(beam, angle, momentum)| {
+                reaction
+                    .excitation_for(beam, angle, momentum)
+                    .ok_or_else(not_reachable)
+            };
+            Ok::<_, EstimateError>((ex(high)? - ex(low)?).abs() * 1e3)
+        };
+        let per_beam_energy = width(
+            (beam_energy - STEP, angle, momentum),
+            (beam_energy + STEP, angle, momentum),
+        )? / (2e3 * STEP);
+        let per_ejectile_energy = width(
+            (
+                beam_energy,
+                angle,
+                momentum_of(state.ejectile_energy - STEP),
+            ),
+            (
+                beam_energy,
+                angle,
+                momentum_of(state.ejectile_energy + STEP),
+            ),
+        )? / (2e3 * STEP);
+
+        let (areal_mass, electrons) = self.target_electrons()?;
+        let entrance = self.entrance_path()?;
+        let (exit, transmission) = self.exit_path()?;
+        let beam_stopping = self.stopping(reaction.beam, self.stopping.beam.as_ref())?;
+        let ejectile_stopping =
+            self.stopping(reaction.ejectile, self.stopping.ejectile.as_ref())?;
+        // Ejectile momentum after the reaction at `depth`, as a fraction of the thickness; in
+        // reflection the ejectile goes back out through the front
+        let ejectile_at = |depth: f64| {
+            let beam = beam_stopping.energy_after(beam_energy, areal_mass * depth * entrance)?;
+            let state = reaction
+                .solve(beam, angle, excitation)
+                .ok_or_else(not_reachable)?;
+            let remaining = match transmission {
+                true => 1.0 - depth,
+                false => depth,
+            };
+            Ok::<_, EstimateError>(momentum_of(
+                ejectile_stopping
+                    .energy_after(state.ejectile_energy, areal_mass * remaining * exit)?,
+            ))
+        };
+        let straggling = |z: i32, path: f64| bohr_straggling(z, electrons / 2.0 * path);
+
+        let acceptance = solid_angle.sqrt().to_degrees(); // square opening
+        let per_millimeter = momentum / 10.0 / dispersion / radius; // MeV/c
+        let energy_dispersion = width(
+            (beam_energy, angle, momentum - per_millimeter / 2.0),
+            (beam_energy, angle, momentum + per_millimeter / 2.0),
+        )?;
+        let terms = [
+            per_beam_energy * beam_spread,
+            width(
+                (beam_energy, angle, ejectile_at(0.0)?),
+                (beam_energy, angle, ejectile_at(1.0)?),
+            )?,
+            per_beam_energy * straggling(reaction.beam.z, entrance),
+            per_ejectile_energy * straggling(reaction.ejectile.z, exit),
+            (1.0 - correction)
+                * width(
+                    (beam_energy, angle - acceptance / 2.0, momentum),
+                    (beam_energy, angle + acceptance / 2.0, momentum),
+                )?,
+            energy_dispersion * position_resolution,
+        ];
+        let total = terms.iter().map(|fwhm| fwhm * fwhm).sum::<f64>();
+        Ok(Some(ExResolution {
+            excitation,
+            areal_mass,
+            acceptance,
+            energy_dispersion,
+            contributions: ResolutionTerm::ALL
+                .into_iter()
+                .zip(terms)
+                .map(|(term, fwhm)| ResolutionContribution {
+                    term,
+                    fwhm,
+                    share: match total > 0.0 {
+                        true => fwhm * fwhm / total,
+                        false => 0.0,
+                    },
+                })
+                .collect(),
+            fwhm: total.sqrt(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 206Pb(d,p)207Pb at 16 MeV and 20 degrees.
+    fn pb206_dp() -> SPSRunTimeSettings {
+        let mut settings = SPSRunTimeSettings::default();
+        settings.target = "206Pb".to_string();
+        settings.reaction.ejectile = "p".to_string();
+        settings
+    }
+
+    fn fwhm_of(resolution: &ExResolution, term: ResolutionTerm) -> f64 {
+        resolution
+            .contributions
+            .iter()
+            .find(|contribution| contribution.term == term)
+            .unwrap()
+            .fwhm
+    }
+
+    #[test]
+    fn terms_add_in_quadrature() {
+        let mut settings = pb206_dp();
+        settings.resolution.beam_spread = 10.0;
+        let resolution = settings.ex_resolution().unwrap().unwrap();
+        assert_eq!(resolution.contributions.len(), ResolutionTerm::ALL.len());
+        let squares: f64 = resolution
+            .contributions
+            .iter()
+            .map(|contribution| contribution.fwhm.powi(2))
+            .sum();
+        assert!((resolution.fwhm - squares.sqrt()).abs() < 1e-9 * resolution.fwhm);
+        let shares: f64 = resolution
+            .contributions
+            .iter()
+            .map(|contribution| contribution.share)
+            .sum();
+        assert!((shares - 1.0).abs() < 1e-12);
+        for contribution in &resolution.contributions {
+            assert!(contribution.fwhm > 0.0, "{:?}", contribution.term);
+        }
+
+        // Terms scale with their own input only
+        settings.resolution.beam_spread = 20.0;
+        settings.resolution.kinematic_correction = 1.0;
+        let changed = settings.ex_resolution().unwrap().unwrap();
+        let beam = ResolutionTerm::BeamSpread;
+        assert!((fwhm_of(&changed, beam) - 2.0 * fwhm_of(&resolution, beam)).abs() < 1e-9);
+        assert_eq!(fwhm_of(&changed, ResolutionTerm::Kinematic), 0.0);
+        let focal_plane = ResolutionTerm::FocalPlane;
+        assert_eq!(
+            fwhm_of(&changed, focal_plane),
+            fwhm_of(&resolution, focal_plane)
+        );
+    }
+
+    #[test]
+    fn kinematic_broadening_follows_the_q_value_equation() {
+        let settings = pb206_dp();
+        let resolution = settings.ex_resolution().unwrap().unwrap();
+        let reaction = settings.reaction().unwrap().unwrap();
+        let state = reaction.solve(16.0, 20.0, 0.0).unwrap();
+
+        // At a fixed ejectile energy, the non-relativistic Q-value equation gives
+        // dEx/dθ = 2/M_B √(m_a m_b E_a E_b) sin θ, about 5.8 keV over the 3.9° acceptance
+        let (m_a, m_b, m_recoil) = (
+            reaction.beam.mass,
+            reaction.ejectile.mass,
+            reaction.recoil.mass,
+        );
+        let slope = 2.0 / m_recoil
+            * (m_a * m_b * 16.0 * state.ejectile_energy).sqrt()
+            * 20f64.to_radians().sin();
+        let expected = slope * resolution.acceptance.to_radians() * 1e3;
+        let kinematic = fwhm_of(&resolution, ResolutionTerm::Kinematic);
+        assert!(
+            (kinematic - expected).abs() < 1e-2 * expected,
+            "{kinematic} keV != {expected} keV"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Rutherford (pure Coulomb) elastic scattering, as used for target-thickness normalization
+//! runs with the SE-SPS.
+//!
+//! The centre-of-mass cross section is dσ/dΩ = (Z₁ Z₂ e² / 2 p v)² / sin⁴(θ_cm / 2) with the
+//! relative momentum p and velocity v of the centre-of-mass frame, i.e. the classical
+//! (Z₁ Z₂ e² / 4 E_cm)² at low energies; the angle and the lab cross section come from the
+//! relativistic elastic kinematics. Cross sections are in mb/sr, energies in MeV and angles
+//! in degrees.
+
+use super::kinematics::{KinematicsError, Particle, Reaction};
+
+pub const COULOMB_CONSTANT: f64 = 1.43996448; // MeV fm, e^2 / (4 π ε0)
+pub const MB_PER_FM2: f64 = 10.0;
+/// Radius parameter of the touching-spheres Coulomb barrier, R = r₀ (A₁^⅓ + A₂^⅓).
+pub const BARRIER_RADIUS: f64 = 1.44; // fm
+
+/// Rutherford cross section in mb/sr at `cm_angle` degrees for a product `pv` in MeV of the
+/// relative momentum and velocity, 2 E_cm at low energies.
+pub fn rutherford_cm(z1: i32, z2: i32, pv: f64, cm_angle: f64) -> f64 {
+    let half_distance = (z1 * z2) as f64 * COULOMB_CONSTANT / (2.0 * pv); // fm
+    half_distance.powi(2) / (cm_angle.to_radians() / 2.0).sin().powi(4) * MB_PER_FM2
+}
+
+/// Height in MeV (centre of mass) of the Coulomb barrier between two nuclei.
+pub fn coulomb_barrier(beam: &Particle, target: &Particle) -> f64 {
+    let radius =
+        BARRIER_RADIUS * ((beam.a as f64).powf(1.0 / 3.0) + (target.a as f64).powf(1.0 / 3.0));
+    (beam.z * target.z) as f64 * COULOMB_CONSTANT / radius
+}
+
+/// Elastic scattering of the beam on the target at one spectrograph angle.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct RutherfordCrossSection {
+    pub cm_energy: f64,      // MeV
+    pub cm_angle: f64,       // deg
+    pub cm: f64,             // mb/sr, dσ/dΩ_cm
+    pub lab: f64,            // mb/sr, dσ/dΩ_lab
+    pub jacobian: f64,       // dΩ_cm/dΩ_lab
+    pub barrier: f64,        // MeV, beam energy in the lab at the top of the Coulomb barrier
+    pub above_barrier: bool, // pure Rutherford scattering is unreliable
+}
+
+/// Rutherford scattering of `beam` at `beam_energy` MeV on `target` at rest, observed at
+/// `angle` degrees in the lab. Fails where the elastically scattered beam cannot reach the
+/// angle, i.e. beyond the largest angle in inverse kinematics.
+pub fn rutherford(
+    beam: Particle,
+    target: Particle,
+    beam_energy: f64,
+    angle: f64,
+) -> Result<RutherfordCrossSection, KinematicsError> {
+    let state = Reaction::new(beam, target, beam, target)?
+        .solve(beam_energy, angle, 0.0)
+        .ok_or(KinematicsError::NotReachable(0.0))?;
+    let (m1, m2) = (beam.mass, target.mass);
+    let s = (m1 + m2).powi(2) + 2.0 * m2 * beam_energy;
+    let momentum = ((s - (m1 + m2).powi(2)) * (s - (m1 - m2).powi(2))).sqrt() / (2.0 * s.sqrt());
+    let pv = momentum.powi(2) * (1.0 / momentum.hypot(m1) + 1.0 / momentum.hypot(m2));
+    let cm = rutherford_cm(beam.z, target.z, pv, state.cm_angle);
+    let lab_per_cm = (m1 + m2) / m2;
+    let barrier = coulomb_barrier(&beam, &target) * lab_per_cm;
+    Ok(RutherfordCrossSection {
+        cm_energy: s.sqrt() - m1 - m2,
+        cm_angle: state.cm_angle,
+        cm,
+        lab: cm * state.jacobian,
+        jacobian: state.jacobian,
+        barrier,
+        above_barrier: beam_energy > barrier,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::assert_close;
+
+    #[test]
+    fn cross_section_is_the_classical_formula_at_low_energy() {
+        // 5 MeV α on 197Au at θcm = 90°: (Z₁ Z₂ e² / 4 E_cm)² / sin⁴ 45° ≈ 5.39 b/sr
+        let cm_energy = 5.0 * 197.0 / 201.0;
+        let distance = 2.0 * 79.0 * COULOMB_CONSTANT / (4.0 * cm_energy);
+        let classical = distance.powi(2) / 0.25 * MB_PER_FM2;
+        assert_close(
+            rutherford_cm(2, 79, 2.0 * cm_energy, 90.0),
+            classical,
+            1e-12,
+        );
+        assert_close(classical, 5389.0, 1e-3);
+
+        let alpha = Particle::find(2, 4).unwrap();
+        let gold = Particle::find(79, 197).unwrap();
+        let scattering = rutherford(alpha, gold, 5.0, 90.0).unwrap();
+        let half = (scattering.cm_angle.to_radians() / 2.0).sin();
+        let classical = distance.powi(2) / half.powi(4) * MB_PER_FM2;
+        assert_close(scattering.cm, classical, 2e-3);
+        assert_close(scattering.lab, scattering.cm * scattering.jacobian, 1e-12);
+        assert!(!scattering.above_barrier);
+    }
+
+    #[test]
+    fn barrier_is_that_of_touching_spheres() {
+        let proton = Particle::find(1, 1).unwrap();
+        let lead = Particle::find(82, 208).unwrap();
+        let radius = BARRIER_RADIUS * (1.0 + 208f64.powf(1.0 / 3.0));
+        assert_close(
+            coulomb_barrier(&proton, &lead),
+            82.0 * COULOMB_CONSTANT / radius,
+            1e-12,
+        );
+        assert!(rutherford(proton, lead, 30.0, 30.0).unwrap().above_barrier);
+    }
+}
@@ -0,0 +1,338 @@
+use super::cebra::CeBrARunTimeSettings;
+use super::icespice::ICESPICERunTimeSettings;
+use super::sps::{CrossSectionSource, SPSRunTimeSettings, SPSSolution};
+use super::units::Unit;
+
+/// One named set of estimator settings, e.g. "(d,p) at 10 nA".
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Scenario {
+    pub name: String,
+    pub sps: SPSRunTimeSettings,
+    pub icespice: ICESPICERunTimeSettings,
+    pub cebra: CeBrARunTimeSettings,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::new("Scenario 1")
+    }
+}
+
+impl Scenario {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            sps: SPSRunTimeSettings::default(),
+            icespice: ICESPICERunTimeSettings::default(),
+            cebra: CeBrARunTimeSettings::default(),
+        }
+    }
+}
+
+/// Returns `base` or, if a scenario already uses it, `base (2)`, `base (3)`, ...
+pub fn unique_name(scenarios: &[Scenario], base: &str) -> String {
+    let taken = |name: &str| scenarios.iter().any(|s| s.name == name);
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{base} ({n})"))
+        .find(|name| !taken(name))
+        .expect("an unused name exists")
+}
+
+/// One quantity of the comparison view with a value for every scenario.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComparisonRow {
+    pub section: &'static str,
+    pub label: &'static str,
+    pub values: Vec<String>,
+    /// Whether the scenarios disagree on this quantity.
+    pub differs: bool,
+}
+
+impl ComparisonRow {
+    /// Whether the value in `column` differs from the first scenario.
+    pub fn differs_from_first(&self, column: usize) -> bool {
+        self.values.get(column) != self.values.first()
+    }
+}
+
+fn comparison_row(
+    scenarios: &[Scenario],
+    section: &'static str,
+    label: &'static str,
+    value: impl Fn(&Scenario) -> String,
+) -> ComparisonRow {
+    let values: Vec<String> = scenarios.iter().map(value).collect();
+    let differs = values.iter().any(|v| Some(v) != values.first());
+    ComparisonRow {
+        section,
+        label,
+        values,
+        differs,
+    }
+}
+
+/// Inputs and results of every scenario, one row per quantity.
+pub fn compare(scenarios: &[Scenario]) -> Vec<ComparisonRow> {
+    let row = |section, label, value: &dyn Fn(&Scenario) -> String| {
+        comparison_row(scenarios, section, label, value)
+    };
+
+    vec![
+        row(
+            "SE-SPS",
+            "Cross Section",
+            &|s| match s.sps.cross_section_source {
+                CrossSectionSource::Manual => format!(
+                    "{} {}",
+                    s.sps.cross_section,
+                    s.sps.units.cross_section.symbol()
+                ),
+                source => s.sps.cross_section_value().map_or_else(
+                    |e| e.to_string(),
+                    |value| {
+                        format!(
+                            "{value:.4e} {} ({})",
+                            s.sps.units.cross_section.symbol(),
+                            source.name()
+                        )
+                    },
+                ),
+            },
+        ),
+        row("SE-SPS", "Cross Section Frame", &|s| {
+            s.sps.cross_section_frame.name().to_string()
+        }),
+        row("SE-SPS", "Target Density", &|s| {
+            format!(
+                "{} {}",
+                s.sps.target_density,
+                s.sps.units.target_density.symbol()
+            )
+        }),
+        row("SE-SPS", "Target", &|s| s.sps.target.clone()),
+        row("SE-SPS", "Reaction Nuclide", &|s| {
+            s.sps.reaction_nuclide.clone()
+        }),
+        row("SE-SPS", "Assays", &|s| {
+            let isotopes: Vec<String> = s
+                .sps
+                .target_assays
+                .iter()
+                .flat_map(|assay| {
+                    assay.isotopes.iter().map(|isotope| {
+                        format!("{}{} {} %", isotope.a, assay.element, isotope.percent)
+                    })
+                })
+                .collect();
+            isotopes.join(", ")
+        }),
+        row("SE-SPS", "Target Molar Mass", &|s| match s.sps.formula() {
+            Ok(None) => format!("{} g/mol", s.sps.target_molar_mass),
+            _ => s
+                .sps
+                .molar_mass()
+                .map_or_else(|e| e.to_string(), |m| format!("{m:.4} g/mol")),
+        }),
+        row("SE-SPS", "Target Mass Density", &|s| {
+            format!("{} g/cm^3", s.sps.target_mass_density)
+        }),
+        row("SE-SPS", "Beam Current", &|s| {
+            format!(
+                "{} {}",
+                s.sps.beam_current,
+                s.sps.units.beam_current.symbol()
+            )
+        }),
+        row("SE-SPS", "Beam", &|s| s.sps.beam_species()),
+        row("SE-SPS", "Slit Settings", &|s| {
+            format!(
+                "{} {}",
+                s.sps.slit_settings,
+                s.sps.units.slit_settings.symbol()
+            )
+        }),
+        row("SE-SPS", "Counts", &|s| s.sps.desired_counts.to_string()),
+        row("SE-SPS", "Beam Time", &|s| format!("{} h", s.sps.beam_time)),
+        row("SE-SPS", "Reaction", &|s| match s.sps.reaction() {
+            Ok(Some(reaction)) => reaction.name(),
+            Ok(None) => String::new(),
+            Err(e) => e.to_string(),
+        }),
+        row("SE-SPS", "Beam Energy", &|s| {
+            format!("{} MeV", s.sps.reaction.beam_energy)
+        }),
+        row("SE-SPS", "SPS Angle", &|s| {
+            format!("{}°", s.sps.reaction.angle)
+        }),
+        row("SE-SPS", "Lab/CM", &|s| match s.sps.frame_kinematics() {
+            Ok(Some(state)) => format!(
+                "θcm = {:.2}° | dΩcm/dΩlab = {:.4}",
+                state.cm_angle, state.jacobian
+            ),
+            Ok(None) => String::new(),
+            Err(e) => e.to_string(),
+        }),
+        row("SE-SPS", "SPS Field", &|s| match s.sps.field_setting() {
+            Ok(Some(setting)) => format!(
+                "{:.4} T (NMR {:.4} T) | Ex = {} MeV centred",
+                setting.field, setting.nmr_field, setting.excitation
+            ),
+            Ok(None) => String::new(),
+            Err(e) => e.to_string(),
+        }),
+        row("SE-SPS", "Beam Energy Loss", &|s| {
+            if s.sps.target.trim().is_empty() && s.sps.stopping.beam.is_none() {
+                return String::new();
+            }
+            match s.sps.target_energy_loss() {
+                Ok(loss) => format!("{:.1} keV | {:.3} mW", loss.beam.loss, loss.power),
+                Err(e) => e.to_string(),
+            }
+        }),
+        row(
+            "SE-SPS",
+            "Ex Resolution",
+            &|s| match s.sps.ex_resolution() {
+                Ok(Some(resolution)) => format!("{:.1} keV FWHM", resolution.fwhm),
+                Ok(None) => String::new(),
+                Err(e) => e.to_string(),
+            },
+        ),
+        row(
+            "SE-SPS",
+            "Field Settings Plan",
+            &|s| match s.sps.plan_field_settings() {
+                Ok(Some(plan)) => format!(
+                    "{} settings for Ex = {}-{} MeV | {:.2} h",
+                    plan.settings.len(),
+                    s.sps.field_plan.min_excitation,
+                    s.sps.field_plan.max_excitation,
+                    plan.total.hours
+                ),
+                Ok(None) => String::new(),
+                Err(e) => e.to_string(),
+            },
+        ),
+        row(
+            "SE-SPS",
+            "Angular Distribution",
+            &|s| match s.sps.plan_campaign() {
+                Ok(Some(campaign)) => format!(
+                    "{} angles | {:.2} h",
+                    campaign.angles.len(),
+                    campaign.total.hours
+                ),
+                Ok(None) => String::new(),
+                Err(e) => e.to_string(),
+            },
+        ),
+        row("SE-SPS", "Solve For", &|s| s.sps.unknown.name().to_string()),
+        row("SE-SPS", "Solution", &|s| match s.sps.solve() {
+            Ok(SPSSolution::RunTime(time)) => {
+                format!("{:.2} h | {:.2} shifts", time.hours, time.shifts)
+            }
+            Ok(solution) => solution.to_string(),
+            Err(e) => e.to_string(),
+        }),
+        row("CeBrA", "Particle Counts", &|s| {
+            s.cebra.n_particle_counts.to_string()
+        }),
+        row("CeBrA", "γ Energy", &|s| {
+            format!("{} keV", s.cebra.decay.energy)
+        }),
+        row("CeBrA", "γ Intensity", &|s| {
+            format!("{} %", s.cebra.decay.absolute_intensity)
+        }),
+        row("CeBrA", "Detectors", &|s| {
+            s.cebra.detectors.len().to_string()
+        }),
+        row(
+            "CeBrA",
+            "Total ε(γ)",
+            &|s| match s.cebra.calculate_counts() {
+                Ok(estimate) => format!("{:.2} %", estimate.total_efficiency),
+                Err(e) => e.to_string(),
+            },
+        ),
+        row(
+            "CeBrA",
+            "Total Counts",
+            &|s| match s.cebra.calculate_counts() {
+                Ok(estimate) => format!("{:.0}", estimate.total_counts),
+                Err(e) => e.to_string(),
+            },
+        ),
+        row("ICESPICE", "Particle Counts", &|s| {
+            s.icespice.n_particle_counts.to_string()
+        }),
+        row("ICESPICE", "Transmission Probability", &|s| {
+            format!("{} %", s.icespice.transmission_prob)
+        }),
+        row("ICESPICE", "Detector Efficiency", &|s| {
+            format!("{} %", s.icespice.detector_efficiency)
+        }),
+        row("ICESPICE", "Branching Ratio", &|s| {
+            format!("{} %", s.icespice.branching_ratio)
+        }),
+        row("ICESPICE", "Conversion Coefficient (α)", &|s| {
+            s.icespice.conversion_coefficient.to_string()
+        }),
+        row("ICESPICE", "Detected Conversion Electrons", &|s| match s
+            .icespice
+            .calculate_conversion_electrons()
+        {
+            Ok(estimate) => format!("{:.0}", estimate.detected_conversion_electrons),
+            Err(e) => e.to_string(),
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (α,3He) at 30 nA.
+    fn alpha_scenario() -> Scenario {
+        let mut scenario = Scenario::new("(α,3He) at 30 nA");
+        let sps = &mut scenario.sps;
+        sps.z_beam = 2;
+        sps.cross_section = 500.0;
+        sps.beam_current = 30.0;
+        scenario.cebra.n_particle_counts = 2500;
+        scenario.icespice.conversion_coefficient = 0.25;
+        scenario
+    }
+
+    #[test]
+    fn scenarios_survive_serialization() {
+        let scenario = alpha_scenario();
+        let text = toml::to_string(&scenario).unwrap();
+        let restored: Scenario = toml::from_str(&text).unwrap();
+        assert_eq!(toml::to_string(&restored).unwrap(), text);
+
+        assert_eq!(restored.name, "(α,3He) at 30 nA");
+        assert_eq!(restored.sps.z_beam, 2);
+        assert_eq!(restored.cebra.n_particle_counts, 2500);
+        assert_eq!(restored.icespice.conversion_coefficient, 0.25);
+        assert_eq!(
+            restored.sps.calculate_beam_time().unwrap(),
+            scenario.sps.calculate_beam_time().unwrap()
+        );
+    }
+
+    #[test]
+    fn comparison_flags_the_changed_inputs() {
+        let scenarios = [Scenario::new("(d,p)"), alpha_scenario()];
+        let rows = compare(&scenarios);
+        let row = |label| rows.iter().find(|row| row.label == label).unwrap();
+        assert!(row("Beam Current").differs);
+        assert!(row("Beam Current").differs_from_first(1));
+        assert_eq!(row("Beam Current").values[1], "30 nA");
+        assert!(!row("Slit Settings").differs);
+        assert_eq!(unique_name(&scenarios, "(d,p)"), "(d,p) (2)");
+    }
+}
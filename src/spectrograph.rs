@@ -0,0 +1,109 @@
+//! Magnetic rigidity and focal-plane model of the SE-SPS (an Enge split-pole spectrograph).
+//!
+//! To first order a particle of rigidity Bρ follows a radius ρ = Bρ / B and lands
+//! x = D (ρ − ρ₀) from the centre of the focal plane, where ρ₀ is the central radius and D the
+//! dispersion. The constants are settings so other spectrographs can be modelled too.
+
+/// Speed of light in units turning MeV/c per elementary charge into T·m.
+pub const MEV_PER_TESLA_METER: f64 = 299.792458;
+
+/// Magnetic rigidity in T·m of a particle with `momentum` in MeV/c and charge state `charge`.
+pub fn magnetic_rigidity(momentum: f64, charge: i32) -> f64 {
+    momentum / (MEV_PER_TESLA_METER * charge as f64)
+}
+
+/// Ion-optical constants of the spectrograph and its focal-plane detector.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Spectrograph {
+    pub radius: f64,            // cm, central radius ρ₀
+    pub dispersion: f64,        // cm of focal plane per cm of radius
+    pub detector_length: f64,   // cm, active length of the focal-plane detector
+    pub field_calibration: f64, // NMR reading per central field
+}
+
+impl Default for Spectrograph {
+    fn default() -> Self {
+        Self {
+            radius: 71.5,
+            dispersion: 1.96,
+            detector_length: 60.0,
+            field_calibration: 1.0,
+        }
+    }
+}
+
+impl Spectrograph {
+    /// Central field in T that bends `rigidity` onto the central radius.
+    pub fn field_for(&self, rigidity: f64) -> f64 {
+        rigidity / (self.radius / 100.0)
+    }
+
+    /// Field the NMR probe reads at the central `field`.
+    pub fn nmr_field(&self, field: f64) -> f64 {
+        field * self.field_calibration
+    }
+
+    /// Focal-plane position in cm from the detector centre; higher rigidity is positive.
+    pub fn position(&self, rigidity: f64, field: f64) -> f64 {
+        self.dispersion * (rigidity / field * 100.0 - self.radius)
+    }
+
+    /// Smallest and largest radius in cm that still hit the focal-plane detector.
+    pub fn radius_range(&self) -> (f64, f64) {
+        let half_width = self.detector_length / 2.0 / self.dispersion;
+        (self.radius - half_width, self.radius + half_width)
+    }
+
+    pub fn on_detector(&self, position: f64) -> bool {
+        position.abs() <= self.detector_length / 2.0
+    }
+}
+
+/// Field that centres one state on the focal plane.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct FieldSetting {
+    pub excitation: f64, // MeV, state at the detector centre
+    pub rigidity: f64,   // T·m
+    pub field: f64,      // T, central field
+    pub nmr_field: f64,  // T
+}
+
+/// Where one state of the recoil lands on the focal plane.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct FocalPlaneState {
+    pub excitation: f64, // MeV
+    pub rigidity: f64,   // T·m
+    pub position: f64,   // cm from the detector centre
+    pub on_detector: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rigidity_of_a_10_mev_proton() {
+        // p = √(T (T + 2m)) = 137.35 MeV/c
+        let momentum = (10.0f64 * (10.0 + 2.0 * 938.272)).sqrt();
+        assert!((magnetic_rigidity(momentum, 1) - 0.45815).abs() < 1e-4);
+        assert_eq!(
+            magnetic_rigidity(momentum, 2),
+            magnetic_rigidity(momentum, 1) / 2.0
+        );
+    }
+
+    #[test]
+    fn central_rigidity_lands_in_the_middle_of_the_focal_plane() {
+        let spectrograph = Spectrograph::default();
+        let field = spectrograph.field_for(0.5);
+        assert!(spectrograph.position(0.5, field).abs() < 1e-12);
+        let (low, high) = spectrograph.radius_range();
+        let edge = spectrograph.position(high / 100.0 * field, field);
+        assert!((edge - spectrograph.detector_length / 2.0).abs() < 1e-9);
+        let inside = spectrograph.position(0.999 * high / 100.0 * field, field);
+        let outside = spectrograph.position(0.999 * low / 100.0 * field, field);
+        assert!(spectrograph.on_detector(inside));
+        assert!(!spectrograph.on_detector(outside));
+    }
+}
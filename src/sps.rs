@@ -1,133 +1,1410 @@
-use eframe::egui::{self};
+use super::deck::{write_decks, Deck, DeckSettings};
+use super::dwba::{Transfer, TransferPoint, TransferState};
+use super::error::{
+    require_in_range, require_non_negative, require_positive, require_spin, EstimateError,
+};
+use super::kinematics::{EjectileKinematics, Frame, KinematicsError, Particle, Reaction};
+use super::listing::{ImportedCrossSections, ListingPoint};
+use super::nuclide::{self, Nuclide};
+use super::optical::{ElasticPoint, ElasticScattering};
+use super::planner::{AngularCampaign, FieldPlan};
+use super::resolution::ResolutionSettings;
+use super::rutherford::{rutherford, RutherfordCrossSection};
+use super::spectrograph::{magnetic_rigidity, FieldSetting, FocalPlaneState, Spectrograph};
+use super::stopping::StoppingTables;
+use super::target::{Assay, Formula, TargetError};
+use super::units::{
+    CrossSectionUnit, CurrentUnit, Quantity, SolidAngleUnit, ThicknessUnit, Unit, AVOGADRO,
+};
+use std::sync::{Mutex, PoisonError};
 
-const CHARGE: f64 = 1.6e-19; // Elementary charge in C
-const NA: f64 = 6.023e23; // Avogadro's number
+pub const MAX_SLIT_SETTINGS: f64 = 12.8; // msr, largest solid angle of the SE-SPS
+pub const SHIFT_HOURS: f64 = 8.0; // length of a beam-time shift
+
+/// Quantity of the SPS rate equation that is calculated from the others.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum SPSUnknown {
+    #[default]
+    RunTime,
+    Counts,
+    BeamCurrent,
+    CrossSection,
+    TargetDensity,
+}
+
+impl SPSUnknown {
+    pub const ALL: [SPSUnknown; 5] = [
+        SPSUnknown::RunTime,
+        SPSUnknown::Counts,
+        SPSUnknown::BeamCurrent,
+        SPSUnknown::CrossSection,
+        SPSUnknown::TargetDensity,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SPSUnknown::RunTime => "Run Time",
+            SPSUnknown::Counts => "Counts",
+            SPSUnknown::BeamCurrent => "Beam Current",
+            SPSUnknown::CrossSection => "Cross Section",
+            SPSUnknown::TargetDensity => "Target Density",
+        }
+    }
+}
+
+/// Where the cross section of the estimate comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum CrossSectionSource {
+    /// The entered cross section.
+    #[default]
+    Manual,
+    /// Rutherford scattering of the beam on the target nuclide at the spectrograph angle.
+    Rutherford,
+    /// Optical-model elastic scattering of the beam on the target nuclide at the spectrograph
+    /// angle.
+    OpticalModel,
+    /// Zero-range DWBA of the transfer to the state of the transfer settings.
+    Dwba,
+    /// Angular distribution of the selected state imported from the output of a DWBA code.
+    Imported,
+    /// A single-particle cross section scaled by spectroscopic factor and spin statistics.
+    Scaled,
+}
+
+impl CrossSectionSource {
+    pub const ALL: [CrossSectionSource; 6] = [
+        CrossSectionSource::Manual,
+        CrossSectionSource::Rutherford,
+        CrossSectionSource::OpticalModel,
+        CrossSectionSource::Dwba,
+        CrossSectionSource::Imported,
+        CrossSectionSource::Scaled,
+    ];
+
+    /// Whether the cross section is that of elastic scattering of the beam.
+    pub fn is_elastic(&self) -> bool {
+        matches!(
+            self,
+            CrossSectionSource::Rutherford | CrossSectionSource::OpticalModel
+        )
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrossSectionSource::Manual => "Manual",
+            CrossSectionSource::Rutherford => "Rutherford",
+            CrossSectionSource::OpticalModel => "Optical Model",
+            CrossSectionSource::Dwba => "DWBA",
+            CrossSectionSource::Imported => "Imported",
+            CrossSectionSource::Scaled => "Scaled Single-Particle",
+        }
+    }
+}
+
+/// Units the SPS inputs are entered in.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SPSUnits {
+    pub cross_section: CrossSectionUnit,
+    pub target_density: ThicknessUnit,
+    pub beam_current: CurrentUnit,
+    pub slit_settings: SolidAngleUnit,
+}
+
+/// Whether the transferred nucleon is added to the target or removed from it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum TransferType {
+    #[default]
+    Stripping,
+    Pickup,
+}
+
+impl TransferType {
+    pub const ALL: [TransferType; 2] = [TransferType::Stripping, TransferType::Pickup];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransferType::Stripping => "Stripping",
+            TransferType::Pickup => "Pickup",
+        }
+    }
+}
+
+/// Cross section of an unmeasured state from a reference single-particle cross section σ_sp:
+/// (2J_f + 1)/(2J_i + 1) · S · σ_sp in stripping and S · σ_sp in pickup.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ScaledCrossSection {
+    pub single_particle: f64, // units.cross_section, in cross_section_frame
+    pub spectroscopic_factor: f64,
+    pub initial_spin: f64, // J_i of the target
+    pub final_spin: f64,   // J_f of the state
+    pub transfer: TransferType,
+}
+
+impl Default for ScaledCrossSection {
+    fn default() -> Self {
+        Self {
+            single_particle: 100.0,
+            spectroscopic_factor: 1.0,
+            initial_spin: 0.0,
+            final_spin: 0.5,
+            transfer: TransferType::Stripping,
+        }
+    }
+}
+
+impl ScaledCrossSection {
+    /// (2J_f + 1)/(2J_i + 1) in stripping, 1 in pickup.
+    pub fn statistical_factor(&self) -> f64 {
+        match self.transfer {
+            TransferType::Stripping => {
+                (2.0 * self.final_spin + 1.0) / (2.0 * self.initial_spin + 1.0)
+            }
+            TransferType::Pickup => 1.0,
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.statistical_factor() * self.spectroscopic_factor * self.single_particle
+    }
+
+    /// The estimate written out with its numbers, e.g.
+    /// `(2J_f + 1)/(2J_i + 1) · S · σ_sp = (2·3/2 + 1)/(2·0 + 1) · 0.5 · 10 µb/sr = 20 µb/sr`.
+    pub fn explanation(&self, unit: &str) -> String {
+        let (formula, factor) = match self.transfer {
+            TransferType::Stripping => (
+                "(2J_f + 1)/(2J_i + 1) · S · σ_sp",
+                format!(
+                    "(2·{} + 1)/(2·{} + 1) · ",
+                    spin_name(self.final_spin),
+                    spin_name(self.initial_spin)
+                ),
+            ),
+            TransferType::Pickup => ("S · σ_sp", String::new()),
+        };
+        format!(
+            "{formula} = {factor}{} · {} {unit} = {:.4} {unit}",
+            self.spectroscopic_factor,
+            self.single_particle,
+            self.value()
+        )
+    }
+}
+
+/// Angular momentum like `3/2` or `2`.
+pub fn spin_name(spin: f64) -> String {
+    let twice = (2.0 * spin).round() as i64;
+    match twice % 2 {
+        0 => (twice / 2).to_string(),
+        _ => format!("{twice}/2"),
+    }
+}
+
+/// Reaction seen by the SE-SPS. Beam and target nuclide come from the rate inputs, the recoil
+/// follows from conservation of proton and mass number.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SPSReaction {
+    pub ejectile: String,              // nuclide, e.g. p; no kinematics when empty
+    pub recoil_mass: Option<f64>,      // u, replaces the nuclide table mass of the recoil
+    pub beam_energy: f64,              // MeV
+    pub angle: f64,                    // deg, of the spectrograph
+    pub excitation_energies: Vec<f64>, // MeV, states of the recoil
+    pub center_excitation: f64,        // MeV, state put at the centre of the focal plane
+}
+
+impl Default for SPSReaction {
+    fn default() -> Self {
+        Self {
+            ejectile: String::new(),
+            recoil_mass: None,
+            beam_energy: 16.0,
+            angle: 20.0,
+            excitation_energies: vec![0.0],
+            center_excitation: 0.0,
+        }
+    }
+}
+
+/// Result of a slow calculation with the inputs it was made for. A clone keeps its own copy,
+/// so settings cloned and then edited apart do not evict each other's result.
+struct Memo<K, V>(Mutex<Option<(K, Result<V, EstimateError>)>>);
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self(Mutex::new(None))
+    }
+}
+
+impl<K: PartialEq, V: Clone> Memo<K, V> {
+    /// The result for `key`, calculating it only if the last one was made for other inputs.
+    fn get_or_calculate(
+        &self,
+        key: K,
+        calculate: impl FnOnce() -> Result<V, EstimateError>,
+    ) -> Result<V, EstimateError> {
+        let mut memo = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        match &*memo {
+            Some((inputs, result)) if *inputs == key => result.clone(),
+            _ => {
+                let result = calculate();
+                *memo = Some((key, result.clone()));
+                result
+            }
+        }
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for Memo<K, V> {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        ))
+    }
+}
+
+impl<K, V> std::fmt::Debug for Memo<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Memo")
+    }
+}
+
+/// Optical-model and DWBA calculations of the settings, which take too long to repeat on every
+/// frame of the GUI. Neither depends on the spectrograph angle.
+#[derive(Clone, Debug, Default)]
+struct ModelCache {
+    elastic: Memo<(Particle, Particle, f64), ElasticScattering>, // beam, target, beam energy
+    transfer: Memo<(Reaction, f64, TransferState), Transfer>,    // with the beam energy
+}
 
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct SPSRunTimeSettings {
-    cross_section: f64,     // µb/sr
-    target_density: f64,    // µg/cm^2
-    target_molar_mass: f64, // g/mol
-    beam_current: f64,      // nA
-    z_beam: i32,            // proton number
-    slit_settings: f64,     // msr
-    desired_counts: i64,    // counts
-    time_s: f64,            // seconds
-    time_h: f64,            // hours
-    time_d: f64,            // days
+    pub cross_section: f64,         // units.cross_section
+    pub cross_section_frame: Frame, // frame of all cross sections, at reaction.angle
+    pub cross_section_source: CrossSectionSource,
+    pub target: String,            // chemical formula, e.g. 208Pb, natPb or 6LiF
+    pub reaction_nuclide: String,  // nuclide of the target the reaction is on, e.g. 6Li
+    pub target_assays: Vec<Assay>, // isotopic composition of enriched target elements
+    pub target_density: f64,       // units.target_density
+    pub target_molar_mass: f64,    // g/mol, used when no target formula is given
+    pub target_mass_density: f64,  // g/cm^3, for target thicknesses in nm
+    pub beam_current: f64,         // units.beam_current
+    pub z_beam: i32,               // proton number
+    pub a_beam: i32,               // mass number
+    pub q_beam: i32,               // charge state on the Faraday cup
+    pub slit_settings: f64,        // units.slit_settings
+    pub desired_counts: i64,       // counts
+    pub beam_time: f64,            // hours, used when solving for anything but the run time
+    pub unknown: SPSUnknown,
+    pub units: SPSUnits,
+    pub reaction: SPSReaction,
+    pub spectrograph: Spectrograph,
+    pub field_plan: FieldPlan,
+    pub campaign: AngularCampaign,
+    pub transfer: TransferState, // state of the DWBA cross section
+    pub imported: ImportedCrossSections,
+    pub deck: DeckSettings, // input decks for DWBA codes
+    pub scaling: ScaledCrossSection,
+    pub resolution: ResolutionSettings, // of the Ex resolution estimate
+    pub stopping: StoppingTables,       // SRIM tables replacing the built-in stopping
+    #[serde(skip)]
+    models: ModelCache,
 }
 
 impl Default for SPSRunTimeSettings {
     fn default() -> Self {
         Self {
             cross_section: 100.0,
+            cross_section_frame: Frame::Lab,
+            cross_section_source: CrossSectionSource::Manual,
+            target: String::new(),
+            reaction_nuclide: String::new(),
+            target_assays: vec![],
             target_density: 100.0,
             target_molar_mass: 240.0,
+            target_mass_density: 19.8,
             beam_current: 20.0,
             z_beam: 1,
+            a_beam: 2,
+            q_beam: 1,
             slit_settings: 4.62,
             desired_counts: 1000,
-            time_s: 0.0,
-            time_h: 0.0,
-            time_d: 0.0,
+            beam_time: 24.0,
+            unknown: SPSUnknown::RunTime,
+            units: SPSUnits::default(),
+            reaction: SPSReaction::default(),
+            spectrograph: Spectrograph::default(),
+            field_plan: FieldPlan::default(),
+            campaign: AngularCampaign::default(),
+            transfer: TransferState::default(),
+            imported: ImportedCrossSections::default(),
+            deck: DeckSettings::default(),
+            scaling: ScaledCrossSection::default(),
+            resolution: ResolutionSettings::default(),
+            stopping: StoppingTables::default(),
+            models: ModelCache::default(),
+        }
+    }
+}
+
+/// Beam time needed to collect the desired counts.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SPSRunTime {
+    pub seconds: f64,
+    pub hours: f64,
+    pub days: f64,
+    pub shifts: f64,
+}
+
+impl SPSRunTime {
+    pub fn from_seconds(seconds: f64) -> Self {
+        let hours = seconds / 3600.0;
+        Self {
+            seconds,
+            hours,
+            days: hours / 24.0,
+            shifts: hours / SHIFT_HOURS,
+        }
+    }
+}
+
+/// Areal density of one nuclide of the target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IsotopeDensity {
+    pub nuclide: &'static Nuclide,
+    pub atoms_per_cm2: f64,
+    pub micrograms_per_cm2: f64,
+}
+
+/// Counts from a nuclide of the target other than the reaction nuclide.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IsotopeYield {
+    pub nuclide: &'static Nuclide,
+    pub counts: f64,
+}
+
+/// Value of the unknown quantity of the SPS rate equation, in the units chosen for it.
+/// Serialized without a tag; the settings' `unknown` says which quantity it is.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(untagged)]
+pub enum SPSSolution {
+    RunTime(SPSRunTime),
+    Counts(f64),
+    BeamCurrent(Quantity<CurrentUnit>),
+    CrossSection(Quantity<CrossSectionUnit>),
+    TargetDensity(Quantity<ThicknessUnit>),
+}
+
+impl SPSSolution {
+    pub fn unknown(&self) -> SPSUnknown {
+        match self {
+            SPSSolution::RunTime(_) => SPSUnknown::RunTime,
+            SPSSolution::Counts(_) => SPSUnknown::Counts,
+            SPSSolution::BeamCurrent(_) => SPSUnknown::BeamCurrent,
+            SPSSolution::CrossSection(_) => SPSUnknown::CrossSection,
+            SPSSolution::TargetDensity(_) => SPSUnknown::TargetDensity,
+        }
+    }
+}
+
+impl std::fmt::Display for SPSSolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SPSSolution::RunTime(time) => write!(
+                f,
+                "{:.0} s | {:.2} h | {:.2} d",
+                time.seconds, time.hours, time.days
+            ),
+            SPSSolution::Counts(counts) => write!(f, "{counts:.0} counts"),
+            SPSSolution::BeamCurrent(current) => current.fmt(f),
+            SPSSolution::CrossSection(cross_section) => cross_section.fmt(f),
+            SPSSolution::TargetDensity(density) => density.fmt(f),
         }
     }
 }
 
 impl SPSRunTimeSettings {
-    fn calculate_beam_time(&mut self) {
-        let slits_sr = self.slit_settings * 1e-3; // msr to sr
-        let target_density = self.target_density * 1e-6; // µg/cm^2 to g/cm^2
-        let beam_current = self.beam_current * 1e-9; // nA to A
-        let f_target = (target_density * NA) / (self.target_molar_mass) * (1e-24) * (1e-6);
-
-        let run_time_s = (self.z_beam as f64 * CHARGE * self.desired_counts as f64)
-            / (self.cross_section * f_target * slits_sr * beam_current);
-
-        self.time_s = run_time_s;
-        self.time_h = run_time_s / 3600.0;
-        self.time_d = self.time_h / 24.0;
-    }
-
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
-        egui::Grid::new("sps_runtime_settings_grid")
-            .num_columns(2)
-            .striped(true)
-            .show(ui, |ui| {
-                ui.heading("SE-SPS");
-                ui.end_row();
-
-                ui.label("Cross Section:");
-
-                    // Display and adjust the cross section as microbarns
-                ui.add(
-                    egui::DragValue::new(&mut self.cross_section)
-                        .speed(1.0)
-                        .suffix(" µb/sr")
-                        .range(0.0..=f64::INFINITY)
-                    );
-                ui.end_row();
-
-
-                ui.label("Target Density:");
-                ui.add(
-                    egui::DragValue::new(&mut self.target_density)
-                        .speed(1.0)
-                        .suffix(" µg/cm^2")
-                        .range(0.0..=f64::INFINITY)
-                );
-                ui.end_row();
-
-                ui.label("Target Molar Mass:");
-                ui.add(
-                    egui::DragValue::new(&mut self.target_molar_mass)
-                        .speed(1.0)
-                        .suffix(" g/mol")
-                        .range(0.0..=f64::INFINITY)
-                );
-                ui.end_row();
-
-                ui.label("Beam Current:");
-                ui.add(
-                    egui::DragValue::new(&mut self.beam_current)
-                        .speed(1.0)
-                        .suffix(" nA")
-                        .range(0.0..=f64::INFINITY)
-                ).on_hover_text("Beam current on target.");
-                ui.end_row();
-
-                ui.label("Z Beam:");
-                ui.add(
-                    egui::DragValue::new(&mut self.z_beam)
-                        .speed(1.0)
-                        .prefix("Z = ")
-                        .range(1..=118) // Adjusted to allow minimum 1
-                ).on_hover_text("Proton number of the beam.");
-                ui.end_row();
-
-                ui.label("Slit Settings:");
-                ui.add(
-                    egui::DragValue::new(&mut self.slit_settings)
-                        .speed(0.1)
-                        .suffix(" msr")
-                        .range(0.0..=12.8)
-                ).on_hover_text("Solid angle of the SE-SPS. Typical value is 4.62 msr. The SE-SPS has a max solid angle of 12.8 msr.");
-                ui.end_row();
-
-                ui.label("Counts:");
-                ui.add(
-                    egui::DragValue::new(&mut self.desired_counts)
-                        .speed(1.0)
-                        .suffix(" counts")
-                        .range(0..=i64::MAX)
-                ).on_hover_text("The desired number of counts in the peak of interest.");
-                ui.end_row();
-
-                // Call calculate_beam_time here if it should happen automatically upon any change
-                self.calculate_beam_time();
-                ui.label("Estimated Time:");
-                ui.label(format!("{:.0} s | {:.2} h | {:.2} d", self.time_s, self.time_h, self.time_d));
-                ui.end_row();
-        });
+    /// Checks every input that `unknown` is calculated from.
+    pub fn validate_for(&self, unknown: SPSUnknown) -> Result<(), EstimateError> {
+        if unknown != SPSUnknown::CrossSection {
+            require_positive("Cross section", self.cross_section_value()?)?;
+        }
+        if unknown != SPSUnknown::TargetDensity {
+            require_positive("Target density", self.target_density)?;
+        }
+        require_positive("Target molar mass", self.molar_mass()?)?;
+        if unknown != SPSUnknown::BeamCurrent {
+            require_positive("Beam current", self.beam_current)?;
+        }
+        require_in_range("Z beam", self.z_beam as f64, 1.0, 118.0)?;
+        require_in_range("A beam", self.a_beam as f64, self.z_beam as f64, 300.0)?;
+        require_in_range(
+            "Beam charge state",
+            self.q_beam as f64,
+            1.0,
+            self.z_beam as f64,
+        )?;
+        require_positive("Slit settings", self.slit_settings)?;
+        require_in_range(
+            "Slit settings (msr)",
+            SolidAngleUnit::Millisteradian
+                .from_base(self.units.slit_settings.to_base(self.slit_settings)),
+            0.0,
+            MAX_SLIT_SETTINGS,
+        )?;
+        if unknown != SPSUnknown::Counts {
+            require_non_negative("Counts", self.desired_counts as f64)?;
+        }
+        if unknown != SPSUnknown::RunTime {
+            require_positive("Beam time", self.beam_time)?;
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), EstimateError> {
+        self.validate_for(self.unknown)
+    }
+
+    /// Counts per second in the peak for the given cross section, target density and beam
+    /// current in their selected units; the rate equation is linear in each of them.
+    fn count_rate(
+        &self,
+        cross_section: f64,
+        target_density: f64,
+        beam_current: f64,
+    ) -> Result<f64, EstimateError> {
+        let units = &self.units;
+        let cross_section =
+            units.cross_section.to_base(cross_section) * self.cross_section_jacobian()?; // lab cm^2/sr
+        let target_density = units.target_density.to_base(
+            target_density,
+            self.molar_mass()?,
+            self.target_mass_density,
+            self.formula_atoms()?,
+        )? * self.reaction_atoms()?; // reaction nuclei/cm^2
+        let beam_current = units
+            .beam_current
+            .to_base(beam_current, self.q_beam as f64)?; // particles/s
+        let slits = units.slit_settings.to_base(self.slit_settings); // sr
+
+        Ok(cross_section * target_density * slits * beam_current)
+    }
+
+    pub fn calculate_beam_time(&self) -> Result<SPSRunTime, EstimateError> {
+        self.validate_for(SPSUnknown::RunTime)?;
+
+        let run_time_s = self.desired_counts as f64
+            / self.count_rate(
+                self.cross_section_value()?,
+                self.target_density,
+                self.beam_current,
+            )?;
+
+        Ok(SPSRunTime::from_seconds(run_time_s))
+    }
+
+    /// Solves the rate equation for `unknown` from the remaining inputs.
+    pub fn solve_for(&self, unknown: SPSUnknown) -> Result<SPSSolution, EstimateError> {
+        if unknown == SPSUnknown::RunTime {
+            return Ok(SPSSolution::RunTime(self.calculate_beam_time()?));
+        }
+        self.validate_for(unknown)?;
+
+        let seconds = self.beam_time * 3600.0;
+        let counts = self.desired_counts as f64;
+        Ok(match unknown {
+            SPSUnknown::RunTime => unreachable!("handled above"),
+            SPSUnknown::Counts => SPSSolution::Counts(
+                self.count_rate(
+                    self.cross_section_value()?,
+                    self.target_density,
+                    self.beam_current,
+                )? * seconds,
+            ),
+            SPSUnknown::BeamCurrent => SPSSolution::BeamCurrent(Quantity {
+                value: counts
+                    / (self.count_rate(self.cross_section_value()?, self.target_density, 1.0)?
+                        * seconds),
+                unit: self.units.beam_current,
+            }),
+            SPSUnknown::CrossSection => SPSSolution::CrossSection(Quantity {
+                value: counts
+                    / (self.count_rate(1.0, self.target_density, self.beam_current)? * seconds),
+                unit: self.units.cross_section,
+            }),
+            SPSUnknown::TargetDensity => SPSSolution::TargetDensity(Quantity {
+                value: counts
+                    / (self.count_rate(self.cross_section_value()?, 1.0, self.beam_current)?
+                        * seconds),
+                unit: self.units.target_density,
+            }),
+        })
+    }
+
+    /// The parsed target formula, or `None` for a target given only by its molar mass.
+    pub fn formula(&self) -> Result<Option<Formula>, EstimateError> {
+        if self.target.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Formula::parse(&self.target)?))
+    }
+
+    /// Molar mass of the target formula, or the entered molar mass without a formula.
+    pub fn molar_mass(&self) -> Result<f64, EstimateError> {
+        match self.formula()? {
+            Some(formula) => Ok(formula.molar_mass(&self.target_assays)?),
+            None => Ok(self.target_molar_mass),
+        }
+    }
+
+    /// The reaction nuclide, or `None` if every formula unit of the target counts.
+    pub fn reaction_nuclide(&self) -> Result<Option<&'static Nuclide>, EstimateError> {
+        let name = self.reaction_nuclide.trim();
+        if name.is_empty() {
+            return Ok(None);
+        }
+        nuclide::parse(name)
+            .map(Some)
+            .ok_or_else(|| TargetError::UnknownNuclide(name.to_string()).into())
+    }
+
+    /// Atoms per formula unit of the target; a target given by its molar mass counts as one.
+    pub fn formula_atoms(&self) -> Result<f64, EstimateError> {
+        Ok(self.formula()?.map_or(1.0, |formula| formula.atoms()))
+    }
+
+    /// Reaction nuclei per formula unit of the target.
+    fn reaction_atoms(&self) -> Result<f64, EstimateError> {
+        let Some(reaction) = self.reaction_nuclide()? else {
+            return Ok(1.0);
+        };
+        match self.formula()? {
+            Some(formula) => Ok(formula.atoms_of(reaction, &self.target_assays)?),
+            None => Err(TargetError::NotInTarget(reaction.name()).into()),
+        }
+    }
+
+    /// Target density in `units.target_density`, the solved one when solving for it.
+    pub fn solved_target_density(&self) -> Result<f64, EstimateError> {
+        match self.unknown {
+            SPSUnknown::TargetDensity => match self.solve()? {
+                SPSSolution::TargetDensity(thickness) => Ok(thickness.value),
+                _ => unreachable!("solved for the target density"),
+            },
+            _ => Ok(self.target_density),
+        }
+    }
+
+    /// Areal density of every nuclide in the target formula, using the solved thickness when
+    /// solving for it.
+    pub fn target_composition(&self) -> Result<Vec<IsotopeDensity>, EstimateError> {
+        let Some(formula) = self.formula()? else {
+            return Ok(vec![]);
+        };
+        let thickness = self.solved_target_density()?;
+        let molar_mass = formula.molar_mass(&self.target_assays)?;
+        let formula_units = self.units.target_density.to_base(
+            thickness,
+            molar_mass,
+            self.target_mass_density,
+            formula.atoms(),
+        )?; // formula units/cm^2
+
+        Ok(formula
+            .isotopes(&self.target_assays)?
+            .into_iter()
+            .map(|share| {
+                let atoms_per_cm2 = formula_units * share.atoms;
+                IsotopeDensity {
+                    nuclide: share.nuclide,
+                    atoms_per_cm2,
+                    micrograms_per_cm2: atoms_per_cm2 * share.nuclide.mass / AVOGADRO * 1e6,
+                }
+            })
+            .collect())
+    }
+
+    /// Counts expected from every other nuclide of the target during the same run, assuming the
+    /// same cross section as the reaction nuclide. Empty unless a reaction nuclide is chosen.
+    pub fn isotope_yields(&self) -> Result<Vec<IsotopeYield>, EstimateError> {
+        let Some(reaction) = self.reaction_nuclide()? else {
+            return Ok(vec![]);
+        };
+        let counts = match self.solve()? {
+            SPSSolution::Counts(counts) => counts,
+            _ => self.desired_counts as f64,
+        };
+        let composition = self.target_composition()?;
+        let reaction_density = composition
+            .iter()
+            .find(|isotope| isotope.nuclide == reaction)
+            .map(|isotope| isotope.atoms_per_cm2)
+            .ok_or_else(|| TargetError::NotInTarget(reaction.name()))?;
+
+        Ok(composition
+            .iter()
+            .filter(|isotope| isotope.nuclide != reaction)
+            .map(|isotope| IsotopeYield {
+                nuclide: isotope.nuclide,
+                counts: counts * isotope.atoms_per_cm2 / reaction_density,
+            })
+            .collect())
+    }
+
+    /// Beam species as text, e.g. `7Li3+`.
+    pub fn beam_species(&self) -> String {
+        let symbol = nuclide::element_symbol(self.z_beam).unwrap_or("?");
+        format!("{}{}{}+", self.a_beam, symbol, self.q_beam)
+    }
+
+    /// The beam current in every current unit, for showing the conversion.
+    pub fn beam_intensity(&self) -> Result<Vec<Quantity<CurrentUnit>>, EstimateError> {
+        let particles_per_second = self
+            .units
+            .beam_current
+            .to_base(self.beam_current, self.q_beam as f64)?;
+        CurrentUnit::ALL
+            .iter()
+            .map(|&unit| {
+                Ok(Quantity {
+                    value: unit.from_base(particles_per_second, self.q_beam as f64)?,
+                    unit,
+                })
+            })
+            .collect()
+    }
+
+    /// The nucleus of the target the reaction is on: the reaction nuclide, or the target if it
+    /// is a single nuclide.
+    pub fn target_nuclide(&self) -> Result<&'static Nuclide, EstimateError> {
+        if let Some(nuclide) = self.reaction_nuclide()? {
+            return Ok(nuclide);
+        }
+        let isotopes = match self.formula()? {
+            Some(formula) => formula.isotopes(&self.target_assays)?,
+            None => vec![],
+        };
+        match isotopes[..] {
+            [share] => Ok(share.nuclide),
+            _ => Err(KinematicsError::NoTargetNuclide.into()),
+        }
+    }
+
+    /// Beam, target and ejectile of the reaction, or `None` without an ejectile.
+    fn entrance_particles(&self) -> Result<Option<[Particle; 3]>, EstimateError> {
+        let name = self.reaction.ejectile.trim();
+        if name.is_empty() {
+            return Ok(None);
+        }
+        let ejectile = nuclide::parse(name)
+            .map(Particle::from_nuclide)
+            .ok_or_else(|| TargetError::UnknownNuclide(name.to_string()))?;
+        let beam = Particle::find(self.z_beam, self.a_beam)?;
+        let target = Particle::from_nuclide(self.target_nuclide()?);
+        Ok(Some([beam, target, ejectile]))
+    }
+
+    /// Proton and mass number of the recoil, or `None` without an ejectile.
+    pub fn recoil_nucleus(&self) -> Result<Option<(i32, i32)>, EstimateError> {
+        match self.entrance_particles()? {
+            Some([beam, target, ejectile]) => {
+                Ok(Some(Reaction::recoil_of(&beam, &target, &ejectile)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The reaction for the kinematics, or `None` without an ejectile.
+    pub fn reaction(&self) -> Result<Option<Reaction>, EstimateError> {
+        let Some([beam, target, ejectile]) = self.entrance_particles()? else {
+            return Ok(None);
+        };
+        let (z, a) = Reaction::recoil_of(&beam, &target, &ejectile)?;
+        let recoil = match self.reaction.recoil_mass {
+            Some(mass) => Particle::from_atomic_mass(z, a, require_positive("Recoil mass", mass)?),
+            None => Particle::find(z, a)?,
+        };
+        Ok(Some(Reaction::new(beam, target, ejectile, recoil)?))
+    }
+
+    /// Ejectile and recoil for every excitation energy of the recoil, `None` where the state
+    /// cannot be reached at the spectrograph angle. Empty without an ejectile.
+    pub fn kinematics(&self) -> Result<Vec<Option<EjectileKinematics>>, EstimateError> {
+        let Some(reaction) = self.reaction()? else {
+            return Ok(vec![]);
+        };
+        let settings = &self.reaction;
+        require_positive("Beam energy", settings.beam_energy)?;
+        require_in_range("Spectrograph angle", settings.angle, 0.0, 180.0)?;
+        settings
+            .excitation_energies
+            .iter()
+            .map(|&excitation| {
+                require_non_negative("Excitation energy", excitation)?;
+                Ok(reaction.solve(settings.beam_energy, settings.angle, excitation))
+            })
+            .collect()
+    }
+
+    /// Rutherford scattering of the beam on the target nuclide at the beam energy and
+    /// spectrograph angle of the reaction settings.
+    pub fn rutherford(&self) -> Result<RutherfordCrossSection, EstimateError> {
+        let beam_energy = require_positive("Beam energy", self.reaction.beam_energy)?;
+        let angle = require_in_range("Spectrograph angle", self.reaction.angle, 0.0, 180.0)?;
+        require_positive("Spectrograph angle", angle)?;
+        let beam = Particle::find(self.z_beam, self.a_beam)?;
+        let target = Particle::from_nuclide(self.target_nuclide()?);
+        Ok(rutherford(beam, target, beam_energy, angle)?)
+    }
+
+    /// Optical-model elastic scattering of the beam on the target nuclide at the beam energy of
+    /// the reaction settings, calculated again only when one of them changes.
+    pub fn elastic_scattering(&self) -> Result<ElasticScattering, EstimateError> {
+        let beam_energy = require_positive("Beam energy", self.reaction.beam_energy)?;
+        let beam = Particle::find(self.z_beam, self.a_beam)?;
+        let target = Particle::from_nuclide(self.target_nuclide()?);
+        self.models
+            .elastic
+            .get_or_calculate((beam, target, beam_energy), || {
+                Ok(ElasticScattering::new(beam, target, beam_energy)?)
+            })
+    }
+
+    /// Optical-model cross section at the spectrograph angle.
+    pub fn optical_model(&self) -> Result<ElasticPoint, EstimateError> {
+        let angle = require_in_range("Spectrograph angle", self.reaction.angle, 0.0, 180.0)?;
+        require_positive("Spectrograph angle", angle)?;
+        Ok(self.elastic_scattering()?.at_angle(angle)?)
+    }
+
+    /// Zero-range DWBA of the reaction to the state of the transfer settings at the beam
+    /// energy of the reaction settings, calculated again only when one of them changes.
+    pub fn transfer(&self) -> Result<Transfer, EstimateError> {
+        let reaction = self.reaction()?.ok_or(KinematicsError::NoEjectile)?;
+        let beam_energy = require_positive("Beam energy", self.reaction.beam_energy)?;
+        require_non_negative("Excitation energy", self.transfer.excitation)?;
+        require_non_negative("Spectroscopic factor", self.transfer.spectroscopic_factor)?;
+        require_spin("Target spin J_i", self.transfer.initial_spin)?;
+        require_spin("Final spin J_f", self.transfer.spin())?;
+        let state = &self.transfer;
+        self.models
+            .transfer
+            .get_or_calculate((reaction, beam_energy, state.clone()), || {
+                Ok(Transfer::new(reaction, beam_energy, state)?)
+            })
+    }
+
+    /// DWBA cross section at the spectrograph angle.
+    pub fn dwba(&self) -> Result<TransferPoint, EstimateError> {
+        let angle = require_in_range("Spectrograph angle", self.reaction.angle, 0.0, 180.0)?;
+        Ok(self.transfer()?.at_angle(angle)?)
+    }
+
+    /// Ptolemy or FRESCO input decks for the states of the deck settings at the beam energy of
+    /// the reaction settings. Empty without states.
+    pub fn input_decks(&self) -> Result<Vec<Deck>, EstimateError> {
+        let reaction = self.reaction()?.ok_or(KinematicsError::NoEjectile)?;
+        let beam_energy = require_positive("Beam energy", self.reaction.beam_energy)?;
+        let deck = &self.deck;
+        require_in_range("Smallest deck angle", deck.min_angle, 0.0, 180.0)?;
+        require_in_range("Largest deck angle", deck.max_angle, deck.min_angle, 180.0)?;
+        require_positive("Deck angle step", deck.angle_step)?;
+        for state in &deck.states {
+            require_non_negative("Excitation energy", state.excitation)?;
+            require_non_negative("Spectroscopic factor", state.spectroscopic_factor)?;
+        }
+        Ok(write_decks(deck, &reaction, beam_energy)?)
+    }
+
+    /// Imported cross section of the selected state, interpolated at the centre-of-mass angle of
+    /// the spectrograph.
+    pub fn imported(&self) -> Result<ListingPoint, EstimateError> {
+        let state = self.imported.state()?;
+        let angle = require_in_range("Spectrograph angle", self.reaction.angle, 0.0, 180.0)?;
+        let excitation = state.excitation()?;
+        let kinematics = self.state_kinematics(excitation)?;
+        let cm = state.cm_at(kinematics.cm_angle)?;
+        Ok(ListingPoint {
+            state: state.label.clone(),
+            excitation,
+            angle,
+            cm_angle: kinematics.cm_angle,
+            cm,
+            lab: cm * kinematics.jacobian,
+        })
+    }
+
+    /// Single-particle cross section scaled by spectroscopic factor and spin statistics, in
+    /// `units.cross_section` and `cross_section_frame`.
+    pub fn scaled_cross_section(&self) -> Result<f64, EstimateError> {
+        let scaling = &self.scaling;
+        require_non_negative("Single-particle cross section", scaling.single_particle)?;
+        require_non_negative("Spectroscopic factor", scaling.spectroscopic_factor)?;
+        require_spin("Target spin J_i", scaling.initial_spin)?;
+        require_spin("Final spin J_f", scaling.final_spin)?;
+        Ok(scaling.value())
+    }
+
+    /// Cross section the estimate is based on, in `units.cross_section` and
+    /// `cross_section_frame`: the entered one, or that of the chosen model.
+    pub fn cross_section_value(&self) -> Result<f64, EstimateError> {
+        let millibarn = match self.cross_section_source {
+            CrossSectionSource::Manual => return Ok(self.cross_section),
+            CrossSectionSource::Scaled => return self.scaled_cross_section(),
+            CrossSectionSource::Rutherford => {
+                let rutherford = self.rutherford()?;
+                match self.cross_section_frame {
+                    Frame::Lab => rutherford.lab,
+                    Frame::CenterOfMass => rutherford.cm,
+                }
+            }
+            CrossSectionSource::OpticalModel => {
+                let point = self.optical_model()?;
+                match self.cross_section_frame {
+                    Frame::Lab => point.lab,
+                    Frame::CenterOfMass => point.cm,
+                }
+            }
+            CrossSectionSource::Dwba => {
+                let point = self.dwba()?;
+                match self.cross_section_frame {
+                    Frame::Lab => point.lab,
+                    Frame::CenterOfMass => point.cm,
+                }
+            }
+            CrossSectionSource::Imported => {
+                let point = self.imported()?;
+                match self.cross_section_frame {
+                    Frame::Lab => point.lab,
+                    Frame::CenterOfMass => point.cm,
+                }
+            }
+        };
+        Ok(self
+            .units
+            .cross_section
+            .from_base(CrossSectionUnit::MillibarnPerSr.to_base(millibarn)))
+    }
+
+    /// Kinematics of the centred state at the spectrograph angle, which converts cross sections
+    /// between lab and CM. `None` without an ejectile.
+    pub fn frame_kinematics(&self) -> Result<Option<EjectileKinematics>, EstimateError> {
+        let Some(reaction) = self.reaction()? else {
+            return Ok(None);
+        };
+        let beam_energy = require_positive("Beam energy", self.reaction.beam_energy)?;
+        let angle = require_in_range("Spectrograph angle", self.reaction.angle, 0.0, 180.0)?;
+        let excitation =
+            require_non_negative("Centre excitation energy", self.reaction.center_excitation)?;
+        let state = reaction
+            .solve(beam_energy, angle, excitation)
+            .ok_or(KinematicsError::NotReachable(excitation))?;
+        Ok(Some(state))
+    }
+
+    /// Kinematics of the state at `excitation` MeV at the beam energy and spectrograph angle.
+    fn state_kinematics(&self, excitation: f64) -> Result<EjectileKinematics, EstimateError> {
+        let reaction = self.reaction()?.ok_or(KinematicsError::NoEjectile)?;
+        let beam_energy = require_positive("Beam energy", self.reaction.beam_energy)?;
+        let excitation = require_non_negative("Excitation energy", excitation)?;
+        Ok(reaction
+            .solve(beam_energy, self.reaction.angle, excitation)
+            .ok_or(KinematicsError::NotReachable(excitation))?)
+    }
+
+    /// Factor turning the entered cross sections into lab cross sections: 1 in the lab, the
+    /// Jacobian dΩ_cm/dΩ_lab of the centred state at the spectrograph angle in CM, that of
+    /// elastic scattering for an elastic cross section, or that of the DWBA or imported state.
+    pub fn cross_section_jacobian(&self) -> Result<f64, EstimateError> {
+        match self.cross_section_frame {
+            Frame::Lab => Ok(1.0),
+            Frame::CenterOfMass if self.cross_section_source.is_elastic() => {
+                Ok(self.rutherford()?.jacobian) // same elastic kinematics for every model
+            }
+            Frame::CenterOfMass if self.cross_section_source == CrossSectionSource::Dwba => {
+                Ok(self.state_kinematics(self.transfer.excitation)?.jacobian)
+            }
+            Frame::CenterOfMass if self.cross_section_source == CrossSectionSource::Imported => {
+                let excitation = self.imported.state()?.excitation()?;
+                Ok(self.state_kinematics(excitation)?.jacobian)
+            }
+            Frame::CenterOfMass => self
+                .frame_kinematics()?
+                .map(|state| state.jacobian)
+                .ok_or_else(|| KinematicsError::NoEjectile.into()),
+        }
+    }
+
+    /// Dipole field that puts the centre excitation energy at the centre of the focal plane,
+    /// or `None` without an ejectile.
+    pub fn field_setting(&self) -> Result<Option<FieldSetting>, EstimateError> {
+        let Some(reaction) = self.reaction()? else {
+            return Ok(None);
+        };
+        let settings = &self.reaction;
+        let spectrograph = &self.spectrograph;
+        require_positive("Beam energy", settings.beam_energy)?;
+        require_in_range("Spectrograph angle", settings.angle, 0.0, 180.0)?;
+        let excitation =
+            require_non_negative("Centre excitation energy", settings.center_excitation)?;
+        require_positive("Spectrograph radius", spectrograph.radius)?;
+        require_positive("NMR calibration", spectrograph.field_calibration)?;
+        if reaction.ejectile.z == 0 {
+            return Err(KinematicsError::NeutralEjectile.into());
+        }
+
+        let state = reaction
+            .solve(settings.beam_energy, settings.angle, excitation)
+            .ok_or(KinematicsError::NotReachable(excitation))?;
+        let rigidity = magnetic_rigidity(state.ejectile_momentum, reaction.ejectile.z);
+        let field = spectrograph.field_for(rigidity);
+        Ok(Some(FieldSetting {
+            excitation,
+            rigidity,
+            field,
+            nmr_field: spectrograph.nmr_field(field),
+        }))
+    }
+
+    /// Rigidity and focal-plane position of every state at the field setting, `None` where the
+    /// state cannot be reached. Empty without an ejectile.
+    pub fn focal_plane(&self) -> Result<Vec<Option<FocalPlaneState>>, EstimateError> {
+        let Some(setting) = self.field_setting()? else {
+            return Ok(vec![]);
+        };
+        require_non_negative("Detector length", self.spectrograph.detector_length)?;
+        let charge = self.reaction()?.map_or(1, |reaction| reaction.ejectile.z);
+        Ok(self
+            .kinematics()?
+            .into_iter()
+            .map(|state| {
+                state.map(|state| {
+                    let rigidity = magnetic_rigidity(state.ejectile_momentum, charge);
+                    let position = self.spectrograph.position(rigidity, setting.field);
+                    FocalPlaneState {
+                        excitation: state.excitation,
+                        rigidity,
+                        position,
+                        on_detector: self.spectrograph.on_detector(position),
+                    }
+                })
+            })
+            .collect())
+    }
+
+    /// Solves for the selected [`SPSRunTimeSettings::unknown`].
+    pub fn solve(&self) -> Result<SPSSolution, EstimateError> {
+        self.solve_for(self.unknown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::assert_close;
+    use crate::units::ELEMENTARY_CHARGE;
+
+    #[test]
+    fn default_settings_need_69000_seconds() {
+        let time = SPSRunTimeSettings::default().calculate_beam_time().unwrap();
+        assert_eq!(format!("{:.0}", time.seconds), "69000");
+        assert!((time.hours - time.seconds / 3600.0).abs() < 1e-12);
+        assert!((time.days - time.hours / 24.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn invalid_inputs_name_the_parameter() {
+        let with = |change: fn(&mut SPSRunTimeSettings)| {
+            let mut settings = SPSRunTimeSettings::default();
+            change(&mut settings);
+            settings.calculate_beam_time().unwrap_err()
+        };
+        assert_eq!(
+            with(|s| s.cross_section = 0.0),
+            EstimateError::NotPositive {
+                parameter: "Cross section",
+                value: 0.0
+            }
+        );
+        assert_eq!(
+            with(|s| s.beam_current = -5.0),
+            EstimateError::NotPositive {
+                parameter: "Beam current",
+                value: -5.0
+            }
+        );
+        assert_eq!(
+            with(|s| s.z_beam = 0),
+            EstimateError::OutOfRange {
+                parameter: "Z beam",
+                value: 0.0,
+                min: 1.0,
+                max: 118.0
+            }
+        );
+        assert_eq!(
+            with(|s| s.slit_settings = 20.0),
+            EstimateError::OutOfRange {
+                parameter: "Slit settings (msr)",
+                value: 20.0,
+                min: 0.0,
+                max: MAX_SLIT_SETTINGS
+            }
+        );
+        assert_eq!(
+            with(|s| s.desired_counts = -1),
+            EstimateError::Negative {
+                parameter: "Counts",
+                value: -1.0
+            }
+        );
+    }
+
+    fn value(solution: SPSSolution) -> f64 {
+        match solution {
+            SPSSolution::RunTime(time) => time.seconds,
+            SPSSolution::Counts(counts) => counts,
+            SPSSolution::BeamCurrent(quantity) => quantity.value,
+            SPSSolution::CrossSection(quantity) => quantity.value,
+            SPSSolution::TargetDensity(quantity) => quantity.value,
+        }
+    }
+
+    #[test]
+    fn every_unknown_inverts_the_forward_rate() {
+        let mut forward = SPSRunTimeSettings::default();
+        let mixed = SPSUnits {
+            cross_section: CrossSectionUnit::MillibarnPerSr,
+            target_density: ThicknessUnit::AtomsPerCm2,
+            beam_current: CurrentUnit::ParticleNanoampere,
+            slit_settings: SolidAngleUnit::Steradian,
+        };
+        for units in [SPSUnits::default(), mixed] {
+            forward.units = units;
+            forward.cross_section = 0.25;
+            forward.target_density = 3e18;
+            forward.beam_current = 15.0;
+            forward.slit_settings = 0.004;
+            forward.z_beam = 2;
+            let rate = forward
+                .count_rate(
+                    forward.cross_section,
+                    forward.target_density,
+                    forward.beam_current,
+                )
+                .unwrap();
+            let seconds = value(forward.solve_for(SPSUnknown::RunTime).unwrap());
+            assert_close(seconds, forward.desired_counts as f64 / rate, 1e-12);
+
+            let settings = SPSRunTimeSettings {
+                beam_time: seconds / 3600.0,
+                ..forward.clone()
+            };
+            for (unknown, expected) in [
+                (SPSUnknown::Counts, forward.desired_counts as f64),
+                (SPSUnknown::BeamCurrent, forward.beam_current),
+                (SPSUnknown::CrossSection, forward.cross_section),
+                (SPSUnknown::TargetDensity, forward.target_density),
+            ] {
+                let solution = settings.solve_for(unknown).unwrap();
+                assert_eq!(solution.unknown(), unknown);
+                assert_close(value(solution), expected, 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn electrical_current_counts_the_charge_state() {
+        // 7Li3+ at 30 nA are 10 pnA of particles
+        let lithium = SPSRunTimeSettings {
+            z_beam: 3,
+            a_beam: 7,
+            q_beam: 3,
+            beam_current: 30.0,
+            ..Default::default()
+        };
+        assert_eq!(lithium.beam_species(), "7Li3+");
+        let intensity = lithium.beam_intensity().unwrap();
+        assert_close(intensity[1].value, 10.0, 1e-12);
+        assert_close(intensity[2].value, 10e-9 / ELEMENTARY_CHARGE, 1e-12);
+
+        let particle_current = SPSRunTimeSettings {
+            beam_current: 10.0,
+            units: SPSUnits {
+                beam_current: CurrentUnit::ParticleNanoampere,
+                ..Default::default()
+            },
+            ..lithium.clone()
+        };
+        assert_close(
+            particle_current.calculate_beam_time().unwrap().seconds,
+            lithium.calculate_beam_time().unwrap().seconds,
+            1e-12,
+        );
+
+        let stripped = SPSRunTimeSettings {
+            q_beam: 4,
+            ..lithium
+        };
+        assert_eq!(
+            stripped.calculate_beam_time(),
+            Err(EstimateError::OutOfRange {
+                parameter: "Beam charge state",
+                value: 4.0,
+                min: 1.0,
+                max: 3.0
+            })
+        );
+    }
+
+    #[test]
+    fn zero_or_negative_inputs_are_errors() {
+        type Setter = fn(&mut SPSRunTimeSettings, f64);
+        // each input with the unknown that does not use it
+        let inputs: [(&str, Option<SPSUnknown>, Setter); 5] = [
+            ("Cross section", Some(SPSUnknown::CrossSection), |s, v| {
+                s.cross_section = v
+            }),
+            ("Target density", Some(SPSUnknown::TargetDensity), |s, v| {
+                s.target_density = v
+            }),
+            ("Beam current", Some(SPSUnknown::BeamCurrent), |s, v| {
+                s.beam_current = v
+            }),
+            ("Slit settings", None, |s, v| s.slit_settings = v),
+            ("Beam time", Some(SPSUnknown::RunTime), |s, v| {
+                s.beam_time = v
+            }),
+        ];
+        for unknown in SPSUnknown::ALL {
+            for (parameter, unused_by, set) in inputs {
+                for bad in [0.0, -1.0] {
+                    let mut settings = SPSRunTimeSettings {
+                        unknown,
+                        ..Default::default()
+                    };
+                    set(&mut settings, bad);
+                    let solution = settings.solve();
+                    if unused_by == Some(unknown) {
+                        assert!(value(solution.unwrap()).is_finite());
+                    } else {
+                        assert!(
+                            matches!(
+                                solution,
+                                Err(EstimateError::NotPositive { parameter: p, .. }) if p == parameter
+                            ),
+                            "{parameter} = {bad} for {unknown:?}: {solution:?}"
+                        );
+                    }
+                }
+            }
+        }
+        let negative = SPSRunTimeSettings {
+            desired_counts: -1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            negative.solve(),
+            Err(EstimateError::Negative {
+                parameter: "Counts",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn centre_of_mass_cross_sections_change_the_rate_by_the_jacobian() {
+        // 12C(d,p)13C with the entered cross section in either frame
+        let mut settings = SPSRunTimeSettings {
+            target: "12C".to_string(),
+            ..Default::default()
+        };
+        settings.reaction.ejectile = "p".to_string();
+        assert_eq!(settings.cross_section_jacobian(), Ok(1.0));
+        let lab = settings.calculate_beam_time().unwrap().seconds;
+
+        settings.cross_section_frame = Frame::CenterOfMass;
+        let jacobian = settings.cross_section_jacobian().unwrap();
+        let state = settings.frame_kinematics().unwrap().unwrap();
+        assert_eq!(jacobian, state.jacobian);
+        assert!(jacobian > 1.05, "{jacobian}");
+        let cm = settings.calculate_beam_time().unwrap().seconds;
+        assert_close(cm * jacobian, lab, 1e-12);
+
+        // a model cross section gives the same rate in either frame
+        settings.cross_section_source = CrossSectionSource::Rutherford;
+        let rutherford = settings.rutherford().unwrap();
+        assert_eq!(settings.cross_section_jacobian(), Ok(rutherford.jacobian));
+        assert_close(
+            settings.cross_section_value().unwrap() * rutherford.jacobian,
+            rutherford.lab * 1e3,
+            1e-12,
+        );
+        let cm = settings.calculate_beam_time().unwrap().seconds;
+        settings.cross_section_frame = Frame::Lab;
+        assert_close(settings.calculate_beam_time().unwrap().seconds, cm, 1e-12);
+    }
+
+    #[test]
+    fn dwba_target_spin_must_be_a_multiple_of_a_half() {
+        let mut settings = SPSRunTimeSettings {
+            target: "40Ca".to_string(),
+            cross_section_source: CrossSectionSource::Dwba,
+            transfer: TransferState::from_orbit(0.0, "1f7/2").unwrap(),
+            ..Default::default()
+        };
+        settings.reaction.ejectile = "p".to_string();
+        assert!(settings.transfer().is_ok());
+        settings.transfer.initial_spin = 0.25;
+        assert_eq!(
+            settings.transfer().unwrap_err(),
+            EstimateError::NotHalfInteger {
+                parameter: "Target spin J_i",
+                value: 0.25
+            }
+        );
+        settings.transfer.initial_spin = -0.5;
+        assert_eq!(
+            settings.transfer().unwrap_err(),
+            EstimateError::Negative {
+                parameter: "Target spin J_i",
+                value: -0.5
+            }
+        );
+    }
+
+    #[test]
+    fn scaled_stripping_cross_section_has_the_statistical_factor() {
+        let mut settings = SPSRunTimeSettings {
+            cross_section_source: CrossSectionSource::Scaled,
+            scaling: ScaledCrossSection {
+                single_particle: 25.0,
+                spectroscopic_factor: 0.4,
+                initial_spin: 1.5,
+                final_spin: 2.5,
+                transfer: TransferType::Stripping,
+            },
+            ..Default::default()
+        };
+        // (2·5/2 + 1)/(2·3/2 + 1) · 0.4 · 25 = 6/4 · 10
+        assert_close(settings.scaling.statistical_factor(), 1.5, 1e-12);
+        assert_close(settings.cross_section_value().unwrap(), 15.0, 1e-12);
+        assert_eq!(
+            settings.scaling.explanation("µb/sr"),
+            "(2J_f + 1)/(2J_i + 1) · S · σ_sp = (2·5/2 + 1)/(2·3/2 + 1) · 0.4 · 25 µb/sr = 15.0000 µb/sr"
+        );
+        settings.scaling.final_spin = -0.5;
+        assert!(settings.cross_section_value().is_err());
+    }
+
+    #[test]
+    fn scaled_spins_must_be_multiples_of_a_half() {
+        let mut settings = SPSRunTimeSettings {
+            cross_section_source: CrossSectionSource::Scaled,
+            ..Default::default()
+        };
+        assert!(settings.calculate_beam_time().is_ok());
+        settings.scaling.final_spin = 1.3;
+        assert_eq!(
+            settings.calculate_beam_time(),
+            Err(EstimateError::NotHalfInteger {
+                parameter: "Final spin J_f",
+                value: 1.3
+            })
+        );
+    }
+
+    #[test]
+    fn scaled_pickup_cross_section_is_s_times_single_particle() {
+        let settings = SPSRunTimeSettings {
+            cross_section_source: CrossSectionSource::Scaled,
+            scaling: ScaledCrossSection {
+                single_particle: 25.0,
+                spectroscopic_factor: 0.4,
+                initial_spin: 1.5,
+                final_spin: 2.5,
+                transfer: TransferType::Pickup,
+            },
+            ..Default::default()
+        };
+        assert_eq!(settings.scaling.statistical_factor(), 1.0);
+        assert_close(settings.cross_section_value().unwrap(), 10.0, 1e-12);
+        assert_eq!(
+            settings.scaling.explanation("µb/sr"),
+            "S · σ_sp = 0.4 · 25 µb/sr = 10.0000 µb/sr"
+        );
+    }
+
+    /// 40Ca(d,p)41Ca to the 1f7/2 ground state at 16 MeV.
+    fn ca40_dp() -> SPSRunTimeSettings {
+        let mut settings = SPSRunTimeSettings {
+            target: "40Ca".to_string(),
+            cross_section_source: CrossSectionSource::Dwba,
+            transfer: TransferState::from_orbit(0.0, "1f7/2").unwrap(),
+            ..Default::default()
+        };
+        settings.reaction.ejectile = "p".to_string();
+        settings
+    }
+
+    #[test]
+    fn memo_calculates_once_per_inputs() {
+        let memo = Memo::default();
+        let mut calls = 0;
+        let mut get = |key: i32| {
+            memo.get_or_calculate(key, || {
+                calls += 1;
+                Ok(2 * key)
+            })
+        };
+        assert_eq!(get(1), Ok(2));
+        assert_eq!(get(1), Ok(2));
+        assert_eq!(get(2), Ok(4));
+        assert_eq!(get(1), Ok(2));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn elastic_scattering_follows_edits_of_its_inputs() {
+        let mut settings = SPSRunTimeSettings {
+            target: "208Pb".to_string(),
+            ..Default::default()
+        };
+        let elastic = settings.elastic_scattering().unwrap();
+        settings.reaction.angle = 30.0;
+        assert_eq!(settings.elastic_scattering(), Ok(elastic.clone()));
+
+        let copy = settings.clone();
+        settings.reaction.beam_energy = 12.0;
+        let lower = settings.elastic_scattering().unwrap();
+        assert_ne!(lower, elastic);
+        assert_eq!(lower.beam_energy, 12.0);
+        assert_eq!(copy.elastic_scattering(), Ok(elastic));
+    }
+
+    #[test]
+    fn transfer_follows_edits_of_its_inputs() {
+        let mut settings = ca40_dp();
+        let first = settings.transfer().unwrap();
+        let cross_section = settings.cross_section_value().unwrap();
+        settings.reaction.angle = 30.0;
+        assert_eq!(settings.transfer(), Ok(first.clone()));
+        assert_ne!(settings.cross_section_value(), Ok(cross_section));
+
+        let copy = settings.clone();
+        settings.transfer = TransferState::from_orbit(2.0, "2p3/2").unwrap();
+        let second = settings.transfer().unwrap();
+        let reaction = settings.reaction().unwrap().unwrap();
+        assert_eq!(
+            second,
+            Transfer::new(reaction, 16.0, &settings.transfer).unwrap()
+        );
+        assert_ne!(second, first);
+        assert_eq!(copy.transfer(), Ok(first));
     }
 }
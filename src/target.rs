@@ -0,0 +1,479 @@
+//! Targets given as chemical formulas, e.g. `208Pb`, `natPb`, `6LiF`, `CD2` or `Ca(OH)2`.
+//!
+//! A mass number in front of a symbol selects that isotope, `D` and `T` stand for ²H and ³H,
+//! and every other element has its natural isotopic composition (`nat` may be written out).
+//! Counts may be fractional, e.g. `TiO1.9`. An [`Assay`] gives the composition of an enriched
+//! element instead.
+
+use super::nuclide::{self, Nuclide};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TargetError {
+    Empty,
+    UnexpectedCharacter(char),
+    UnbalancedParentheses,
+    UnknownElement(String),
+    UnknownNuclide(String),
+    /// The element has no naturally occurring isotopes, so its mass number must be given.
+    NotNatural(String),
+    /// The reaction nuclide does not occur in the target.
+    NotInTarget(String),
+    /// The assay of the element has negative or no abundances.
+    InvalidAssay(String),
+}
+
+impl std::fmt::Display for TargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TargetError::Empty => write!(f, "target formula is empty"),
+            TargetError::UnexpectedCharacter(c) => {
+                write!(f, "unexpected '{c}' in target formula")
+            }
+            TargetError::UnbalancedParentheses => {
+                write!(f, "unbalanced parentheses in target formula")
+            }
+            TargetError::UnknownElement(symbol) => write!(f, "unknown element '{symbol}'"),
+            TargetError::UnknownNuclide(name) => write!(f, "{name} is not in the nuclide table"),
+            TargetError::NotNatural(symbol) => write!(
+                f,
+                "{symbol} has no natural isotopes, give a mass number (e.g. 239Pu)"
+            ),
+            TargetError::NotInTarget(name) => write!(f, "{name} does not occur in the target"),
+            TargetError::InvalidAssay(symbol) => write!(
+                f,
+                "the {symbol} assay needs non-negative abundances that add up to more than zero"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TargetError {}
+
+/// Isotopic composition of one element of an enriched target, e.g. from the supplier's assay.
+/// Abundances are normalized to their total, so an assay adding up to 99.9 % is fine.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Assay {
+    pub element: String,
+    pub isotopes: Vec<AssayIsotope>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct AssayIsotope {
+    pub a: i32,
+    pub percent: f64,
+}
+
+/// Proton number of an element symbol as written in a formula, e.g. `N` but not `n`.
+fn element_z(symbol: &str) -> Option<i32> {
+    (1..=118).find(|&z| nuclide::element_symbol(z) == Some(symbol))
+}
+
+impl Assay {
+    /// Assay listing every isotope of element `z` in the nuclide table at its natural
+    /// abundance, as a starting point for entering an enrichment.
+    pub fn natural(z: i32) -> Self {
+        Self {
+            element: nuclide::element_symbol(z).unwrap_or("?").to_string(),
+            isotopes: nuclide::isotopes(z)
+                .map(|n| AssayIsotope {
+                    a: n.a,
+                    percent: n.abundance,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn z(&self) -> Result<i32, TargetError> {
+        element_z(&self.element).ok_or_else(|| TargetError::UnknownElement(self.element.clone()))
+    }
+
+    pub fn total(&self) -> f64 {
+        self.isotopes.iter().map(|isotope| isotope.percent).sum()
+    }
+
+    /// Nuclides of the assay with their fraction of the element.
+    pub fn fractions(&self) -> Result<Vec<(&'static Nuclide, f64)>, TargetError> {
+        let z = self.z()?;
+        let total = self.total();
+        if total.is_nan()
+            || total <= 0.0
+            || self.isotopes.iter().any(|isotope| isotope.percent < 0.0)
+        {
+            return Err(TargetError::InvalidAssay(self.element.clone()));
+        }
+        self.isotopes
+            .iter()
+            .filter(|isotope| isotope.percent > 0.0)
+            .map(|isotope| {
+                let nuclide = nuclide::find(z, isotope.a).ok_or_else(|| {
+                    TargetError::UnknownNuclide(format!("{}{}", isotope.a, self.element))
+                })?;
+                Ok((nuclide, isotope.percent / total))
+            })
+            .collect()
+    }
+}
+
+/// One element of a formula.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Component {
+    pub z: i32,
+    /// Mass number of an isotopically pure component, `None` for natural composition.
+    pub a: Option<i32>,
+    /// Atoms per formula unit.
+    pub atoms: f64,
+}
+
+impl Component {
+    pub fn symbol(&self) -> &'static str {
+        nuclide::element_symbol(self.z).unwrap_or("?")
+    }
+
+    /// Isotopes making up the component, with atoms per formula unit. An assay of the element
+    /// replaces its natural or formula composition.
+    fn isotopes(&self, assays: &[Assay]) -> Result<Vec<IsotopeShare>, TargetError> {
+        for assay in assays {
+            if assay.z()? == self.z {
+                return Ok(assay
+                    .fractions()?
+                    .into_iter()
+                    .map(|(nuclide, fraction)| IsotopeShare {
+                        nuclide,
+                        atoms: self.atoms * fraction,
+                    })
+                    .collect());
+            }
+        }
+        match self.a {
+            Some(a) => {
+                let nuclide = nuclide::find(self.z, a)
+                    .ok_or_else(|| TargetError::UnknownNuclide(format!("{a}{}", self.symbol())))?;
+                Ok(vec![IsotopeShare {
+                    nuclide,
+                    atoms: self.atoms,
+                }])
+            }
+            None => {
+                let isotopes: Vec<IsotopeShare> = nuclide::isotopes(self.z)
+                    .filter(|n| n.is_natural())
+                    .map(|nuclide| IsotopeShare {
+                        nuclide,
+                        atoms: self.atoms * nuclide.abundance / 100.0,
+                    })
+                    .collect();
+                if isotopes.is_empty() {
+                    return Err(TargetError::NotNatural(self.symbol().to_string()));
+                }
+                Ok(isotopes)
+            }
+        }
+    }
+}
+
+/// Atoms of one nuclide per formula unit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IsotopeShare {
+    pub nuclide: &'static Nuclide,
+    pub atoms: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Formula {
+    pub components: Vec<Component>,
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    formula: &'a str,
+}
+
+impl Parser<'_> {
+    fn number(&mut self) -> Option<&str> {
+        let start = self.chars.peek()?.0;
+        let mut end = start;
+        while let Some(&(i, c)) = self.chars.peek() {
+            if !(c.is_ascii_digit() || c == '.') {
+                break;
+            }
+            end = i + c.len_utf8();
+            self.chars.next();
+        }
+        (end > start).then(|| &self.formula[start..end])
+    }
+
+    fn count(&mut self) -> Result<f64, TargetError> {
+        match self.number() {
+            Some(number) => number
+                .parse()
+                .map_err(|_| TargetError::UnexpectedCharacter('.')),
+            None => Ok(1.0),
+        }
+    }
+
+    /// Parses components until the end of the formula or a closing parenthesis.
+    fn group(&mut self, depth: usize) -> Result<Vec<Component>, TargetError> {
+        let mut components = vec![];
+        while let Some(&(_, c)) = self.chars.peek() {
+            match c {
+                ' ' | '·' => {
+                    self.chars.next();
+                }
+                '(' => {
+                    self.chars.next();
+                    let inner = self.group(depth + 1)?;
+                    if self.chars.next().map(|(_, c)| c) != Some(')') {
+                        return Err(TargetError::UnbalancedParentheses);
+                    }
+                    let count = self.count()?;
+                    components.extend(inner.into_iter().map(|c| Component {
+                        atoms: c.atoms * count,
+                        ..c
+                    }));
+                }
+                ')' if depth > 0 => return Ok(components),
+                ')' => return Err(TargetError::UnbalancedParentheses),
+                _ => components.push(self.component()?),
+            }
+        }
+        if depth > 0 {
+            return Err(TargetError::UnbalancedParentheses);
+        }
+        Ok(components)
+    }
+
+    fn component(&mut self) -> Result<Component, TargetError> {
+        let mut natural = false;
+        if self.formula[self.chars.peek().map_or(0, |&(i, _)| i)..].starts_with("nat") {
+            natural = true;
+            self.chars.nth(2);
+        }
+        let a = match self.number() {
+            Some(_) if natural => return Err(TargetError::UnexpectedCharacter('n')),
+            Some(number) => Some(
+                number
+                    .parse::<i32>()
+                    .map_err(|_| TargetError::UnexpectedCharacter('.'))?,
+            ),
+            None => None,
+        };
+
+        let mut symbol = String::new();
+        match self.chars.next() {
+            Some((_, c)) if c.is_ascii_uppercase() => symbol.push(c),
+            Some((_, c)) => return Err(TargetError::UnexpectedCharacter(c)),
+            None => return Err(TargetError::Empty),
+        }
+        if let Some(&(_, c)) = self.chars.peek() {
+            if c.is_ascii_lowercase() {
+                symbol.push(c);
+                self.chars.next();
+            }
+        }
+
+        let (z, a) = match (symbol.as_str(), a) {
+            ("D", None) => (1, Some(2)),
+            ("T", None) => (1, Some(3)),
+            _ => {
+                let z = element_z(&symbol).ok_or(TargetError::UnknownElement(symbol))?;
+                (z, a)
+            }
+        };
+        Ok(Component {
+            z,
+            a,
+            atoms: self.count()?,
+        })
+    }
+}
+
+impl Formula {
+    pub fn parse(formula: &str) -> Result<Self, TargetError> {
+        let mut parser = Parser {
+            chars: formula.trim().char_indices().peekable(),
+            formula: formula.trim(),
+        };
+        let mut formula = Formula::default();
+        for component in parser.group(0)? {
+            match formula
+                .components
+                .iter_mut()
+                .find(|c| c.z == component.z && c.a == component.a)
+            {
+                Some(existing) => existing.atoms += component.atoms,
+                None => formula.components.push(component),
+            }
+        }
+        if formula.components.is_empty() {
+            return Err(TargetError::Empty);
+        }
+        Ok(formula)
+    }
+
+    /// Every nuclide in the target with its atoms per formula unit, using the assays of
+    /// enriched elements.
+    pub fn isotopes(&self, assays: &[Assay]) -> Result<Vec<IsotopeShare>, TargetError> {
+        let mut isotopes: Vec<IsotopeShare> = vec![];
+        for component in &self.components {
+            for share in component.isotopes(assays)? {
+                match isotopes.iter_mut().find(|s| s.nuclide == share.nuclide) {
+                    Some(existing) => existing.atoms += share.atoms,
+                    None => isotopes.push(share),
+                }
+            }
+        }
+        Ok(isotopes)
+    }
+
+    /// Mass of one formula unit in u, which is the molar mass in g/mol.
+    pub fn molar_mass(&self, assays: &[Assay]) -> Result<f64, TargetError> {
+        Ok(self
+            .isotopes(assays)?
+            .iter()
+            .map(|share| share.atoms * share.nuclide.mass)
+            .sum())
+    }
+
+    /// Atoms of every element per formula unit, e.g. 3 for CD2.
+    pub fn atoms(&self) -> f64 {
+        self.components.iter().map(|c| c.atoms).sum()
+    }
+
+    /// Atoms of `nuclide` per formula unit.
+    pub fn atoms_of(&self, nuclide: &Nuclide, assays: &[Assay]) -> Result<f64, TargetError> {
+        self.isotopes(assays)?
+            .iter()
+            .find(|share| share.nuclide == nuclide)
+            .map(|share| share.atoms)
+            .ok_or_else(|| TargetError::NotInTarget(nuclide.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn components(formula: &str) -> Vec<(i32, Option<i32>, f64)> {
+        Formula::parse(formula)
+            .unwrap()
+            .components
+            .iter()
+            .map(|c| (c.z, c.a, c.atoms))
+            .collect()
+    }
+
+    #[test]
+    fn formulas_are_parsed_into_elements_and_isotopes() {
+        assert_eq!(components("208Pb"), [(82, Some(208), 1.0)]);
+        assert_eq!(components("natPb"), [(82, None, 1.0)]);
+        assert_eq!(components("6LiF"), [(3, Some(6), 1.0), (9, None, 1.0)]);
+        assert_eq!(components("CD2"), [(6, None, 1.0), (1, Some(2), 2.0)]);
+        assert_eq!(
+            components("Ca(OH)2"),
+            [(20, None, 1.0), (8, None, 2.0), (1, None, 2.0)]
+        );
+        assert_eq!(components(" TiO1.9 "), [(22, None, 1.0), (8, None, 1.9)]);
+        // the same isotope twice is added up
+        assert_eq!(components("CH3CH3"), [(6, None, 2.0), (1, None, 6.0)]);
+    }
+
+    #[test]
+    fn malformed_formulas_are_rejected() {
+        for (formula, error) in [
+            ("", TargetError::Empty),
+            ("Xx", TargetError::UnknownElement("Xx".to_string())),
+            ("Ca(OH", TargetError::UnbalancedParentheses),
+            ("Pb)", TargetError::UnbalancedParentheses),
+            ("pb", TargetError::UnexpectedCharacter('p')),
+            ("nat208Pb", TargetError::UnexpectedCharacter('n')),
+        ] {
+            assert_eq!(Formula::parse(formula), Err(error), "{formula}");
+        }
+        let tc = Formula::parse("Tc").unwrap();
+        assert_eq!(
+            tc.isotopes(&[]),
+            Err(TargetError::NotNatural("Tc".to_string()))
+        );
+    }
+
+    #[test]
+    fn molar_mass_and_atoms_follow_the_isotopes() {
+        let cd2 = Formula::parse("CD2").unwrap();
+        let molar_mass = cd2.molar_mass(&[]).unwrap();
+        assert!(
+            (molar_mass - (12.011 + 2.0 * 2.0141)).abs() < 1e-2,
+            "{molar_mass}"
+        );
+        assert_eq!(cd2.atoms(), 3.0);
+        let deuteron = nuclide::find(1, 2).unwrap();
+        assert_eq!(cd2.atoms_of(deuteron, &[]), Ok(2.0));
+        let proton = nuclide::find(1, 1).unwrap();
+        assert_eq!(
+            cd2.atoms_of(proton, &[]),
+            Err(TargetError::NotInTarget(proton.name()))
+        );
+    }
+
+    #[test]
+    fn assays_replace_the_natural_composition_and_are_normalized() {
+        let assay = Assay {
+            element: "Ca".to_string(),
+            isotopes: vec![
+                AssayIsotope {
+                    a: 48,
+                    percent: 95.9,
+                },
+                AssayIsotope {
+                    a: 40,
+                    percent: 4.0,
+                },
+            ],
+        };
+        let calcium = Formula::parse("CaCO3").unwrap();
+        let ca48 = nuclide::find(20, 48).unwrap();
+        let atoms = calcium.atoms_of(ca48, &[assay.clone()]).unwrap();
+        assert!((atoms - 95.9 / 99.9).abs() < 1e-12);
+        assert!(calcium.atoms_of(ca48, &[]).unwrap() < 0.01);
+
+        let negative = Assay {
+            isotopes: vec![AssayIsotope {
+                a: 48,
+                percent: -1.0,
+            }],
+            ..assay.clone()
+        };
+        assert_eq!(
+            negative.fractions(),
+            Err(TargetError::InvalidAssay("Ca".to_string()))
+        );
+        let nitrogen = Assay {
+            element: "N".to_string(),
+            isotopes: vec![AssayIsotope {
+                a: 15,
+                percent: 99.0,
+            }],
+        };
+        assert_eq!(nitrogen.z(), Ok(7));
+        let melamine = Formula::parse("C3H6N6").unwrap();
+        let n15 = nuclide::find(7, 15).unwrap();
+        assert_eq!(melamine.atoms_of(n15, &[nitrogen.clone()]), Ok(6.0));
+        let neutron = Assay {
+            element: "n".to_string(),
+            ..nitrogen
+        };
+        assert_eq!(
+            neutron.z(),
+            Err(TargetError::UnknownElement("n".to_string()))
+        );
+
+        let unknown = Assay {
+            element: "Qq".to_string(),
+            ..assay
+        };
+        assert_eq!(
+            unknown.fractions(),
+            Err(TargetError::UnknownElement("Qq".to_string()))
+        );
+    }
+}
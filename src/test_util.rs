@@ -0,0 +1,10 @@
+//! Helpers shared by the unit tests.
+
+/// Asserts that `actual` differs from `expected` by at most `tolerance` times `expected`.
+#[track_caller]
+pub fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance * expected.abs(),
+        "{actual} != {expected} within {tolerance:e}"
+    );
+}
@@ -0,0 +1,145 @@
+use crate::cebra::{CeBrARunTimeSettings, Detector, Efficiency};
+
+impl Efficiency {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.add(
+            egui::DragValue::new(&mut self.a)
+                // .prefix("a = ")
+                .speed(0.1)
+                .range(0.0..=f64::INFINITY),
+        )
+        .on_hover_text("Efficiency = a * exp(-energy / b) + c * exp(-energy / d)");
+        ui.add(
+            egui::DragValue::new(&mut self.b)
+                // .prefix("b = ")
+                .speed(10.0)
+                .range(0.0..=f64::INFINITY),
+        )
+        .on_hover_text("Efficiency = a * exp(-energy / b) + c * exp(-energy / d)");
+        ui.add(
+            egui::DragValue::new(&mut self.c)
+                // .prefix("c = ")
+                .speed(0.1)
+                .range(0.0..=f64::INFINITY),
+        )
+        .on_hover_text("Efficiency = a * exp(-energy / b) + c * exp(-energy / d)");
+        ui.add(
+            egui::DragValue::new(&mut self.d)
+                // .prefix("d = ")
+                .speed(10.0)
+                .range(0.0..=f64::INFINITY),
+        )
+        .on_hover_text("Efficiency = a * exp(-energy / b) + c * exp(-energy / d)");
+    }
+}
+
+impl CeBrARunTimeSettings {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("cebra_runtime_settings_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.heading("CeBrA");
+                ui.end_row();
+                ui.label("Particle Counts:")
+                    .on_hover_text("Number of particles detected in the excited state");
+                ui.add(
+                    egui::DragValue::new(&mut self.n_particle_counts)
+                        .speed(1.0)
+                        .range(0..=i64::MAX),
+                );
+                ui.end_row();
+
+                ui.label("γ Decay");
+                ui.label("Energy");
+                ui.label("Intensity")
+                    .on_hover_text("Absolute intensity of the decay");
+                ui.end_row();
+                ui.label("");
+                ui.add(
+                    egui::DragValue::new(&mut self.decay.energy)
+                        .suffix(" keV")
+                        .speed(10.0)
+                        .range(0.0..=f64::INFINITY),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.decay.absolute_intensity)
+                        .suffix(" %")
+                        .speed(0.1)
+                        .range(0.0..=100.0),
+                );
+
+                ui.end_row();
+
+                ui.heading("Detectors");
+
+                if ui.button("+").clicked() {
+                    self.detectors.push(Detector {
+                        name: format!("Detector {}", self.detectors.len() + 1),
+                        efficiency: Efficiency::default(),
+                    });
+                }
+
+                if ui.button("REU-2023").on_hover_text("Efficiency values for the 52Cr(d,pγ)53Cr and 34S(d,pγ)35S experiments that took place in the summer of 2023 during the REU.").clicked() {
+                    self.detectors = CeBrARunTimeSettings::reu_2023_detectors();
+                }
+
+                if ui.button("Summer-2022").on_hover_text("Efficiency values for the 49Ti(d,pγ)50Ti and 61Ni(d,pγ)62Ni experiments that took place in the summer of 2022 during CeBrA's commissioning run.").clicked() {
+                    self.detectors = CeBrARunTimeSettings::summer_2022_detectors();
+                }
+
+
+                ui.end_row();
+
+                ui.label("Name");
+                ui.label("a");
+                ui.label("b");
+                ui.label("c");
+                ui.label("d");
+                ui.label("ε(γ)");
+                ui.label("Counts");
+                ui.label("");
+                ui.end_row();
+
+                let mut index_to_remove = None;
+
+                let mut total_efficiency = 0.0;
+                let mut total_counts = 0.0;
+                for (index, detector) in self.detectors.iter_mut().enumerate() {
+                    ui.text_edit_singleline(&mut detector.name);
+                    detector.efficiency.ui(ui);
+
+                    let efficiency = detector.efficiency.calculate_efficiency(self.decay.energy);
+                    ui.label(format!("{:.2} %", efficiency));
+
+                    let expected_counts = self.decay.expected_counts(self.n_particle_counts, efficiency);
+
+                    ui.label(format!("{:.0}", expected_counts)).on_hover_text(
+                        "Estimated counts = Particle counts * Absolute intensity [%] /100 * Efficiency [%] /100",
+                    );
+
+                    if ui.button("-").clicked() {
+                        index_to_remove = Some(index);
+                    }
+                    ui.end_row();
+
+                    total_efficiency += efficiency;
+                    total_counts += expected_counts;
+
+                }
+
+                if let Some(index) = index_to_remove {
+                    self.detectors.remove(index);
+                }
+
+                ui.label("");
+                ui.label("");
+                ui.label("");
+                ui.label("");
+                ui.label("Total");
+                ui.label(format!("{:.2} %", total_efficiency));
+                ui.label(format!("{:.0}", total_counts));
+                ui.end_row();
+
+            });
+    }
+}
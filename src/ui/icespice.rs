@@ -0,0 +1,63 @@
+use crate::icespice::ICESPICERunTimeSettings;
+
+impl ICESPICERunTimeSettings {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("icespice_runtime_settings_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.heading("ICESPICE");
+                ui.end_row();
+
+                ui.label("Particle Counts:")
+                    .on_hover_text("Number of particles detected in the excited state.");
+                ui.add(
+                    egui::DragValue::new(&mut self.n_particle_counts)
+                        .speed(1.0)
+                        .range(0..=i64::MAX),
+                );
+                ui.end_row();
+
+                ui.label("Transmission Probability:").on_hover_text(
+                    "Probability of the particle passing through ICESPICE to the detector in 4π.",
+                );
+                ui.add(egui::DragValue::new(&mut self.transmission_prob)
+                    .suffix(" %")
+                    .speed(0.1)
+                    .range(0.0..=100.0),
+                );
+                ui.end_row();
+
+                ui.label("Detector Efficiency:").on_hover_text(
+                    "Efficiency of the detector for an electron to deposit its full energy.",
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.detector_efficiency)
+                    .suffix(" %")
+                    .speed(0.1)
+                    .range(0.0..=100.0),
+                );
+                ui.end_row();
+
+                ui.label("Branching Ratio:").on_hover_text("The branching ratio is the fraction of decays that proceed through a particular transition.");
+                ui.add(
+                    egui::DragValue::new(&mut self.branching_ratio)
+                    .suffix(" %")
+                    .speed(1.0)
+                    .range(0.0..=100.0),
+                );
+                ui.end_row();
+
+                ui.label("Conversion Coefficient (α):").on_hover_text("The internal conversion coefficient (α) is the ratio of the number of conversion electrons emitted to the number of gamma rays emitted for a particular transition. ICCs depend on the energy of the transition and the atomic number of the nucleus. These coefficients can be obtained from tables or calculated using theoretical models.");
+                ui.add(egui::DragValue::new(&mut self.conversion_coefficient));
+                ui.end_row();
+
+                ui.label("Estimated Number of\nDetected Conversion Electrons:")
+                .on_hover_text("Formula: Particle Counts * (Branching Ratio [%] / 100) * α * (Transmission Probability [%] / 100) * (Detector Efficiency [%] / 100)");
+                match self.calculate_conversion_electrons() {
+                    Ok(estimate) => ui.label(format!("{:.0}", estimate.detected_conversion_electrons)),
+                    Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                };
+                ui.end_row();
+            });
+    }
+}
@@ -0,0 +1,7 @@
+//! egui widgets for the estimator settings, only built with the `gui` feature.
+
+mod cebra;
+mod icespice;
+mod nuclide;
+mod sps;
+mod units;
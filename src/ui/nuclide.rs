@@ -0,0 +1,104 @@
+use crate::nuclide::{self, Nuclide};
+use eframe::egui::{self};
+
+/// Entry chosen in a [`nuclide_picker`].
+pub(super) enum NuclidePick {
+    /// Element with its natural isotopic composition.
+    Natural(i32),
+    Nuclide(&'static Nuclide),
+}
+
+/// Elements whose natural composition matches `query`, e.g. `pb` or `natPb`.
+fn natural_matches(query: &str) -> Vec<i32> {
+    let query = query.trim();
+    let symbol = query.strip_prefix("nat").unwrap_or(query);
+    if symbol.is_empty() || !symbol.chars().all(|c| c.is_ascii_alphabetic()) {
+        return vec![];
+    }
+    (1..=118)
+        .filter(|&z| {
+            nuclide::element_symbol(z).is_some_and(|s| {
+                s.to_ascii_lowercase()
+                    .starts_with(&symbol.to_ascii_lowercase())
+            }) && nuclide::natural_molar_mass(z).is_some()
+        })
+        .collect()
+}
+
+/// Text field listing the matching nuclides below it while typing. Natural elements are
+/// offered too when `natural` is set. Returns the entry the user clicked.
+pub(super) fn nuclide_picker(
+    ui: &mut egui::Ui,
+    id_source: &str,
+    text: &mut String,
+    natural: bool,
+) -> Option<NuclidePick> {
+    let response = ui.add(
+        egui::TextEdit::singleline(text)
+            .hint_text("e.g. 208Pb")
+            .desired_width(80.0),
+    );
+    let popup_id = ui.make_persistent_id(id_source);
+    if response.gained_focus() || response.changed() {
+        ui.memory_mut(|memory| memory.open_popup(popup_id));
+    }
+
+    let mut picked = None;
+    egui::popup_below_widget(
+        ui,
+        popup_id,
+        &response,
+        egui::PopupCloseBehavior::CloseOnClickOutside,
+        |ui| {
+            ui.set_min_width(220.0);
+            egui::ScrollArea::vertical()
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    if natural {
+                        for z in natural_matches(text) {
+                            let symbol = nuclide::element_symbol(z).unwrap_or("?");
+                            let mass = nuclide::natural_molar_mass(z).unwrap_or(0.0);
+                            if ui
+                                .selectable_label(false, format!("nat{symbol}  {mass:.4} g/mol"))
+                                .clicked()
+                            {
+                                picked = Some(NuclidePick::Natural(z));
+                            }
+                        }
+                    }
+                    for nuclide in nuclide::search(text).into_iter().take(60) {
+                        let label = if nuclide.is_natural() {
+                            format!(
+                                "{}  {:.4} u  {} %",
+                                nuclide.name(),
+                                nuclide.mass,
+                                nuclide.abundance
+                            )
+                        } else {
+                            format!("{}  {:.4} u", nuclide.name(), nuclide.mass)
+                        };
+                        if ui.selectable_label(false, label).clicked() {
+                            picked = Some(NuclidePick::Nuclide(nuclide));
+                        }
+                    }
+                });
+        },
+    );
+    if picked.is_some() {
+        ui.memory_mut(|memory| memory.close_popup());
+    }
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natural_elements_match_their_symbol() {
+        assert_eq!(natural_matches("natN"), [7, 10, 11, 28, 41, 60]);
+        assert_eq!(natural_matches("pb"), [82]);
+        assert!(natural_matches("Tc").is_empty());
+        assert!(natural_matches("208Pb").is_empty());
+    }
+}
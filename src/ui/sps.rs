@@ -0,0 +1,1685 @@
+use crate::deck::DeckCode;
+use crate::dwba::{TransferState, Vertex};
+use crate::error::EstimateError;
+use crate::kinematics::Frame;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::listing::ImportedCrossSections;
+use crate::listing::ListingFormat;
+use crate::nuclide;
+use crate::optical::GlobalPotential;
+use crate::planner::{CampaignAngle, CampaignGoal, PlanState};
+use crate::sps::{
+    spin_name, CrossSectionSource, SPSRunTimeSettings, SPSSolution, SPSUnknown, TransferType,
+    MAX_SLIT_SETTINGS,
+};
+use crate::stopping::MAX_PATH_ANGLE;
+use crate::target::{Assay, Formula};
+use crate::units::{SolidAngleUnit, ThicknessUnit, Unit};
+use eframe::egui::{self};
+
+use super::nuclide::{nuclide_picker, NuclidePick};
+use super::units::{quantity_drag_value, unit_selector};
+
+/// Shows the solved value in place of the input of the unknown quantity.
+fn solution_label(ui: &mut egui::Ui, solution: &Result<SPSSolution, EstimateError>) {
+    match solution {
+        Ok(solution) => ui.strong(solution.to_string()),
+        Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+    }
+    .on_hover_text("Calculated from the other inputs.");
+}
+
+/// Nodes, l and j of the transferred nucleon, followed by the orbit they make.
+fn orbit_ui(ui: &mut egui::Ui, id: impl std::hash::Hash, state: &mut TransferState) {
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::DragValue::new(&mut state.nodes)
+                .prefix("nodes ")
+                .range(0..=10),
+        )
+        .on_hover_text("Radial nodes of the bound state, without the origin.");
+        if ui
+            .add(
+                egui::DragValue::new(&mut state.l)
+                    .prefix("l = ")
+                    .range(0..=10),
+            )
+            .changed()
+        {
+            state.j = state.l as f64 + 0.5;
+        }
+        let l = state.l as f64;
+        egui::ComboBox::from_id_source(id)
+            .selected_text(format!("j = {}/2", (2.0 * state.j).round()))
+            .show_ui(ui, |ui| {
+                for j in [l - 0.5, l + 0.5].into_iter().filter(|&j| j > 0.0) {
+                    ui.selectable_value(&mut state.j, j, format!("{}/2", 2.0 * j));
+                }
+            });
+        ui.label(state.orbit());
+    });
+}
+
+impl SPSRunTimeSettings {
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let solution = self.solve();
+        let molar_mass = self.molar_mass().unwrap_or(self.target_molar_mass);
+        let mass_density = self.target_mass_density;
+        let formula_atoms = self.formula_atoms().unwrap_or(1.0);
+        let charge_state = self.q_beam as f64;
+
+        egui::Grid::new("sps_runtime_settings_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.heading("SE-SPS");
+                ui.end_row();
+
+                ui.label("Solve For:");
+                egui::ComboBox::from_id_source("sps_unknown")
+                    .selected_text(self.unknown.name())
+                    .show_ui(ui, |ui| {
+                        for unknown in SPSUnknown::ALL {
+                            ui.selectable_value(&mut self.unknown, unknown, unknown.name());
+                        }
+                    }).response.on_hover_text("Quantity calculated from the other inputs.");
+                ui.end_row();
+
+                ui.label("Cross Section:");
+                ui.horizontal(|ui| {
+                    if self.unknown == SPSUnknown::CrossSection {
+                        solution_label(ui, &solution);
+                    } else if self.cross_section_source == CrossSectionSource::Manual {
+                        ui.add(quantity_drag_value(&mut self.cross_section));
+                    } else {
+                        match self.cross_section_value() {
+                            Ok(value) => ui.strong(format!("{value:.4e}")),
+                            Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                        }.on_hover_text(format!("From the {} cross section.", self.cross_section_source.name()));
+                    }
+                    let previous_unit = self.units.cross_section;
+                    unit_selector(ui, "sps_cross_section_unit", &mut self.cross_section, &mut self.units.cross_section,
+                        |value, from, to| Ok(to.from_base(from.to_base(value))));
+                    if self.units.cross_section != previous_unit {
+                        self.scaling.single_particle = self.units.cross_section.from_base(previous_unit.to_base(self.scaling.single_particle));
+                    }
+                    self.cross_section_frame_ui(ui);
+                });
+                ui.end_row();
+
+                if self.unknown != SPSUnknown::CrossSection {
+                    ui.label("Cross Section Source:");
+                    egui::ComboBox::from_id_source("sps_cross_section_source")
+                        .selected_text(self.cross_section_source.name())
+                        .show_ui(ui, |ui| {
+                            for source in CrossSectionSource::ALL {
+                                ui.selectable_value(&mut self.cross_section_source, source, source.name());
+                            }
+                        }).response.on_hover_text("Enter the cross section or take it from a model at the beam energy and spectrograph angle under Reaction Kinematics.");
+                    ui.end_row();
+                }
+
+                if self.cross_section_source == CrossSectionSource::Rutherford {
+                    ui.label("Rutherford:");
+                    match self.rutherford() {
+                        Ok(rutherford) => {
+                            ui.vertical(|ui| {
+                                ui.label(format!(
+                                    "{:.4} mb/sr (Lab) | {:.4} mb/sr (CM) | θcm = {:.2}° | Ecm = {:.3} MeV",
+                                    rutherford.lab, rutherford.cm, rutherford.cm_angle, rutherford.cm_energy
+                                ));
+                                if rutherford.above_barrier {
+                                    ui.colored_label(
+                                        ui.visuals().warn_fg_color,
+                                        format!("Above the Coulomb barrier ({:.2} MeV in the lab), pure Rutherford scattering is unreliable.", rutherford.barrier),
+                                    );
+                                }
+                            });
+                        }
+                        Err(e) => {
+                            ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                        }
+                    }
+                    ui.end_row();
+                }
+
+                if self.cross_section_source == CrossSectionSource::OpticalModel {
+                    ui.label("Optical Model:");
+                    match self.optical_model() {
+                        Ok(point) => ui.label(format!(
+                            "{:.4} mb/sr (Lab) | {:.4} mb/sr (CM) | σ/σR = {:.4} | θcm = {:.2}°",
+                            point.lab, point.cm, point.ratio, point.cm_angle
+                        )),
+                        Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                    };
+                    ui.end_row();
+                }
+
+                if self.cross_section_source == CrossSectionSource::Dwba {
+                    ui.label("DWBA:");
+                    match self.dwba() {
+                        Ok(point) => ui.label(format!(
+                            "{:.4} mb/sr (Lab) | {:.4} mb/sr (CM) | θcm = {:.2}° | {} at {} MeV",
+                            point.lab, point.cm, point.cm_angle, self.transfer.orbit(), self.transfer.excitation
+                        )),
+                        Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                    };
+                    ui.end_row();
+                }
+
+                if self.cross_section_source == CrossSectionSource::Imported {
+                    ui.label("Imported:");
+                    match self.imported() {
+                        Ok(point) => ui.label(format!(
+                            "{:.4} mb/sr (Lab) | {:.4} mb/sr (CM) | θcm = {:.2}° | {}",
+                            point.lab, point.cm, point.cm_angle, point.state
+                        )),
+                        Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                    };
+                    ui.end_row();
+                }
+
+                if self.cross_section_source == CrossSectionSource::Scaled {
+                    ui.label("Single-Particle σ:");
+                    ui.add(
+                        quantity_drag_value(&mut self.scaling.single_particle)
+                            .suffix(format!(" {}", self.units.cross_section.symbol())),
+                    ).on_hover_text("Reference single-particle cross section σ_sp, e.g. from DWBA or a measured neighbouring state with S = 1.");
+                    ui.end_row();
+
+                    ui.label("Scaling:");
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source("sps_scaling_transfer")
+                            .selected_text(self.scaling.transfer.name())
+                            .show_ui(ui, |ui| {
+                                for transfer in TransferType::ALL {
+                                    ui.selectable_value(&mut self.scaling.transfer, transfer, transfer.name());
+                                }
+                            });
+                        ui.add(egui::DragValue::new(&mut self.scaling.spectroscopic_factor).speed(0.01).prefix("S = ").range(0.0..=f64::INFINITY));
+                        for (prefix, spin) in [("J_i = ", &mut self.scaling.initial_spin), ("J_f = ", &mut self.scaling.final_spin)] {
+                            if ui.add(
+                                egui::DragValue::new(spin)
+                                    .speed(0.05)
+                                    .prefix(prefix)
+                                    .range(0.0..=30.0)
+                                    .custom_formatter(|value, _| spin_name(value)),
+                            ).changed() {
+                                *spin = (2.0 * *spin).round() / 2.0;
+                            }
+                        }
+                    }).response.on_hover_text("Spectroscopic factor, target spin J_i and final-state spin J_f. Stripping carries the statistical factor (2J_f + 1)/(2J_i + 1), pickup none.");
+                    ui.end_row();
+
+                    ui.label("Scaled:");
+                    match self.scaled_cross_section() {
+                        Ok(_) => ui.label(self.scaling.explanation(self.units.cross_section.symbol())),
+                        Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                    };
+                    ui.end_row();
+                }
+
+                ui.label("Target:");
+                ui.horizontal(|ui| {
+                    match nuclide_picker(ui, "sps_target_picker", &mut self.target, true) {
+                        Some(NuclidePick::Natural(z)) => {
+                            self.target = format!("nat{}", nuclide::element_symbol(z).unwrap_or("?"));
+                        }
+                        Some(NuclidePick::Nuclide(nuclide)) => self.target = nuclide.name(),
+                        None => {}
+                    }
+                }).response.on_hover_text("Chemical formula of the target, e.g. 208Pb, natPb, 6LiF, CD2 or TiO2. A mass number selects an isotope, otherwise the natural composition is used. Leave empty to enter the molar mass by hand.");
+                ui.end_row();
+
+                if let Ok(Some(formula)) = self.formula() {
+                    ui.label("Reaction Nuclide:");
+                    let selected = if self.reaction_nuclide.is_empty() { "Whole target".to_string() } else { self.reaction_nuclide.clone() };
+                    egui::ComboBox::from_id_source("sps_reaction_nuclide")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.reaction_nuclide, String::new(), "Whole target");
+                            for share in formula.isotopes(&self.target_assays).unwrap_or_default() {
+                                let name = share.nuclide.name();
+                                ui.selectable_value(&mut self.reaction_nuclide, name.clone(), name);
+                            }
+                        }).response.on_hover_text("Nuclide the reaction is on. Its areal density follows from the target thickness and formula.");
+                    ui.end_row();
+                }
+
+                ui.label("Target Density:");
+                ui.horizontal(|ui| {
+                    if self.unknown == SPSUnknown::TargetDensity {
+                        solution_label(ui, &solution);
+                    } else {
+                        ui.add(quantity_drag_value(&mut self.target_density));
+                    }
+                    unit_selector(ui, "sps_target_density_unit", &mut self.target_density, &mut self.units.target_density,
+                        |value, from, to| to.from_base(from.to_base(value, molar_mass, mass_density, formula_atoms)?, molar_mass, mass_density, formula_atoms));
+                });
+                ui.end_row();
+
+                ui.label("Target Molar Mass:");
+                match self.formula() {
+                    Ok(None) => {
+                        ui.add(
+                            egui::DragValue::new(&mut self.target_molar_mass)
+                                .speed(1.0)
+                                .suffix(" g/mol")
+                                .range(0.0..=f64::INFINITY)
+                        );
+                    }
+                    Ok(Some(_)) => {
+                        match self.molar_mass() {
+                            Ok(molar_mass) => ui.label(format!("{molar_mass:.4} g/mol")).on_hover_text("Calculated from the target formula."),
+                            Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                        };
+                    }
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                    }
+                }
+                ui.end_row();
+
+                if self.units.target_density == ThicknessUnit::Nanometer {
+                    ui.label("Target Mass Density:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.target_mass_density)
+                            .speed(0.1)
+                            .suffix(" g/cm^3")
+                            .range(0.0..=f64::INFINITY)
+                    ).on_hover_text("Density of the target material, needed for a thickness in nm.");
+                    ui.end_row();
+                }
+
+                ui.label("Beam Current:");
+                ui.horizontal(|ui| {
+                    if self.unknown == SPSUnknown::BeamCurrent {
+                        solution_label(ui, &solution);
+                    } else {
+                        ui.add(quantity_drag_value(&mut self.beam_current))
+                            .on_hover_text("Beam current on target, as electrical nA, particle nA (pnA) or particles/s.");
+                    }
+                    unit_selector(ui, "sps_beam_current_unit", &mut self.beam_current, &mut self.units.beam_current,
+                        |value, from, to| to.from_base(from.to_base(value, charge_state)?, charge_state));
+                });
+                ui.end_row();
+
+                ui.label("Beam:");
+                let beam_picker = ui.make_persistent_id("sps_beam_picker");
+                let mut beam = if ui.memory(|memory| memory.is_popup_open(beam_picker)) {
+                    ui.data_mut(|data| data.get_temp::<String>(beam_picker)).unwrap_or_default()
+                } else {
+                    format!("{}{}", self.a_beam, nuclide::element_symbol(self.z_beam).unwrap_or("?"))
+                };
+                if let Some(NuclidePick::Nuclide(nuclide)) = nuclide_picker(ui, "sps_beam_picker", &mut beam, false) {
+                    self.z_beam = nuclide.z;
+                    self.a_beam = nuclide.a;
+                    self.q_beam = self.q_beam.min(nuclide.z);
+                }
+                ui.data_mut(|data| data.insert_temp(beam_picker, beam));
+                ui.end_row();
+
+                ui.label("Z Beam:");
+                ui.add(
+                    egui::DragValue::new(&mut self.z_beam)
+                        .speed(1.0)
+                        .prefix("Z = ")
+                        .range(1..=118) // Adjusted to allow minimum 1
+                ).on_hover_text("Proton number of the beam.");
+                ui.end_row();
+
+                ui.label("A Beam:");
+                ui.add(
+                    egui::DragValue::new(&mut self.a_beam)
+                        .speed(1.0)
+                        .prefix("A = ")
+                        .range(self.z_beam..=300)
+                ).on_hover_text("Mass number of the beam.");
+                ui.end_row();
+
+                ui.label("Charge State:");
+                ui.add(
+                    egui::DragValue::new(&mut self.q_beam)
+                        .speed(1.0)
+                        .prefix("q = ")
+                        .range(1..=self.z_beam)
+                ).on_hover_text("Charge state of the beam on the Faraday cup, e.g. 3 for 7Li3+. Electrical nA are divided by q to get particles.");
+                ui.end_row();
+
+                ui.label("Beam Intensity:");
+                match self.beam_intensity() {
+                    Ok(intensity) => ui.label(format!(
+                        "{} | {}",
+                        self.beam_species(),
+                        intensity.iter().map(ToString::to_string).collect::<Vec<_>>().join(" = ")
+                    )),
+                    Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                };
+                ui.end_row();
+
+                ui.label("Slit Settings:");
+                ui.horizontal(|ui| {
+                    let max_slit_settings = self.units.slit_settings.from_base(SolidAngleUnit::Millisteradian.to_base(MAX_SLIT_SETTINGS));
+                    ui.add(
+                        egui::DragValue::new(&mut self.slit_settings)
+                            .speed(max_slit_settings / 128.0)
+                            .range(0.0..=max_slit_settings)
+                    ).on_hover_text("Solid angle of the SE-SPS. Typical value is 4.62 msr. The SE-SPS has a max solid angle of 12.8 msr.");
+                    unit_selector(ui, "sps_slit_settings_unit", &mut self.slit_settings, &mut self.units.slit_settings,
+                        |value, from, to| Ok(to.from_base(from.to_base(value))));
+                });
+                ui.end_row();
+
+                ui.label("Counts:");
+                if self.unknown == SPSUnknown::Counts {
+                    solution_label(ui, &solution);
+                } else {
+                    ui.add(
+                        egui::DragValue::new(&mut self.desired_counts)
+                            .speed(1.0)
+                            .suffix(" counts")
+                            .range(0..=i64::MAX)
+                    ).on_hover_text("The desired number of counts in the peak of interest.");
+                }
+                ui.end_row();
+
+                if self.unknown == SPSUnknown::RunTime {
+                    ui.label("Estimated Time:");
+                    solution_label(ui, &solution);
+                } else {
+                    ui.label("Beam Time:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.beam_time)
+                            .speed(0.5)
+                            .suffix(" h")
+                            .range(0.0..=f64::INFINITY)
+                    ).on_hover_text("Available beam time on target.");
+                }
+                ui.end_row();
+        });
+
+        ui.collapsing("Reaction Kinematics", |ui| self.kinematics_ui(ui));
+        ui.collapsing("Ex Resolution", |ui| self.resolution_ui(ui));
+        ui.collapsing("Target Energy Loss", |ui| self.energy_loss_ui(ui));
+        ui.collapsing("Elastic Scattering", |ui| self.elastic_ui(ui));
+        ui.collapsing("Transfer (DWBA)", |ui| self.transfer_ui(ui));
+        ui.collapsing("Imported Cross Sections", |ui| self.imported_ui(ui));
+        ui.collapsing("DWBA Input Decks", |ui| self.deck_ui(ui));
+        ui.collapsing("Field Settings Plan", |ui| self.field_plan_ui(ui));
+        ui.collapsing("Angular Distribution", |ui| self.campaign_ui(ui));
+
+        if let Ok(Some(formula)) = self.formula() {
+            ui.collapsing("Enrichment", |ui| self.enrichment_ui(ui, &formula));
+        }
+
+        if let Ok(composition) = self.target_composition() {
+            if !composition.is_empty() {
+                let yields = self.isotope_yields().unwrap_or_default();
+                ui.collapsing("Target Composition", |ui| {
+                    egui::Grid::new("sps_target_composition_grid")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("Nuclide");
+                            ui.label("Areal Density");
+                            ui.label("");
+                            ui.label("Yield").on_hover_text("Counts from this nuclide during the same run, assuming the cross section of the reaction nuclide.");
+                            ui.end_row();
+
+                            for isotope in composition {
+                                ui.label(isotope.nuclide.name());
+                                ui.label(format!("{:.3e} atoms/cm^2", isotope.atoms_per_cm2));
+                                ui.label(format!("{:.3} µg/cm^2", isotope.micrograms_per_cm2));
+                                match yields.iter().find(|y| y.nuclide == isotope.nuclide) {
+                                    Some(isotope_yield) => ui.label(format!("{:.0} counts", isotope_yield.counts)),
+                                    None => ui.label(""),
+                                };
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+        }
+    }
+
+    /// Ejectile, beam energy, angle and states of the reaction, with the kinematics of each.
+    /// Frame selector of the cross section; switching converts the entered value with the
+    /// Jacobian at the spectrograph angle when the reaction is known.
+    fn cross_section_frame_ui(&mut self, ui: &mut egui::Ui) {
+        let previous = self.cross_section_frame;
+        egui::ComboBox::from_id_source("sps_cross_section_frame")
+            .selected_text(previous.name())
+            .width(50.0)
+            .show_ui(ui, |ui| {
+                for frame in Frame::ALL {
+                    ui.selectable_value(&mut self.cross_section_frame, frame, frame.name());
+                }
+            })
+            .response
+            .on_hover_text(
+                "Frame the cross sections are given in. CM needs the reaction kinematics.",
+            );
+        if self.cross_section_frame != previous {
+            if let Ok(Some(state)) = self.frame_kinematics() {
+                let factor = match self.cross_section_frame {
+                    Frame::Lab => state.jacobian,
+                    Frame::CenterOfMass => 1.0 / state.jacobian,
+                };
+                self.cross_section *= factor;
+                self.scaling.single_particle *= factor;
+            }
+        }
+    }
+
+    fn kinematics_ui(&mut self, ui: &mut egui::Ui) {
+        let reaction = self.reaction();
+        let kinematics = self.kinematics();
+        let field_setting = self.field_setting();
+        let focal_plane = self.focal_plane().unwrap_or_default();
+
+        egui::Grid::new("sps_reaction_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Ejectile:");
+                if let Some(NuclidePick::Nuclide(nuclide)) = nuclide_picker(ui, "sps_ejectile_picker", &mut self.reaction.ejectile, false) {
+                    self.reaction.ejectile = nuclide.name();
+                }
+                ui.end_row();
+
+                ui.label("Recoil:");
+                ui.horizontal(|ui| {
+                    match &reaction {
+                        Ok(Some(reaction)) => {
+                            ui.label(format!("{} | Q = {:.3} MeV", reaction.name(), reaction.q_value()));
+                        }
+                        Ok(None) => {
+                            ui.label("Choose an ejectile.");
+                        }
+                        Err(e) => {
+                            ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                        }
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Recoil Mass:");
+                ui.horizontal(|ui| {
+                    let mut manual = self.reaction.recoil_mass.is_some();
+                    if ui.checkbox(&mut manual, "Enter by hand")
+                        .on_hover_text("Atomic mass of the recoil for nuclides that are not in the nuclide table.")
+                        .changed()
+                    {
+                        self.reaction.recoil_mass = manual.then_some(match self.recoil_nucleus() {
+                            Ok(Some((_, a))) => a as f64,
+                            _ => 1.0,
+                        });
+                    }
+                    if let Some(mass) = &mut self.reaction.recoil_mass {
+                        ui.add(
+                            egui::DragValue::new(mass)
+                                .speed(0.001)
+                                .suffix(" u")
+                                .range(0.0..=f64::INFINITY)
+                        );
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Beam Energy:");
+                ui.add(
+                    egui::DragValue::new(&mut self.reaction.beam_energy)
+                        .speed(0.1)
+                        .suffix(" MeV")
+                        .range(0.0..=f64::INFINITY)
+                ).on_hover_text("Kinetic energy of the beam.");
+                ui.end_row();
+
+                ui.label("SPS Angle:");
+                ui.add(
+                    egui::DragValue::new(&mut self.reaction.angle)
+                        .speed(0.5)
+                        .suffix("°")
+                        .range(0.0..=180.0)
+                ).on_hover_text("Laboratory angle of the spectrograph.");
+                ui.end_row();
+
+                ui.label("Centred Ex:");
+                ui.add(
+                    egui::DragValue::new(&mut self.reaction.center_excitation)
+                        .speed(0.01)
+                        .suffix(" MeV")
+                        .range(0.0..=f64::INFINITY)
+                ).on_hover_text("Excitation energy put at the centre of the focal-plane detector.");
+                ui.end_row();
+
+                ui.label("Lab/CM:");
+                match self.frame_kinematics() {
+                    Ok(Some(state)) => {
+                        ui.label(format!("θcm = {:.2}° | dΩcm/dΩlab = {:.4}", state.cm_angle, state.jacobian))
+                            .on_hover_text("Centre-of-mass angle and Jacobian of the centred state; dσ/dΩlab = dΩcm/dΩlab · dσ/dΩcm.");
+                    }
+                    Ok(None) => {
+                        ui.label("");
+                    }
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                    }
+                }
+                ui.end_row();
+
+                ui.label("SPS Field:");
+                match &field_setting {
+                    Ok(Some(setting)) => {
+                        ui.strong(format!("{:.4} T | NMR {:.4} T", setting.field, setting.nmr_field))
+                            .on_hover_text(format!("Bρ = {:.4} T·m at the centre of the focal plane.", setting.rigidity));
+                    }
+                    Ok(None) => {
+                        ui.label("");
+                    }
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                    }
+                }
+                ui.end_row();
+            });
+
+        ui.collapsing("Spectrograph", |ui| {
+            egui::Grid::new("sps_spectrograph_grid")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Central Radius:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.spectrograph.radius)
+                            .speed(0.1)
+                            .suffix(" cm")
+                            .range(0.0..=f64::INFINITY)
+                    ).on_hover_text("Radius ρ₀ of the central trajectory, which lands at the centre of the focal plane.");
+                    ui.end_row();
+
+                    ui.label("Dispersion:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.spectrograph.dispersion)
+                            .speed(0.01)
+                            .range(0.0..=f64::INFINITY)
+                    ).on_hover_text("Focal-plane distance per change of radius, x = D (ρ − ρ₀).");
+                    ui.end_row();
+
+                    ui.label("Detector Length:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.spectrograph.detector_length)
+                            .speed(0.5)
+                            .suffix(" cm")
+                            .range(0.0..=f64::INFINITY)
+                    ).on_hover_text("Active length of the focal-plane detector, centred on the central trajectory.");
+                    ui.end_row();
+
+                    ui.label("NMR Calibration:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.spectrograph.field_calibration)
+                            .speed(0.001)
+                            .range(0.0..=f64::INFINITY)
+                    ).on_hover_text("NMR reading per central field of the dipole.");
+                    ui.end_row();
+                });
+        });
+
+        let kinematics = kinematics.unwrap_or_default();
+        egui::Grid::new("sps_kinematics_grid")
+            .num_columns(9)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Ex");
+                ui.label("Ejectile Energy");
+                ui.label("Momentum");
+                ui.label("dE/dθ")
+                    .on_hover_text("Kinematic factor: change of the ejectile energy with angle.");
+                ui.label("Recoil Energy");
+                ui.label("Recoil Angle");
+                ui.label("Bρ");
+                ui.label("Position")
+                    .on_hover_text("Focal-plane position from the detector centre.");
+                if ui.button("+").on_hover_text("Add a state").clicked() {
+                    let last = self
+                        .reaction
+                        .excitation_energies
+                        .last()
+                        .copied()
+                        .unwrap_or(-1.0);
+                    self.reaction.excitation_energies.push(last + 1.0);
+                }
+                ui.end_row();
+
+                let mut remove = None;
+                for (index, excitation) in self.reaction.excitation_energies.iter_mut().enumerate()
+                {
+                    ui.add(
+                        egui::DragValue::new(excitation)
+                            .speed(0.01)
+                            .suffix(" MeV")
+                            .range(0.0..=f64::INFINITY),
+                    )
+                    .on_hover_text("Excitation energy of the recoil.");
+                    match kinematics.get(index) {
+                        Some(Some(state)) => {
+                            ui.label(format!("{:.3} MeV", state.ejectile_energy));
+                            ui.label(format!("{:.3} MeV/c", state.ejectile_momentum));
+                            ui.label(format!("{:.2} keV/°", state.kinematic_factor));
+                            ui.label(format!("{:.3} MeV", state.recoil_energy));
+                            ui.label(format!("{:.2}°", state.recoil_angle));
+                            match focal_plane.get(index) {
+                                Some(Some(position)) => {
+                                    ui.label(format!("{:.4} T·m", position.rigidity));
+                                    let text = format!("{:.1} cm", position.position);
+                                    if position.on_detector {
+                                        ui.label(text);
+                                    } else {
+                                        ui.colored_label(ui.visuals().warn_fg_color, text)
+                                            .on_hover_text("Off the focal-plane detector.");
+                                    }
+                                }
+                                _ => {
+                                    ui.label("");
+                                    ui.label("");
+                                }
+                            }
+                        }
+                        Some(None) => {
+                            ui.colored_label(ui.visuals().warn_fg_color, "Not reachable");
+                            for _ in 0..6 {
+                                ui.label("");
+                            }
+                        }
+                        None => {
+                            for _ in 0..7 {
+                                ui.label("");
+                            }
+                        }
+                    }
+                    if ui.button("-").on_hover_text("Remove this state").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+                if let Some(index) = remove {
+                    self.reaction.excitation_energies.remove(index);
+                }
+            });
+    }
+
+    /// Ex range, overlap and states of the field-setting plan, with the proposed settings.
+    fn resolution_ui(&mut self, ui: &mut egui::Ui) {
+        let resolution = self.ex_resolution();
+        let settings = &mut self.resolution;
+
+        egui::Grid::new("sps_resolution_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Beam Energy Spread:");
+                ui.add(
+                    egui::DragValue::new(&mut settings.beam_spread)
+                        .speed(0.1)
+                        .suffix(" keV")
+                        .range(0.0..=f64::INFINITY),
+                )
+                .on_hover_text("FWHM of the beam energy.");
+                ui.end_row();
+
+                ui.label("Target Angle:");
+                ui.add(
+                    egui::DragValue::new(&mut settings.target_angle)
+                        .speed(0.5)
+                        .suffix("°")
+                        .range(-MAX_PATH_ANGLE..=MAX_PATH_ANGLE),
+                )
+                .on_hover_text("Angle of the target normal from the beam, positive toward the spectrograph. Also sets the paths under Target Energy Loss.");
+                ui.end_row();
+
+                ui.label("Kinematic Correction:");
+                let mut correction = settings.kinematic_correction * 100.0;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut correction)
+                            .speed(1.0)
+                            .suffix(" %")
+                            .range(0.0..=100.0),
+                    )
+                    .on_hover_text("Part of the kinematic broadening across the slit acceptance corrected by the focal-plane position or in the analysis.")
+                    .changed()
+                {
+                    settings.kinematic_correction = correction / 100.0;
+                }
+                ui.end_row();
+
+                ui.label("Position Resolution:");
+                ui.add(
+                    egui::DragValue::new(&mut settings.position_resolution)
+                        .speed(0.05)
+                        .suffix(" mm")
+                        .range(0.0..=f64::INFINITY),
+                )
+                .on_hover_text("FWHM position resolution of the focal-plane detector.");
+                ui.end_row();
+            });
+
+        match resolution {
+            Ok(Some(resolution)) => {
+                egui::Grid::new("sps_resolution_terms_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Term");
+                        ui.label("Ex FWHM");
+                        ui.label("Share").on_hover_text("Part of the squared total.");
+                        ui.end_row();
+
+                        for contribution in &resolution.contributions {
+                            ui.label(contribution.term.name());
+                            ui.label(format!("{:.1} keV", contribution.fwhm));
+                            ui.label(format!("{:.1} %", contribution.share * 100.0));
+                            ui.end_row();
+                        }
+
+                        ui.strong("Total");
+                        ui.strong(format!("{:.1} keV", resolution.fwhm))
+                            .on_hover_text(format!(
+                                "At Ex = {} MeV for {:.3} mg/cm^2 and {:.2}° horizontal acceptance, {:.2} keV/mm on the focal plane.",
+                                resolution.excitation, resolution.areal_mass, resolution.acceptance, resolution.energy_dispersion
+                            ));
+                        ui.label("");
+                        ui.end_row();
+                    });
+            }
+            Ok(None) => {
+                ui.label("Choose an ejectile for the resolution.");
+            }
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+            }
+        }
+    }
+
+    fn energy_loss_ui(&mut self, ui: &mut egui::Ui) {
+        let error_id = ui.id().with("sps_stopping_error");
+        let energy_loss = self.target_energy_loss();
+
+        egui::Grid::new("sps_stopping_tables_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for (label, table) in [("Beam Stopping:", &mut self.stopping.beam), ("Ejectile Stopping:", &mut self.stopping.ejectile)] {
+                    ui.label(label);
+                    ui.horizontal(|ui| {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if ui.button("Import SRIM...").on_hover_text("Stopping table written by the SRIM Stopping/Range module for this ion in the target.").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                                let file = path
+                                    .file_name()
+                                    .map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+                                let imported = std::fs::read_to_string(&path)
+                                    .map_err(|e| e.to_string())
+                                    .and_then(|text| crate::stopping::StoppingTable::import(&file, &text).map_err(|e| e.to_string()));
+                                match imported {
+                                    Ok(imported) => {
+                                        *table = Some(imported);
+                                        ui.data_mut(|data| data.remove::<String>(error_id));
+                                    }
+                                    Err(e) => ui.data_mut(|data| {
+                                        data.insert_temp(error_id, format!("Could not import {}: {e}", path.display()))
+                                    }),
+                                }
+                            }
+                        }
+                        match table {
+                            Some(imported) => {
+                                ui.label(format!("{} ({})", imported.file, imported.ion));
+                                if ui.button("Clear").on_hover_text("Use the built-in stopping powers again").clicked() {
+                                    *table = None;
+                                }
+                            }
+                            None => {
+                                ui.label("Built-in parametrization").on_hover_text(
+                                    "Electronic stopping only, a few per cent from PSTAR above 1 MeV/u but too low near and below the stopping maximum. Import a SRIM table for slower ions.",
+                                );
+                            }
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        if let Some(error) = ui.data(|data| data.get_temp::<String>(error_id)) {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        match energy_loss {
+            Ok(loss) => {
+                egui::Grid::new("sps_energy_loss_grid")
+                    .num_columns(6)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Ion");
+                        ui.label("Energy In");
+                        ui.label("Energy Out");
+                        ui.label("ΔE");
+                        ui.label("Straggling")
+                            .on_hover_text("FWHM of the Bohr energy straggling.");
+                        ui.label("Stopping")
+                            .on_hover_text("Stopping power at the entrance energy.");
+                        ui.end_row();
+
+                        for passage in std::iter::once(&loss.beam).chain(&loss.ejectile) {
+                            ui.label(&passage.ion).on_hover_text(format!(
+                                "{:.3} mg/cm^2 along its path, {}.",
+                                passage.path, passage.source
+                            ));
+                            ui.label(format!("{:.4} MeV", passage.energy_in));
+                            if passage.energy_out > 0.0 {
+                                ui.label(format!("{:.4} MeV", passage.energy_out));
+                            } else {
+                                ui.colored_label(ui.visuals().warn_fg_color, "Stopped");
+                            }
+                            ui.label(format!("{:.1} keV", passage.loss));
+                            ui.label(format!("{:.1} keV", passage.straggling));
+                            ui.label(format!("{:.2} keV/(mg/cm^2)", passage.stopping));
+                            ui.end_row();
+                        }
+                    });
+                ui.label(format!(
+                    "Mean beam energy in the target: {:.4} MeV, {:.3} mW deposited by the beam.",
+                    loss.mean_beam_energy, loss.power
+                ))
+                .on_hover_text(
+                    "The ejectile leaves the centred state at the upstream face of the target.",
+                );
+            }
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+            }
+        }
+    }
+
+    fn field_plan_ui(&mut self, ui: &mut egui::Ui) {
+        let plan = self.plan_field_settings();
+        let cross_section_unit = self.units.cross_section.symbol();
+
+        egui::Grid::new("sps_field_plan_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Ex Range:");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.field_plan.min_excitation)
+                            .speed(0.1)
+                            .suffix(" MeV")
+                            .range(0.0..=f64::INFINITY),
+                    );
+                    ui.label("to");
+                    ui.add(
+                        egui::DragValue::new(&mut self.field_plan.max_excitation)
+                            .speed(0.1)
+                            .suffix(" MeV")
+                            .range(0.0..=f64::INFINITY),
+                    );
+                })
+                .response
+                .on_hover_text("Excitation energies the field settings have to cover together.");
+                ui.end_row();
+
+                ui.label("Overlap:");
+                let mut overlap = self.field_plan.overlap * 100.0;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut overlap)
+                            .speed(1.0)
+                            .suffix(" %")
+                            .range(0.0..=90.0),
+                    )
+                    .on_hover_text("Part of the focal plane neighbouring settings share.")
+                    .changed()
+                {
+                    self.field_plan.overlap = overlap / 100.0;
+                }
+                ui.end_row();
+            });
+
+        egui::Grid::new("sps_field_plan_states_grid")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("State Ex");
+                ui.label("Cross Section");
+                if ui.button("+").on_hover_text("Add a state").clicked() {
+                    let last = self
+                        .field_plan
+                        .states
+                        .last()
+                        .map_or(-1.0, |state| state.excitation);
+                    self.field_plan.states.push(PlanState {
+                        excitation: last + 1.0,
+                        cross_section: self.cross_section,
+                    });
+                }
+                ui.end_row();
+
+                let mut remove = None;
+                for (index, state) in self.field_plan.states.iter_mut().enumerate() {
+                    ui.add(
+                        egui::DragValue::new(&mut state.excitation)
+                            .speed(0.01)
+                            .suffix(" MeV")
+                            .range(0.0..=f64::INFINITY),
+                    );
+                    ui.add(
+                        quantity_drag_value(&mut state.cross_section)
+                            .suffix(format!(" {cross_section_unit}")),
+                    );
+                    if ui.button("-").on_hover_text("Remove this state").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+                if let Some(index) = remove {
+                    self.field_plan.states.remove(index);
+                }
+            });
+
+        match plan {
+            Ok(Some(plan)) => {
+                egui::Grid::new("sps_field_plan_settings_grid")
+                    .num_columns(5)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Field");
+                        ui.label("NMR");
+                        ui.label("Ex on Detector");
+                        ui.label("Weakest State").on_hover_text("State the beam time is based on; the SPS cross section if no state falls on the detector.");
+                        ui.label("Beam Time");
+                        ui.end_row();
+
+                        for setting in &plan.settings {
+                            ui.label(format!("{:.4} T", setting.field));
+                            ui.label(format!("{:.4} T", setting.nmr_field));
+                            ui.label(format!("{:.2} - {:.2} MeV", setting.min_excitation, setting.max_excitation));
+                            match setting.weakest_state {
+                                Some(excitation) => ui.label(format!("{excitation} MeV | {} {cross_section_unit}", setting.cross_section)),
+                                None => ui.label(format!("{} {cross_section_unit}", setting.cross_section)),
+                            };
+                            ui.label(format!("{:.2} h", setting.run_time.hours));
+                            ui.end_row();
+                        }
+
+                        ui.strong("Total:");
+                        ui.label("");
+                        ui.label("");
+                        ui.label("");
+                        ui.strong(format!("{:.2} h | {:.2} shifts", plan.total.hours, plan.total.shifts));
+                        ui.end_row();
+                    });
+            }
+            Ok(None) => {
+                ui.label("Choose an ejectile and an Ex range to plan the field settings.");
+            }
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+            }
+        }
+    }
+
+    /// Angles and cross sections of an angular distribution with the beam time of each.
+    /// Optical-model potential and elastic angular distribution of the beam on the target.
+    fn elastic_ui(&mut self, ui: &mut egui::Ui) {
+        let elastic = match self.elastic_scattering() {
+            Ok(elastic) => elastic,
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                return;
+            }
+        };
+        let parameters = &elastic.parameters;
+        ui.label(format!(
+            "{} at {} MeV, k = {:.4} fm⁻¹, η = {:.4}, {} partial waves",
+            elastic.potential.name(),
+            elastic.beam_energy,
+            elastic.k,
+            elastic.eta,
+            elastic.partial_waves()
+        ));
+
+        egui::Grid::new("sps_optical_potential_grid")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Term");
+                ui.label("Depth (MeV)");
+                ui.label("r (fm)");
+                ui.label("a (fm)");
+                ui.end_row();
+                for (name, term) in [
+                    ("Real Volume", parameters.real),
+                    ("Imaginary Volume", parameters.volume),
+                    ("Imaginary Surface", parameters.surface),
+                    ("Spin-Orbit", parameters.spin_orbit),
+                ] {
+                    ui.label(name);
+                    ui.label(format!("{:.3}", term.depth));
+                    ui.label(format!("{:.3}", term.radius));
+                    ui.label(format!("{:.3}", term.diffuseness));
+                    ui.end_row();
+                }
+                ui.label("Coulomb");
+                ui.label("");
+                ui.label(format!("{:.3}", parameters.coulomb_radius));
+                ui.label("");
+                ui.end_row();
+            });
+
+        ui.separator();
+        egui::Grid::new("sps_elastic_curve_grid")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("θlab");
+                ui.label("θcm");
+                ui.label("Lab (mb/sr)");
+                ui.label("CM (mb/sr)");
+                ui.label("σ/σR");
+                ui.end_row();
+                for point in elastic.curve() {
+                    ui.label(format!("{:.1}°", point.angle));
+                    ui.label(format!("{:.2}°", point.cm_angle));
+                    ui.label(format!("{:.4e}", point.lab));
+                    ui.label(format!("{:.4e}", point.cm));
+                    ui.label(format!("{:.4}", point.ratio));
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// State of the DWBA cross section and its angular distribution.
+    fn transfer_ui(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("sps_transfer_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("State Ex:");
+                ui.add(
+                    egui::DragValue::new(&mut self.transfer.excitation)
+                        .speed(0.01)
+                        .suffix(" MeV")
+                        .range(0.0..=f64::INFINITY),
+                )
+                .on_hover_text("Excitation energy of the recoil state populated by the transfer.");
+                ui.end_row();
+
+                ui.label("Orbit:");
+                orbit_ui(ui, "sps_transfer_j", &mut self.transfer);
+                ui.end_row();
+
+                ui.label("Spectroscopic Factor:");
+                ui.add(
+                    egui::DragValue::new(&mut self.transfer.spectroscopic_factor)
+                        .speed(0.01)
+                        .range(0.0..=f64::INFINITY),
+                );
+                ui.end_row();
+
+                ui.label("Spins:");
+                ui.horizontal(|ui| {
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut self.transfer.initial_spin)
+                                .speed(0.05)
+                                .prefix("J_i = ")
+                                .range(0.0..=30.0)
+                                .custom_formatter(|value, _| spin_name(value)),
+                        )
+                        .changed()
+                    {
+                        self.transfer.initial_spin = (2.0 * self.transfer.initial_spin).round() / 2.0;
+                    }
+                    let mut final_spin = self.transfer.spin();
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut final_spin)
+                                .speed(0.05)
+                                .prefix("J_f = ")
+                                .range(0.0..=30.0)
+                                .custom_formatter(|value, _| spin_name(value)),
+                        )
+                        .changed()
+                    {
+                        self.transfer.final_spin = Some((2.0 * final_spin).round() / 2.0);
+                    }
+                    if self.transfer.final_spin.is_some() && ui.button("J_f = j").clicked() {
+                        self.transfer.final_spin = None;
+                    }
+                })
+                .response
+                .on_hover_text("Target spin J_i and spin J_f of the state, j of the transferred nucleon unless set. Stripping carries the statistical factor (2J_f + 1)/(2J_i + 1), pickup none.");
+                ui.end_row();
+            });
+
+        let transfer = match self.transfer() {
+            Ok(transfer) => transfer,
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                return;
+            }
+        };
+        ui.label(format!(
+            "{} + {}, binding {:.3} MeV in a {:.2} MeV Woods–Saxon well, {} partial waves",
+            transfer.entrance.name(),
+            transfer.exit.name(),
+            transfer.bound_state.binding,
+            transfer.bound_state.depth,
+            transfer.partial_waves
+        ));
+
+        ui.separator();
+        egui::Grid::new("sps_transfer_curve_grid")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("θlab");
+                ui.label("θcm");
+                ui.label("Lab (mb/sr)");
+                ui.label("CM (mb/sr)");
+                ui.end_row();
+                for point in transfer.curve() {
+                    ui.label(format!("{:.1}°", point.angle));
+                    ui.label(format!("{:.2}°", point.cm_angle));
+                    ui.label(format!("{:.4e}", point.lab));
+                    ui.label(format!("{:.4e}", point.cm));
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn imported_ui(&mut self, ui: &mut egui::Ui) {
+        let error_id = ui.id().with("sps_listing_error");
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("sps_listing_format")
+                .selected_text(self.imported.format.name())
+                .show_ui(ui, |ui| {
+                    for format in ListingFormat::ALL {
+                        ui.selectable_value(&mut self.imported.format, format, format.name());
+                    }
+                })
+                .response
+                .on_hover_text("Code that wrote the listing, used when it is not recognized from its contents.");
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Import...").clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    let file = path
+                        .file_name()
+                        .map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+                    let imported = std::fs::read_to_string(&path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|text| {
+                            let format = ListingFormat::detect(&text).unwrap_or(self.imported.format);
+                            ImportedCrossSections::import(&file, format, &text).map_err(|e| e.to_string())
+                        });
+                    match imported {
+                        Ok(imported) => {
+                            self.imported = imported;
+                            ui.data_mut(|data| data.remove::<String>(error_id));
+                        }
+                        Err(e) => ui.data_mut(|data| {
+                            data.insert_temp(error_id, format!("Could not import {}: {e}", path.display()))
+                        }),
+                    }
+                }
+            }
+            if !self.imported.file.is_empty() {
+                ui.label(format!("{} ({})", self.imported.file, self.imported.format.name()));
+            }
+        });
+        if let Some(error) = ui.data(|data| data.get_temp::<String>(error_id)) {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        if self.imported.states.is_empty() {
+            ui.label("Import the output of Ptolemy, FRESCO (fort.201) or DWUCK4 to use its angular distributions.");
+            return;
+        }
+
+        egui::Grid::new("sps_listing_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("State:");
+                let selected = self.imported.selected;
+                egui::ComboBox::from_id_source("sps_listing_state")
+                    .selected_text(self.imported.states.get(selected).map_or("", |state| &state.label))
+                    .show_ui(ui, |ui| {
+                        for (index, state) in self.imported.states.iter().enumerate() {
+                            ui.selectable_value(&mut self.imported.selected, index, &state.label);
+                        }
+                    });
+                ui.end_row();
+
+                if let Some(state) = self.imported.states.get_mut(self.imported.selected) {
+                    ui.label("State Ex:");
+                    ui.horizontal(|ui| {
+                        let mut excitation = state.excitation.unwrap_or(0.0);
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut excitation)
+                                    .speed(0.01)
+                                    .suffix(" MeV")
+                                    .range(0.0..=f64::INFINITY),
+                            )
+                            .on_hover_text("Excitation energy of the state, for the CM angle and the Jacobian at the spectrograph angle.")
+                            .changed()
+                        {
+                            state.excitation = Some(excitation);
+                        }
+                        if state.excitation.is_none() {
+                            ui.colored_label(ui.visuals().warn_fg_color, "not in the listing, enter it");
+                            if ui.button("Ground State").clicked() {
+                                state.excitation = Some(0.0);
+                            }
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+        let Ok(state) = self.imported.state() else {
+            return;
+        };
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .show(ui, |ui| {
+                egui::Grid::new("sps_listing_curve_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("θcm");
+                        ui.label("CM (mb/sr)");
+                        ui.end_row();
+                        for (angle, cross_section) in
+                            state.cm_angles.iter().zip(&state.cross_sections)
+                        {
+                            ui.label(format!("{angle:.2}°"));
+                            ui.label(format!("{cross_section:.4e}"));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn deck_ui(&mut self, ui: &mut egui::Ui) {
+        let deck = &mut self.deck;
+        egui::Grid::new("sps_deck_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Code:");
+                egui::ComboBox::from_id_source("sps_deck_code")
+                    .selected_text(deck.code.name())
+                    .show_ui(ui, |ui| {
+                        for code in DeckCode::ALL {
+                            ui.selectable_value(&mut deck.code, code, code.name());
+                        }
+                    });
+                ui.end_row();
+
+                for (label, id, potential) in [
+                    (
+                        "Entrance Potential:",
+                        "sps_deck_entrance",
+                        &mut deck.entrance,
+                    ),
+                    ("Exit Potential:", "sps_deck_exit", &mut deck.exit),
+                ] {
+                    ui.label(label);
+                    egui::ComboBox::from_id_source(id)
+                        .selected_text(potential.map_or("Global", |potential| potential.name()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(potential, None, "Global")
+                                .on_hover_text(
+                                    "The global potential of the particle in the channel.",
+                                );
+                            for choice in GlobalPotential::ALL {
+                                ui.selectable_value(potential, Some(choice), choice.name());
+                            }
+                        });
+                    ui.end_row();
+                }
+
+                ui.label("Spin-Orbit:");
+                ui.checkbox(
+                    &mut deck.spin_orbit,
+                    "Keep the spin-orbit terms of the optical potentials",
+                );
+                ui.end_row();
+
+                ui.label("CM Angles:");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut deck.min_angle)
+                            .speed(1.0)
+                            .suffix("°")
+                            .range(0.0..=180.0),
+                    );
+                    ui.label("to");
+                    ui.add(
+                        egui::DragValue::new(&mut deck.max_angle)
+                            .speed(1.0)
+                            .suffix("°")
+                            .range(0.0..=180.0),
+                    );
+                    ui.label("in steps of");
+                    ui.add(
+                        egui::DragValue::new(&mut deck.angle_step)
+                            .speed(0.1)
+                            .suffix("°")
+                            .range(0.1..=90.0),
+                    );
+                });
+                ui.end_row();
+            });
+
+        ui.separator();
+        let reaction = self.reaction().ok().flatten();
+        let vertex = reaction
+            .as_ref()
+            .and_then(|reaction| Vertex::of(reaction).ok());
+        let deck = &mut self.deck;
+        let mut remove = None;
+        egui::Grid::new("sps_deck_states_grid")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Ex");
+                ui.label("Orbit");
+                ui.label("S");
+                ui.label("Binding");
+                ui.label("");
+                ui.end_row();
+                for (index, state) in deck.states.iter_mut().enumerate() {
+                    ui.add(
+                        egui::DragValue::new(&mut state.excitation)
+                            .speed(0.01)
+                            .suffix(" MeV")
+                            .range(0.0..=f64::INFINITY),
+                    );
+                    orbit_ui(ui, ("sps_deck_j", index), state);
+                    ui.add(
+                        egui::DragValue::new(&mut state.spectroscopic_factor)
+                            .speed(0.01)
+                            .range(0.0..=f64::INFINITY),
+                    );
+                    match (&reaction, &vertex) {
+                        (Some(reaction), Some(vertex)) => ui.label(format!(
+                            "{:.3} MeV",
+                            vertex.binding(reaction, state.excitation)
+                        )),
+                        _ => ui.label("–"),
+                    };
+                    if ui.button("Remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            deck.states.remove(index);
+        }
+        if ui
+            .button("Add State")
+            .on_hover_text("Add the state of Transfer (DWBA).")
+            .clicked()
+        {
+            deck.states.push(self.transfer.clone());
+        }
+
+        let decks = match self.input_decks() {
+            Ok(decks) => decks,
+            Err(e) => {
+                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                return;
+            }
+        };
+        if decks.is_empty() {
+            return;
+        }
+        ui.separator();
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui
+            .button("Export...")
+            .on_hover_text("Write the decks into a folder.")
+            .clicked()
+        {
+            if let Some(directory) = rfd::FileDialog::new().pick_folder() {
+                let error_id = ui.id().with("sps_deck_error");
+                let written = decks.iter().try_for_each(|deck| {
+                    let path = directory.join(&deck.file_name);
+                    std::fs::write(&path, &deck.contents)
+                        .map_err(|e| format!("Could not write {}: {e}", path.display()))
+                });
+                match written {
+                    Ok(()) => ui.data_mut(|data| data.remove::<String>(error_id)),
+                    Err(e) => ui.data_mut(|data| data.insert_temp(error_id, e)),
+                }
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(error) = ui.data(|data| data.get_temp::<String>(ui.id().with("sps_deck_error")))
+        {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        for deck in decks {
+            ui.collapsing(deck.file_name, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut deck.contents.as_str()).code_editor(),
+                        );
+                    });
+            });
+        }
+    }
+
+    fn campaign_ui(&mut self, ui: &mut egui::Ui) {
+        let campaign = self.plan_campaign();
+        let counts = self.campaign_counts();
+        let cross_section_unit = self.units.cross_section.symbol();
+        let source = self.cross_section_source;
+
+        egui::Grid::new("sps_campaign_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Goal per Angle:");
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("sps_campaign_goal")
+                        .selected_text(self.campaign.goal.name())
+                        .show_ui(ui, |ui| {
+                            for goal in CampaignGoal::ALL {
+                                ui.selectable_value(&mut self.campaign.goal, goal, goal.name());
+                            }
+                        });
+                    if self.campaign.goal == CampaignGoal::Uncertainty {
+                        ui.add(
+                            egui::DragValue::new(&mut self.campaign.uncertainty)
+                                .speed(0.1)
+                                .suffix(" %")
+                                .range(0.0..=100.0),
+                        )
+                        .on_hover_text("Statistical uncertainty 1/√N of every angle.");
+                    }
+                    match &counts {
+                        Ok(counts) => ui.label(format!("{counts} counts")),
+                        Err(e) => ui.colored_label(ui.visuals().error_fg_color, e.to_string()),
+                    };
+                });
+                ui.end_row();
+
+                ui.label("Angle Change:");
+                ui.add(
+                    egui::DragValue::new(&mut self.campaign.overhead)
+                        .speed(0.1)
+                        .suffix(" h")
+                        .range(0.0..=f64::INFINITY),
+                )
+                .on_hover_text("Beam time lost moving the spectrograph to the next angle.");
+                ui.end_row();
+            });
+
+        let result = campaign.as_ref().ok().and_then(Option::as_ref);
+        egui::Grid::new("sps_campaign_angles_grid")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Angle");
+                ui.label("Cross Section");
+                ui.label("Beam Time");
+                if ui.button("+").on_hover_text("Add an angle").clicked() {
+                    let last = self.campaign.angles.last().map_or(0.0, |angle| angle.angle);
+                    self.campaign.angles.push(CampaignAngle {
+                        angle: (last + 5.0).min(180.0),
+                        cross_section: self.cross_section,
+                    });
+                }
+                ui.end_row();
+
+                let mut remove = None;
+                for (index, angle) in self.campaign.angles.iter_mut().enumerate() {
+                    ui.add(
+                        egui::DragValue::new(&mut angle.angle)
+                            .speed(0.5)
+                            .suffix("°")
+                            .range(0.0..=180.0),
+                    );
+                    let time = result.and_then(|result| result.angles.get(index));
+                    match (source, time) {
+                        (CrossSectionSource::Manual, _) => ui.add(
+                            quantity_drag_value(&mut angle.cross_section)
+                                .suffix(format!(" {cross_section_unit}")),
+                        ),
+                        (CrossSectionSource::Scaled, _) => {
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    quantity_drag_value(&mut angle.cross_section)
+                                        .suffix(format!(" {cross_section_unit}")),
+                                )
+                                .on_hover_text("Single-particle cross section σ_sp at this angle.");
+                                if let Some(time) = time {
+                                    ui.label(format!(
+                                        "→ {:.4e} {cross_section_unit}",
+                                        time.cross_section
+                                    ));
+                                }
+                            })
+                            .response
+                        }
+                        (_, Some(time)) => {
+                            ui.label(format!("{:.4e} {cross_section_unit}", time.cross_section))
+                        }
+                        (_, None) => ui.label(""),
+                    };
+                    match result.and_then(|result| result.angles.get(index)) {
+                        Some(time) => ui.label(format!("{:.2} h", time.run_time.hours)),
+                        None => ui.label(""),
+                    };
+                    if ui.button("-").on_hover_text("Remove this angle").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+                if let Some(index) = remove {
+                    self.campaign.angles.remove(index);
+                }
+
+                if let Some(result) = result {
+                    ui.label("Overhead:");
+                    ui.label("");
+                    ui.label(format!("{:.2} h", result.overhead.hours));
+                    ui.end_row();
+
+                    ui.strong("Total:");
+                    ui.label("");
+                    ui.strong(format!(
+                        "{:.2} h | {:.2} shifts",
+                        result.total.hours, result.total.shifts
+                    ));
+                    ui.end_row();
+                }
+            });
+
+        if let Err(e) = campaign {
+            ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+        }
+    }
+
+    /// Assay editor for every element of the target formula.
+    fn enrichment_ui(&mut self, ui: &mut egui::Ui, formula: &Formula) {
+        let mut elements: Vec<i32> = vec![];
+        for component in &formula.components {
+            if !elements.contains(&component.z) {
+                elements.push(component.z);
+            }
+        }
+
+        for z in elements {
+            let symbol = nuclide::element_symbol(z).unwrap_or("?");
+            let position = self
+                .target_assays
+                .iter()
+                .position(|assay| assay.z() == Ok(z));
+
+            let mut enriched = position.is_some();
+            if ui
+                .checkbox(&mut enriched, format!("Enriched {symbol}"))
+                .on_hover_text("Enter the isotopic composition from the supplier's assay.")
+                .changed()
+            {
+                match position {
+                    Some(index) => {
+                        self.target_assays.remove(index);
+                    }
+                    None => self.target_assays.push(Assay::natural(z)),
+                }
+            }
+
+            let Some(assay) = self
+                .target_assays
+                .iter_mut()
+                .find(|assay| assay.z() == Ok(z))
+            else {
+                continue;
+            };
+            egui::Grid::new(format!("sps_assay_grid_{z}"))
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for isotope in &mut assay.isotopes {
+                        ui.label(format!("{}{symbol}", isotope.a));
+                        ui.add(
+                            egui::DragValue::new(&mut isotope.percent)
+                                .speed(0.1)
+                                .suffix(" %")
+                                .range(0.0..=100.0),
+                        );
+                        ui.end_row();
+                    }
+
+                    ui.label("Total:");
+                    let total = assay.total();
+                    if (total - 100.0).abs() > 0.5 {
+                        ui.colored_label(ui.visuals().warn_fg_color, format!("{total:.2} %"))
+                            .on_hover_text("The abundances are normalized to their total.");
+                    } else {
+                        ui.label(format!("{total:.2} %"));
+                    }
+                    ui.end_row();
+                });
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::error::EstimateError;
+use crate::units::Unit;
+use eframe::egui::{self};
+
+/// Drag value whose speed follows the magnitude, so both µb/sr and atoms/cm^2 are usable.
+pub(super) fn quantity_drag_value(value: &mut f64) -> egui::DragValue<'_> {
+    let speed = (value.abs() * 1e-2).max(1e-2);
+    egui::DragValue::new(value)
+        .speed(speed)
+        .range(0.0..=f64::INFINITY)
+}
+
+/// Combo box choosing `unit`. `value` is converted to the new unit so the quantity stays the
+/// same, unless the conversion is not possible with the current inputs.
+pub(super) fn unit_selector<U: Unit>(
+    ui: &mut egui::Ui,
+    id_source: &str,
+    value: &mut f64,
+    unit: &mut U,
+    convert: impl Fn(f64, U, U) -> Result<f64, EstimateError>,
+) {
+    let previous = *unit;
+    egui::ComboBox::from_id_source(id_source)
+        .selected_text(unit.symbol())
+        .show_ui(ui, |ui| {
+            for &choice in U::ALL {
+                ui.selectable_value(unit, choice, choice.symbol());
+            }
+        });
+    if *unit != previous {
+        if let Ok(converted) = convert(*value, previous, *unit) {
+            *value = converted;
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Units the estimator inputs can be entered in.
+//!
+//! Each quantity is stored as the number the user typed together with its unit, and is
+//! converted to a base unit (cm^2/sr, atoms/cm^2, particles/s, sr) only when calculating.
+//! Conversions that need more information than the value itself, like a target thickness in
+//! nm, check those inputs and return an [`EstimateError`] instead of a meaningless number.
+
+use super::error::{require_positive, EstimateError};
+
+pub const ELEMENTARY_CHARGE: f64 = 1.6e-19; // C
+pub const AVOGADRO: f64 = 6.023e23; // 1/mol
+
+const BARN: f64 = 1e-24; // cm^2
+const NANOMETER: f64 = 1e-7; // cm
+
+/// A unit that can be picked from a fixed list.
+pub trait Unit: Copy + PartialEq + std::fmt::Debug + 'static {
+    const ALL: &'static [Self];
+
+    fn symbol(&self) -> &'static str;
+}
+
+/// Whether `s` spells `symbol`, also with `u` or `mu` for the micro sign µ. Case matters, so
+/// that `Mb/sr` is not read as millibarn.
+fn spells(symbol: &str, s: &str) -> bool {
+    symbol == s
+        || (symbol.contains('µ')
+            && ["u", "mu"]
+                .iter()
+                .any(|micro| symbol.replace('µ', micro) == s))
+}
+
+/// Parses a unit from its symbol, e.g. `"mb/sr"`, or `"ub/sr"` for µb/sr.
+fn parse_unit<U: Unit>(s: &str) -> Result<U, String> {
+    U::ALL
+        .iter()
+        .find(|unit| spells(unit.symbol(), s.trim()))
+        .copied()
+        .ok_or_else(|| {
+            let symbols: Vec<&str> = U::ALL.iter().map(Unit::symbol).collect();
+            format!(
+                "unknown unit '{s}', expected one of: {}",
+                symbols.join(", ")
+            )
+        })
+}
+
+/// A value in a given unit.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+pub struct Quantity<U> {
+    pub value: f64,
+    pub unit: U,
+}
+
+impl<U: Unit> std::fmt::Display for Quantity<U> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let magnitude = self.value.abs();
+        if magnitude != 0.0 && !(1e-3..1e6).contains(&magnitude) {
+            write!(f, "{:.3e} {}", self.value, self.unit.symbol())
+        } else {
+            write!(f, "{:.3} {}", self.value, self.unit.symbol())
+        }
+    }
+}
+
+macro_rules! impl_from_str {
+    ($unit:ty) => {
+        impl std::str::FromStr for $unit {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse_unit(s)
+            }
+        }
+    };
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum CrossSectionUnit {
+    BarnPerSr,
+    MillibarnPerSr,
+    #[default]
+    MicrobarnPerSr,
+    NanobarnPerSr,
+}
+
+impl Unit for CrossSectionUnit {
+    const ALL: &'static [Self] = &[
+        CrossSectionUnit::BarnPerSr,
+        CrossSectionUnit::MillibarnPerSr,
+        CrossSectionUnit::MicrobarnPerSr,
+        CrossSectionUnit::NanobarnPerSr,
+    ];
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            CrossSectionUnit::BarnPerSr => "b/sr",
+            CrossSectionUnit::MillibarnPerSr => "mb/sr",
+            CrossSectionUnit::MicrobarnPerSr => "µb/sr",
+            CrossSectionUnit::NanobarnPerSr => "nb/sr",
+        }
+    }
+}
+
+impl_from_str!(CrossSectionUnit);
+
+impl CrossSectionUnit {
+    fn cm2_per_sr(&self) -> f64 {
+        match self {
+            CrossSectionUnit::BarnPerSr => BARN,
+            CrossSectionUnit::MillibarnPerSr => 1e-3 * BARN,
+            CrossSectionUnit::MicrobarnPerSr => 1e-6 * BARN,
+            CrossSectionUnit::NanobarnPerSr => 1e-9 * BARN,
+        }
+    }
+
+    pub fn to_base(&self, value: f64) -> f64 {
+        value * self.cm2_per_sr()
+    }
+
+    pub fn from_base(&self, cm2_per_sr: f64) -> f64 {
+        cm2_per_sr / self.cm2_per_sr()
+    }
+}
+
+/// Target thickness units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ThicknessUnit {
+    #[default]
+    MicrogramPerCm2,
+    MilligramPerCm2,
+    AtomsPerCm2,
+    Nanometer,
+}
+
+impl Unit for ThicknessUnit {
+    const ALL: &'static [Self] = &[
+        ThicknessUnit::MicrogramPerCm2,
+        ThicknessUnit::MilligramPerCm2,
+        ThicknessUnit::AtomsPerCm2,
+        ThicknessUnit::Nanometer,
+    ];
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            ThicknessUnit::MicrogramPerCm2 => "µg/cm^2",
+            ThicknessUnit::MilligramPerCm2 => "mg/cm^2",
+            ThicknessUnit::AtomsPerCm2 => "atoms/cm^2",
+            ThicknessUnit::Nanometer => "nm",
+        }
+    }
+}
+
+impl_from_str!(ThicknessUnit);
+
+impl ThicknessUnit {
+    /// Formula units/cm^2 per unit of thickness for a target of the given molar mass (g/mol),
+    /// mass density (g/cm^3) and atoms per formula unit. The density is only needed for nm,
+    /// the atoms only for atoms/cm^2, which counts every atom of the target.
+    fn formula_units_per_cm2(
+        &self,
+        molar_mass: f64,
+        mass_density: f64,
+        atoms: f64,
+    ) -> Result<f64, EstimateError> {
+        let units_per_gram = AVOGADRO / require_positive("Target molar mass", molar_mass)?;
+        Ok(match self {
+            ThicknessUnit::MicrogramPerCm2 => 1e-6 * units_per_gram,
+            ThicknessUnit::MilligramPerCm2 => 1e-3 * units_per_gram,
+            ThicknessUnit::AtomsPerCm2 => 1.0 / require_positive("Atoms per formula unit", atoms)?,
+            ThicknessUnit::Nanometer => {
+                NANOMETER * require_positive("Target mass density", mass_density)? * units_per_gram
+            }
+        })
+    }
+
+    /// Formula units/cm^2 of a target `value` thick.
+    pub fn to_base(
+        &self,
+        value: f64,
+        molar_mass: f64,
+        mass_density: f64,
+        atoms: f64,
+    ) -> Result<f64, EstimateError> {
+        Ok(value * self.formula_units_per_cm2(molar_mass, mass_density, atoms)?)
+    }
+
+    pub fn from_base(
+        &self,
+        formula_units_per_cm2: f64,
+        molar_mass: f64,
+        mass_density: f64,
+        atoms: f64,
+    ) -> Result<f64, EstimateError> {
+        Ok(formula_units_per_cm2 / self.formula_units_per_cm2(molar_mass, mass_density, atoms)?)
+    }
+}
+
+/// Beam intensity units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum CurrentUnit {
+    /// Electrical current; one particle carries `charge_state` elementary charges.
+    #[default]
+    Nanoampere,
+    /// Particle nanoamperes, the electrical current divided by the charge state.
+    ParticleNanoampere,
+    ParticlesPerSecond,
+}
+
+impl Unit for CurrentUnit {
+    const ALL: &'static [Self] = &[
+        CurrentUnit::Nanoampere,
+        CurrentUnit::ParticleNanoampere,
+        CurrentUnit::ParticlesPerSecond,
+    ];
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            CurrentUnit::Nanoampere => "nA",
+            CurrentUnit::ParticleNanoampere => "pnA",
+            CurrentUnit::ParticlesPerSecond => "particles/s",
+        }
+    }
+}
+
+impl_from_str!(CurrentUnit);
+
+impl CurrentUnit {
+    fn particles_per_second(&self, charge_state: f64) -> Result<f64, EstimateError> {
+        Ok(match self {
+            CurrentUnit::Nanoampere => {
+                1e-9 / (require_positive("Charge state", charge_state)? * ELEMENTARY_CHARGE)
+            }
+            CurrentUnit::ParticleNanoampere => 1e-9 / ELEMENTARY_CHARGE,
+            CurrentUnit::ParticlesPerSecond => 1.0,
+        })
+    }
+
+    pub fn to_base(&self, value: f64, charge_state: f64) -> Result<f64, EstimateError> {
+        Ok(value * self.particles_per_second(charge_state)?)
+    }
+
+    pub fn from_base(
+        &self,
+        particles_per_second: f64,
+        charge_state: f64,
+    ) -> Result<f64, EstimateError> {
+        Ok(particles_per_second / self.particles_per_second(charge_state)?)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum SolidAngleUnit {
+    #[default]
+    Millisteradian,
+    Steradian,
+}
+
+impl Unit for SolidAngleUnit {
+    const ALL: &'static [Self] = &[SolidAngleUnit::Millisteradian, SolidAngleUnit::Steradian];
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            SolidAngleUnit::Millisteradian => "msr",
+            SolidAngleUnit::Steradian => "sr",
+        }
+    }
+}
+
+impl_from_str!(SolidAngleUnit);
+
+impl SolidAngleUnit {
+    fn sr(&self) -> f64 {
+        match self {
+            SolidAngleUnit::Millisteradian => 1e-3,
+            SolidAngleUnit::Steradian => 1.0,
+        }
+    }
+
+    pub fn to_base(&self, value: f64) -> f64 {
+        value * self.sr()
+    }
+
+    pub fn from_base(&self, sr: f64) -> f64 {
+        sr / self.sr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::assert_close;
+
+    #[test]
+    fn atoms_per_cm2_counts_every_atom_of_the_formula() {
+        // 3e18 atoms/cm^2 of CD2 are 1e18 formula units
+        let unit = ThicknessUnit::AtomsPerCm2;
+        assert_close(unit.to_base(3e18, 16.04, 1.0, 3.0).unwrap(), 1e18, 1e-9);
+        assert_close(unit.from_base(1e18, 16.04, 1.0, 3.0).unwrap(), 3e18, 1e-9);
+    }
+
+    #[test]
+    fn mass_thickness_is_converted_with_the_molar_mass() {
+        let unit = ThicknessUnit::MicrogramPerCm2;
+        let formula_units = unit.to_base(100.0, 208.0, 11.3, 1.0).unwrap();
+        assert_close(formula_units, 100e-6 * AVOGADRO / 208.0, 1e-9);
+        assert_close(
+            ThicknessUnit::MilligramPerCm2
+                .from_base(formula_units, 208.0, 11.3, 1.0)
+                .unwrap(),
+            0.1,
+            1e-9,
+        );
+        // 10 nm of 11.3 g/cm^3 are 11.3 µg/cm^2
+        assert_close(
+            ThicknessUnit::Nanometer
+                .to_base(10.0, 208.0, 11.3, 1.0)
+                .unwrap(),
+            unit.to_base(11.3, 208.0, 11.3, 1.0).unwrap(),
+            1e-9,
+        );
+        assert_eq!(
+            ThicknessUnit::Nanometer.to_base(10.0, 208.0, 0.0, 1.0),
+            Err(EstimateError::NotPositive {
+                parameter: "Target mass density",
+                value: 0.0
+            })
+        );
+    }
+
+    #[test]
+    fn electrical_current_is_divided_by_the_charge_state() {
+        let particles = 1e-9 / ELEMENTARY_CHARGE;
+        assert_close(
+            CurrentUnit::Nanoampere.to_base(1.0, 1.0).unwrap(),
+            particles,
+            1e-9,
+        );
+        assert_close(
+            CurrentUnit::Nanoampere.to_base(1.0, 2.0).unwrap(),
+            particles / 2.0,
+            1e-9,
+        );
+        assert_close(
+            CurrentUnit::ParticleNanoampere.to_base(1.0, 2.0).unwrap(),
+            particles,
+            1e-9,
+        );
+        assert_close(
+            CurrentUnit::ParticlesPerSecond
+                .from_base(particles, 2.0)
+                .unwrap(),
+            particles,
+            1e-9,
+        );
+        assert!(CurrentUnit::Nanoampere.to_base(1.0, 0.0).is_err());
+        assert_close(CrossSectionUnit::MillibarnPerSr.to_base(2.0), 2e-27, 1e-9);
+        assert_close(SolidAngleUnit::Millisteradian.from_base(0.004), 4.0, 1e-9);
+    }
+
+    #[test]
+    fn units_parse_from_their_symbols() {
+        assert_eq!("mb/sr".parse(), Ok(CrossSectionUnit::MillibarnPerSr));
+        assert_eq!("µg/cm^2".parse(), Ok(ThicknessUnit::MicrogramPerCm2));
+        assert_eq!("pnA".parse(), Ok(CurrentUnit::ParticleNanoampere));
+        assert!("xb/sr".parse::<CrossSectionUnit>().is_err());
+    }
+
+    #[test]
+    fn micro_units_parse_with_ascii_spellings() {
+        for s in ["µb/sr", "ub/sr", "mub/sr"] {
+            assert_eq!(s.parse(), Ok(CrossSectionUnit::MicrobarnPerSr), "{s}");
+        }
+        for s in ["µg/cm^2", "ug/cm^2", "mug/cm^2", " ug/cm^2 "] {
+            assert_eq!(s.parse(), Ok(ThicknessUnit::MicrogramPerCm2), "{s}");
+        }
+        assert_eq!("mb/sr".parse(), Ok(CrossSectionUnit::MillibarnPerSr));
+        assert_eq!("mg/cm^2".parse(), Ok(ThicknessUnit::MilligramPerCm2));
+        assert!("xb/sr".parse::<CrossSectionUnit>().is_err());
+        for s in ["Mb/sr", "MB/SR", "UB/SR"] {
+            assert!(s.parse::<CrossSectionUnit>().is_err(), "{s}");
+        }
+        assert!("PNA".parse::<CurrentUnit>().is_err());
+    }
+}